@@ -1,15 +1,20 @@
 use crate::cfg::otto::RetentionSpec;
 use crate::cfg::param::Value;
+use crate::cli::commands::diff::DiffCommand;
 use crate::cli::commands::history::HistoryCommand;
+use crate::cli::commands::rerun::{RerunCommand, resume_point};
 use crate::cli::commands::stats::StatsCommand;
-use crate::cli::parser::Task;
+use crate::cli::parser::{RunFlags, Task};
 use crate::cli::{CleanCommand, ConvertCommand, Parser};
-use crate::executor::{DagVisualizer, TaskScheduler, Workspace};
+use crate::executor::cancel::forward_signals;
+use crate::executor::output_cache::OutputCache;
+use crate::executor::{DagVisualizer, RunStatus, StateManager, TaskScheduler, Workspace};
+use crate::ports::StateStore;
 use eyre::{Report, Result};
 use log::info;
 use std::collections::HashMap;
 use std::env;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 // ============================================================================
@@ -59,6 +64,42 @@ pub fn extract_clean_params(values: &HashMap<String, Value>) -> CleanParams {
     }
 }
 
+/// Parameters for the Diff command, extracted from task values.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffParams {
+    pub run_a: i64,
+    pub run_b: i64,
+    pub threshold: f64,
+    pub min_seconds: f64,
+    pub logs: bool,
+    pub json: bool,
+}
+
+/// Extract Diff command parameters from task values.
+/// This is a pure function - no I/O, easily testable.
+pub fn extract_diff_params(values: &HashMap<String, Value>) -> Result<DiffParams> {
+    let run_id = |name: &str| match values.get(name) {
+        Some(Value::Item(s)) => s.parse().map_err(|_| eyre::eyre!("Invalid run id '{s}'")),
+        _ => Err(eyre::eyre!("Diff needs two run ids: otto Diff <RUN_A> <RUN_B>")),
+    };
+    let number = |name: &str, default: f64| match values.get(name) {
+        Some(Value::Item(s)) => s
+            .parse()
+            .map_err(|_| eyre::eyre!("Invalid --{} '{s}'", name.replace('_', "-"))),
+        _ => Ok(default),
+    };
+    let flag = |name: &str| matches!(values.get(name), Some(Value::Item(s)) if s == "true");
+
+    Ok(DiffParams {
+        run_a: run_id("run_a")?,
+        run_b: run_id("run_b")?,
+        threshold: number("threshold", 20.0)?,
+        min_seconds: number("min_seconds", 1.0)?,
+        logs: flag("logs"),
+        json: flag("json"),
+    })
+}
+
 /// Parameters for the History command, extracted from task values.
 #[derive(Debug, Clone, PartialEq)]
 pub struct HistoryParams {
@@ -67,6 +108,8 @@ pub struct HistoryParams {
     pub status: Option<String>,
     pub project: Option<String>,
     pub json: bool,
+    pub run: Option<i64>,
+    pub trace: Option<PathBuf>,
 }
 
 impl Default for HistoryParams {
@@ -77,6 +120,8 @@ impl Default for HistoryParams {
             status: None,
             project: None,
             json: false,
+            run: None,
+            trace: None,
         }
     }
 }
@@ -107,15 +152,48 @@ pub fn extract_history_params(values: &HashMap<String, Value>) -> HistoryParams
         .and_then(|v| if let Value::Item(s) = v { Some(s == "true") } else { None })
         .unwrap_or(false);
 
+    let run = values
+        .get("run")
+        .and_then(|v| if let Value::Item(s) = v { s.parse().ok() } else { None });
+
+    let trace = values
+        .get("trace")
+        .and_then(|v| if let Value::Item(s) = v { Some(PathBuf::from(s)) } else { None });
+
     HistoryParams {
         task_name,
         limit,
         status,
         project,
         json,
+        run,
+        trace,
     }
 }
 
+/// Parameters for the Rerun command, extracted from task values.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RerunParams {
+    pub run: Option<i64>,
+    pub failed: bool,
+}
+
+/// Extract Rerun command parameters from task values.
+/// This is a pure function - no I/O, easily testable.
+pub fn extract_rerun_params(values: &HashMap<String, Value>) -> Result<RerunParams> {
+    let run = match values.get("run") {
+        Some(Value::Item(s)) => Some(s.parse().map_err(|_| eyre::eyre!("Invalid run id '{s}'"))?),
+        _ => None,
+    };
+
+    let failed = values
+        .get("failed")
+        .and_then(|v| if let Value::Item(s) = v { Some(s == "true") } else { None })
+        .unwrap_or(false);
+
+    Ok(RerunParams { run, failed })
+}
+
 /// Parameters for the Stats command, extracted from task values.
 #[derive(Debug, Clone, PartialEq)]
 pub struct StatsParams {
@@ -176,6 +254,16 @@ pub fn find_tasks_by_name<'a>(tasks: &'a [Task], name: &str) -> Vec<&'a Task> {
     tasks.iter().filter(|task| task.name == name).collect()
 }
 
+/// Final status of a run given the scheduler result and whether it was cancelled.
+/// This is a pure function - no I/O, easily testable.
+pub fn final_run_status(result: &Result<()>, cancelled: bool) -> RunStatus {
+    match result {
+        _ if cancelled => RunStatus::Cancelled,
+        Ok(()) => RunStatus::Success,
+        Err(_) => RunStatus::Failed,
+    }
+}
+
 // ============================================================================
 // Application Code
 // ============================================================================
@@ -187,8 +275,11 @@ pub struct RuntimeConfig {
     pub tasks: Vec<Task>,
     pub hash: String,
     pub ottofile_path: Option<PathBuf>,
+    /// Command line otto was started with, recorded with the run
+    pub args: Vec<String>,
     pub jobs: usize,
     pub tui_mode: bool,
+    pub flags: RunFlags,
     pub retention: RetentionSpec,
 }
 
@@ -197,12 +288,15 @@ impl RuntimeConfig {
     pub fn from_parser(parser: &mut Parser) -> Result<Self> {
         let (tasks, hash, ottofile_path, jobs, tui_mode) = parser.parse()?;
         let retention = parser.retention();
+        let flags = parser.flags();
         Ok(Self {
             tasks,
             hash,
             ottofile_path,
+            args: parser.args().to_vec(),
             jobs,
             tui_mode,
+            flags,
             retention,
         })
     }
@@ -212,35 +306,48 @@ impl RuntimeConfig {
 pub async fn run(config: RuntimeConfig) -> Result<()> {
     info!("Running otto with {} tasks", config.tasks.len());
 
+    // Rerun replaces this run with the one it replays
+    if let Some(task) = find_tasks_by_name(&config.tasks, "Rerun").first() {
+        return execute_rerun_from_task(task, config.ottofile_path.as_deref()).await;
+    }
+    if config.flags.resume {
+        return execute_resume(config.ottofile_path.as_deref()).await;
+    }
+
     execute_tasks(
         config.tasks,
         config.hash,
         config.ottofile_path,
+        config.args,
         config.jobs,
         config.tui_mode,
+        config.flags,
         config.retention,
     )
     .await
 }
 
 /// Execute tasks based on configuration.
+#[allow(clippy::too_many_arguments)]
 pub async fn execute_tasks(
     tasks: Vec<Task>,
     hash: String,
     ottofile_path: Option<PathBuf>,
+    args: Vec<String>,
     jobs: usize,
     tui_mode: bool,
+    flags: RunFlags,
     retention: RetentionSpec,
 ) -> Result<(), Report> {
-    if tui_mode {
+    if tui_mode && !flags.dry_run {
         if !atty::is(atty::Stream::Stdout) {
             eprintln!("Warning: --tui requires a TTY, falling back to standard output");
-            return execute_with_terminal_output(tasks, hash, ottofile_path, jobs, retention).await;
+            return execute_with_terminal_output(tasks, hash, ottofile_path, args, jobs, flags, retention).await;
         }
 
-        execute_with_tui(tasks, hash, ottofile_path, jobs, retention).await
+        execute_with_tui(tasks, hash, ottofile_path, args, jobs, flags, retention).await
     } else {
-        execute_with_terminal_output(tasks, hash, ottofile_path, jobs, retention).await
+        execute_with_terminal_output(tasks, hash, ottofile_path, args, jobs, flags, retention).await
     }
 }
 
@@ -249,7 +356,9 @@ pub async fn execute_with_terminal_output(
     tasks: Vec<Task>,
     hash: String,
     ottofile_path: Option<PathBuf>,
+    args: Vec<String>,
     jobs: usize,
+    flags: RunFlags,
     retention: RetentionSpec,
 ) -> Result<(), Report> {
     if tasks.is_empty() {
@@ -268,6 +377,11 @@ pub async fn execute_with_terminal_output(
         return DagVisualizer::execute_command(graph_tasks[0]).await;
     }
 
+    let diff_tasks = find_tasks_by_name(&tasks, "Diff");
+    if !diff_tasks.is_empty() {
+        return execute_diff_from_task(diff_tasks[0]);
+    }
+
     let history_tasks = find_tasks_by_name(&tasks, "History");
     if !history_tasks.is_empty() {
         return execute_history_from_task(history_tasks[0]);
@@ -287,43 +401,66 @@ pub async fn execute_with_terminal_output(
     }
 
     let cwd = env::current_dir()?;
-    let workspace = Workspace::new(cwd).await?;
-    workspace.init().await?;
 
+    // Convert parser tasks to executor tasks
+    let executor_tasks: Vec<crate::executor::Task> =
+        execution_tasks.into_iter().map(crate::executor::Task::from).collect();
+
+    if flags.dry_run {
+        // Plan only: no run directory, no database records
+        let workspace = Workspace::new(cwd).await?.without_state_store();
+        let mut scheduler = TaskScheduler::new(
+            executor_tasks,
+            Arc::new(workspace),
+            crate::executor::workspace::ExecutionContext::new(),
+            jobs,
+            false,
+        )
+        .await?;
+        scheduler.set_force(flags.force);
+        scheduler.set_pty(flags.pty);
+        return scheduler.print_plan().await;
+    }
+
+    let mut workspace = Workspace::new(cwd).await?;
     let mut execution_context = crate::executor::workspace::ExecutionContext::new();
+    if let Some(resume) = &flags.resume_point {
+        workspace = workspace.resuming(resume.run_id, resume.timestamp);
+        execution_context.timestamp = resume.timestamp;
+    }
+    workspace.init().await?;
+
     execution_context.ottofile = ottofile_path;
     execution_context.hash = hash;
+    execution_context.args = args;
 
     // Save execution context to run directory
     workspace.save_execution_context(execution_context.clone()).await?;
 
-    // Convert parser tasks to executor tasks
-    let executor_tasks: Vec<crate::executor::Task> = execution_tasks
-        .into_iter()
-        .map(|parser_task| {
-            // Derive parent for subtasks (names with colons like "install:td")
-            let parent = if parser_task.name.contains(':') {
-                parser_task.name.split(':').next().map(|s| s.to_string())
-            } else {
-                None
-            };
-            crate::executor::Task::new(
-                parser_task.name,
-                parent,
-                parser_task.task_deps,
-                parser_task.file_deps,
-                parser_task.output_deps,
-                parser_task.envs,
-                parser_task.values,
-                parser_task.action,
-            )
-        })
-        .collect();
+    let workspace = Arc::new(workspace);
+    let mut scheduler = TaskScheduler::new(executor_tasks, workspace.clone(), execution_context, jobs, false).await?;
+    scheduler.set_keep_going(flags.keep_going);
+    scheduler.set_force(flags.force);
+    scheduler.set_pty(flags.pty);
+    scheduler.set_strip_ansi(flags.strip_ansi);
+    scheduler.set_output_mode(flags.output);
+    scheduler.set_reports(flags.reports.clone());
+    scheduler.set_reused_outputs(flags.reused_outputs.clone());
+    if let Some(resume) = &flags.resume_point {
+        scheduler.set_completed_before_resume(resume.completed_tasks.clone());
+    }
+    scheduler.set_verbosity(flags.verbosity);
+    if let Some(cache) = OutputCache::from_retention(&retention) {
+        scheduler.set_output_cache(cache);
+    }
 
-    let scheduler = TaskScheduler::new(executor_tasks, Arc::new(workspace), execution_context, jobs, false).await?;
+    // Ctrl-C / SIGTERM cancel the run instead of killing otto and orphaning task processes
+    let cancel = scheduler.cancel_handle();
+    tokio::spawn(forward_signals(cancel.clone()));
 
     // Execute all tasks, capturing result
     let result = scheduler.execute_all().await;
+    workspace.record_run_complete_in_db(final_run_status(&result, cancel.is_cancelled()));
 
     // Auto-prune runs even if tasks failed — failing CI jobs that never prune
     // are exactly the scenario that fills disks
@@ -339,7 +476,9 @@ pub async fn execute_with_tui(
     tasks: Vec<Task>,
     hash: String,
     ottofile_path: Option<PathBuf>,
+    args: Vec<String>,
     jobs: usize,
+    flags: RunFlags,
     retention: RetentionSpec,
 ) -> Result<(), Report> {
     use crate::tui::{TaskPane, TuiApp};
@@ -358,12 +497,17 @@ pub async fn execute_with_tui(
     }
 
     let cwd = env::current_dir()?;
-    let workspace = Workspace::new(cwd).await?;
+    let mut workspace = Workspace::new(cwd).await?;
+    let mut execution_context = crate::executor::workspace::ExecutionContext::new();
+    if let Some(resume) = &flags.resume_point {
+        workspace = workspace.resuming(resume.run_id, resume.timestamp);
+        execution_context.timestamp = resume.timestamp;
+    }
     workspace.init().await?;
 
-    let mut execution_context = crate::executor::workspace::ExecutionContext::new();
     execution_context.ottofile = ottofile_path;
     execution_context.hash = hash;
+    execution_context.args = args;
 
     // Save execution context to run directory
     workspace.save_execution_context(execution_context.clone()).await?;
@@ -375,32 +519,22 @@ pub async fn execute_with_tui(
     for parser_task in execution_tasks {
         let task_name = parser_task.name.clone();
 
-        let streams = crate::executor::output::TaskStreams::new(&task_name, &output_dir).await?;
-        task_streams_map.insert(task_name.clone(), streams);
+        // Interactive tasks write straight to the terminal, so they get no pane
+        if !parser_task.interactive {
+            let streams = crate::executor::output::TaskStreams::new(&task_name, &output_dir).await?;
+            task_streams_map.insert(task_name.clone(), streams);
+        }
 
-        // Derive parent for subtasks (names with colons like "install:td")
-        let parent = if parser_task.name.contains(':') {
-            parser_task.name.split(':').next().map(|s| s.to_string())
-        } else {
-            None
-        };
-        let executor_task = crate::executor::Task::new(
-            parser_task.name,
-            parent,
-            parser_task.task_deps,
-            parser_task.file_deps,
-            parser_task.output_deps,
-            parser_task.envs,
-            parser_task.values,
-            parser_task.action,
-        );
-        executor_tasks.push(executor_task);
+        executor_tasks.push(crate::executor::Task::from(parser_task));
     }
 
     // Initialize TUI
     let mut terminal = crate::tui::init_terminal().map_err(|e| eyre::eyre!("Failed to initialize TUI: {}", e))?;
 
     let mut app = TuiApp::new();
+    // Interactive tasks get the terminal to themselves while the TUI is suspended
+    let terminal_handoff = crate::executor::handoff::TerminalHandoff::new();
+    app.set_terminal_handoff(terminal_handoff.clone());
 
     // Create message broadcast channel for status updates (larger buffer for fast tasks)
     let (message_tx, _) = tokio::sync::broadcast::channel::<crate::executor::output::TaskMessage>(1000);
@@ -414,9 +548,10 @@ pub async fn execute_with_tui(
     }
 
     // Start scheduler in background with TUI mode enabled
+    let workspace = Arc::new(workspace);
     let mut scheduler = TaskScheduler::new(
         executor_tasks,
-        Arc::new(workspace),
+        workspace.clone(),
         execution_context,
         jobs,
         true, // tui_mode = true
@@ -425,6 +560,21 @@ pub async fn execute_with_tui(
 
     // Set message channel on scheduler for broadcasting status updates
     scheduler.set_message_channel(message_tx);
+    scheduler.set_terminal_handoff(terminal_handoff.clone());
+    scheduler.set_keep_going(flags.keep_going);
+    scheduler.set_force(flags.force);
+    scheduler.set_pty(flags.pty);
+    scheduler.set_strip_ansi(flags.strip_ansi);
+    scheduler.set_output_mode(flags.output);
+    scheduler.set_reports(flags.reports.clone());
+    scheduler.set_reused_outputs(flags.reused_outputs.clone());
+    if let Some(resume) = &flags.resume_point {
+        scheduler.set_completed_before_resume(resume.completed_tasks.clone());
+    }
+    scheduler.set_verbosity(flags.verbosity);
+    if let Some(cache) = OutputCache::from_retention(&retention) {
+        scheduler.set_output_cache(cache);
+    }
 
     // Pass the pre-created task streams to the scheduler
     scheduler.set_task_streams(task_streams_map);
@@ -434,14 +584,17 @@ pub async fn execute_with_tui(
         app.layout_mut().render(f, f.area());
     })?;
 
+    let cancel = scheduler.cancel_handle();
     let scheduler_handle = tokio::spawn(async move { scheduler.execute_all().await });
 
     let ctrl_c_pressed = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
     let ctrl_c_flag = ctrl_c_pressed.clone();
+    let ctrl_c_cancel = cancel.clone();
 
     tokio::spawn(async move {
         if tokio::signal::ctrl_c().await.is_ok() {
             ctrl_c_flag.store(true, std::sync::atomic::Ordering::SeqCst);
+            ctrl_c_cancel.interrupt();
         }
     });
 
@@ -449,6 +602,7 @@ pub async fn execute_with_tui(
 
     // Run TUI (blocks until user quits or Ctrl+C)
     let tui_result = app.run(&mut terminal);
+    terminal_handoff.close();
 
     // Always restore terminal, even on Ctrl+C or error
     if let Err(e) = crate::tui::restore_terminal(&mut terminal) {
@@ -458,6 +612,9 @@ pub async fn execute_with_tui(
     // Handle TUI errors
     tui_result.map_err(|e| eyre::eyre!("TUI error: {}", e))?;
 
+    // Tasks may still be shutting down; further signals escalate the cancellation
+    tokio::spawn(forward_signals(cancel.clone()));
+
     // Wait for scheduler to complete
     let result = match scheduler_handle.await {
         Ok(Ok(())) => Ok(()),
@@ -465,6 +622,8 @@ pub async fn execute_with_tui(
         Err(e) => Err(eyre::eyre!("Scheduler panicked: {}", e)),
     };
 
+    workspace.record_run_complete_in_db(final_run_status(&result, cancel.is_cancelled()));
+
     // Auto-prune runs even if tasks failed
     if let Ok(otto_home) = crate::executor::pruning::resolve_otto_home() {
         crate::executor::pruning::auto_prune(&otto_home, &retention).await;
@@ -491,6 +650,23 @@ pub async fn execute_clean_from_task(task: &Task) -> Result<(), Report> {
     Ok(())
 }
 
+/// Execute Diff command from a parsed task.
+pub fn execute_diff_from_task(task: &Task) -> Result<(), Report> {
+    let params = extract_diff_params(&task.values)?;
+
+    let diff_cmd = DiffCommand {
+        run_a: params.run_a,
+        run_b: params.run_b,
+        threshold: params.threshold,
+        min_seconds: params.min_seconds,
+        logs: params.logs,
+        json: params.json,
+    };
+    diff_cmd.execute()?;
+
+    Ok(())
+}
+
 /// Execute History command from a parsed task.
 pub fn execute_history_from_task(task: &Task) -> Result<(), Report> {
     let params = extract_history_params(&task.values);
@@ -501,12 +677,94 @@ pub fn execute_history_from_task(task: &Task) -> Result<(), Report> {
         status: params.status,
         project: params.project,
         json: params.json,
+        run: params.run,
+        trace: params.trace,
     };
     history_cmd.execute()?;
 
     Ok(())
 }
 
+/// Execute Rerun command from a parsed task: replay a recorded run with the
+/// command line and working directory it was started with.
+pub async fn execute_rerun_from_task(task: &Task, ottofile_path: Option<&Path>) -> Result<(), Report> {
+    let params = extract_rerun_params(&task.values)?;
+    let store = StateManager::try_new().ok_or_else(|| eyre::eyre!("No history database found, nothing to rerun"))?;
+
+    let rerun_cmd = RerunCommand {
+        run: params.run,
+        failed: params.failed,
+    };
+    let plan = rerun_cmd.plan(&store as &dyn StateStore, ottofile_path)?;
+
+    // Run directories are named after the second a run starts in, so a quick
+    // rerun must not start in the same second as the run it replays
+    while std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_secs()
+        <= plan.timestamp
+    {
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    }
+
+    if let Some(cwd) = &plan.cwd {
+        env::set_current_dir(cwd).map_err(|e| eyre::eyre!("Failed to enter {}: {}", cwd.display(), e))?;
+    }
+    eprintln!("Rerunning run {}: {}", plan.run_id, plan.args.join(" "));
+
+    let mut parser = Parser::new(plan.args)?;
+    let mut config = RuntimeConfig::from_parser(&mut parser)?;
+    config.flags.reused_outputs = plan.reused_outputs;
+
+    execute_tasks(
+        config.tasks,
+        config.hash,
+        config.ottofile_path,
+        config.args,
+        config.jobs,
+        config.tui_mode,
+        config.flags,
+        config.retention,
+    )
+    .await
+}
+
+/// Continue the last run of this project where it was interrupted (`otto --resume`).
+/// The run keeps its directory and database row; tasks that completed aren't run again.
+pub async fn execute_resume(ottofile_path: Option<&Path>) -> Result<(), Report> {
+    let store = StateManager::try_new().ok_or_else(|| eyre::eyre!("No history database found, nothing to resume"))?;
+
+    // A run killed together with otto still looks like it is running until reconciled
+    store.reconcile_stale_runs()?;
+    let resume = resume_point(&store as &dyn StateStore, ottofile_path)?;
+
+    if let Some(cwd) = &resume.cwd {
+        env::set_current_dir(cwd).map_err(|e| eyre::eyre!("Failed to enter {}: {}", cwd.display(), e))?;
+    }
+    eprintln!(
+        "Resuming run {} ({} task(s) already completed): {}",
+        resume.run_id,
+        resume.completed_tasks.len(),
+        resume.args.join(" ")
+    );
+
+    let mut parser = Parser::new(resume.args.clone())?;
+    let mut config = RuntimeConfig::from_parser(&mut parser)?;
+    config.flags.resume_point = Some(resume);
+
+    execute_tasks(
+        config.tasks,
+        config.hash,
+        config.ottofile_path,
+        config.args,
+        config.jobs,
+        config.tui_mode,
+        config.flags,
+        config.retention,
+    )
+    .await
+}
+
 /// Execute Stats command from a parsed task.
 pub fn execute_stats_from_task(task: &Task) -> Result<(), Report> {
     let params = extract_stats_params(&task.values);
@@ -539,6 +797,15 @@ pub fn execute_history_command(args: &[String]) -> Result<(), Report> {
     Ok(())
 }
 
+/// Execute Diff subcommand from CLI args.
+pub fn execute_diff_command(args: &[String]) -> Result<(), Report> {
+    use clap::Parser;
+
+    let diff_cmd = DiffCommand::parse_from(args);
+    diff_cmd.execute()?;
+    Ok(())
+}
+
 /// Execute Convert subcommand from CLI args.
 pub fn execute_convert_command(args: &[String]) -> Result<(), Report> {
     use clap::Parser;
@@ -577,8 +844,13 @@ mod tests {
             tasks: vec![],
             hash: "abc123".to_string(),
             ottofile_path: Some(PathBuf::from("/tmp/otto.yml")),
+            args: vec!["otto".to_string(), "build".to_string()],
             jobs: 4,
             tui_mode: false,
+            flags: RunFlags {
+                keep_going: true,
+                ..Default::default()
+            },
             retention: crate::cfg::otto::RetentionSpec::default(),
         };
 
@@ -587,9 +859,24 @@ mod tests {
         assert_eq!(config.ottofile_path, Some(PathBuf::from("/tmp/otto.yml")));
         assert_eq!(config.jobs, 4);
         assert!(!config.tui_mode);
+        assert!(config.flags.keep_going);
+        assert!(!config.flags.dry_run);
         assert_eq!(config.retention, crate::cfg::otto::RetentionSpec::default());
     }
 
+    #[test]
+    fn test_final_run_status() {
+        assert_eq!(final_run_status(&Ok(()), false), RunStatus::Success);
+        assert_eq!(
+            final_run_status(&Err(eyre::eyre!("Task a failed")), false),
+            RunStatus::Failed
+        );
+        assert_eq!(
+            final_run_status(&Err(eyre::eyre!("Run cancelled")), true),
+            RunStatus::Cancelled
+        );
+    }
+
     // =========================================================================
     // CleanParams Tests
     // =========================================================================
@@ -738,7 +1025,11 @@ mod tests {
         values.insert("status".to_string(), Value::Item("passed".to_string()));
         values.insert("project".to_string(), Value::Item("my-proj".to_string()));
         values.insert("json".to_string(), Value::Item("true".to_string()));
+        values.insert("run".to_string(), Value::Item("42".to_string()));
+        values.insert("trace".to_string(), Value::Item("trace.json".to_string()));
         let params = extract_history_params(&values);
+        assert_eq!(params.run, Some(42));
+        assert_eq!(params.trace, Some(PathBuf::from("trace.json")));
         assert_eq!(params.task_name, Some("test".to_string()));
         assert_eq!(params.limit, 100);
         assert_eq!(params.status, Some("passed".to_string()));
@@ -746,6 +1037,57 @@ mod tests {
         assert!(params.json);
     }
 
+    // =========================================================================
+    // DiffParams Tests
+    // =========================================================================
+
+    #[test]
+    fn test_extract_diff_params() {
+        let mut values = HashMap::new();
+        values.insert("run_a".to_string(), Value::Item("41".to_string()));
+        assert!(extract_diff_params(&values).is_err());
+
+        values.insert("run_b".to_string(), Value::Item("42".to_string()));
+        values.insert("logs".to_string(), Value::Item("true".to_string()));
+        assert_eq!(
+            extract_diff_params(&values).unwrap(),
+            DiffParams {
+                run_a: 41,
+                run_b: 42,
+                threshold: 20.0,
+                min_seconds: 1.0,
+                logs: true,
+                json: false
+            }
+        );
+
+        values.insert("threshold".to_string(), Value::Item("lots".to_string()));
+        assert!(extract_diff_params(&values).is_err());
+    }
+
+    // =========================================================================
+    // RerunParams Tests
+    // =========================================================================
+
+    #[test]
+    fn test_extract_rerun_params() {
+        let mut values = HashMap::new();
+        assert_eq!(extract_rerun_params(&values).unwrap(), RerunParams::default());
+
+        values.insert("run".to_string(), Value::Item("42".to_string()));
+        values.insert("failed".to_string(), Value::Item("true".to_string()));
+        assert_eq!(
+            extract_rerun_params(&values).unwrap(),
+            RerunParams {
+                run: Some(42),
+                failed: true
+            }
+        );
+
+        values.insert("run".to_string(), Value::Item("latest".to_string()));
+        assert!(extract_rerun_params(&values).is_err());
+    }
+
     // =========================================================================
     // StatsParams Tests
     // =========================================================================
@@ -823,6 +1165,24 @@ mod tests {
             values: HashMap::new(),
             action: String::new(),
             hash: String::new(),
+            timeout: None,
+            timeout_grace: None,
+            retry: None,
+            fingerprint: crate::cfg::task::Fingerprint::default(),
+            finally: false,
+            handles_failure_of: vec![],
+            service: false,
+            ready: None,
+            dir: None,
+            env_clear: false,
+            env_passthrough: Vec::new(),
+            path_prepend: Vec::new(),
+            interpreter: None,
+            allow_failure: false,
+            success_codes: Vec::new(),
+            interactive: false,
+            pty: false,
+            outputs: std::collections::BTreeMap::new(),
         }
     }
 
@@ -928,6 +1288,8 @@ mod tests {
             status: None,
             project: None,
             json: false,
+            run: None,
+            trace: None,
         };
         let b = HistoryParams {
             task_name: Some("test".to_string()),
@@ -0,0 +1,141 @@
+use eyre::{Result, eyre};
+use serde::{Deserialize, Deserializer, Serializer};
+use std::time::Duration;
+
+/// Parse a human-friendly duration such as `90`, `30s`, `10m`, `1h30m` or `500ms`.
+///
+/// A bare number is interpreted as seconds.
+pub fn parse_duration(input: &str) -> Result<Duration> {
+    let s = input.trim();
+    if s.is_empty() {
+        return Err(eyre!("Invalid duration '{}': empty value", input));
+    }
+
+    if let Ok(secs) = s.parse::<u64>() {
+        return Ok(Duration::from_secs(secs));
+    }
+
+    let mut total = Duration::ZERO;
+    let mut rest = s;
+    while !rest.is_empty() {
+        let digits_end = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+        if digits_end == 0 {
+            return Err(eyre!("Invalid duration '{}': expected a number before the unit", input));
+        }
+        let amount: u64 = rest[..digits_end]
+            .parse()
+            .map_err(|_| eyre!("Invalid duration '{}': number out of range", input))?;
+        rest = &rest[digits_end..];
+
+        let unit_end = rest.find(|c: char| c.is_ascii_digit()).unwrap_or(rest.len());
+        let unit = &rest[..unit_end];
+        rest = &rest[unit_end..];
+
+        total += match unit {
+            "ms" => Duration::from_millis(amount),
+            "s" => Duration::from_secs(amount),
+            "m" => Duration::from_secs(amount * 60),
+            "h" => Duration::from_secs(amount * 3600),
+            "d" => Duration::from_secs(amount * 86400),
+            _ => {
+                return Err(eyre!(
+                    "Invalid duration '{}': unknown unit '{}' (expected ms, s, m, h or d)",
+                    input,
+                    unit
+                ));
+            }
+        };
+    }
+
+    Ok(total)
+}
+
+/// Format a duration in the same compact notation accepted by `parse_duration`
+#[must_use]
+pub fn format_duration(duration: Duration) -> String {
+    let millis = duration.subsec_millis();
+    let mut secs = duration.as_secs();
+
+    if secs == 0 && millis > 0 {
+        return format!("{millis}ms");
+    }
+
+    let mut out = String::new();
+    for (unit, size) in [("d", 86400), ("h", 3600), ("m", 60)] {
+        if secs >= size {
+            out.push_str(&format!("{}{}", secs / size, unit));
+            secs %= size;
+        }
+    }
+    if secs > 0 || out.is_empty() {
+        out.push_str(&format!("{secs}s"));
+    }
+    out
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum DurationRepr {
+    Seconds(u64),
+    Text(String),
+}
+
+/// Deserialize an optional duration given either as a number of seconds or as a string like `10m`
+pub fn deserialize_option_duration<'de, D>(deserializer: D) -> Result<Option<Duration>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Option::<DurationRepr>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(DurationRepr::Seconds(secs)) => Ok(Some(Duration::from_secs(secs))),
+        Some(DurationRepr::Text(text)) => parse_duration(&text).map(Some).map_err(serde::de::Error::custom),
+    }
+}
+
+/// Serialize an optional duration using the compact `format_duration` notation
+pub fn serialize_option_duration<S>(duration: &Option<Duration>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match duration {
+        Some(d) => serializer.serialize_str(&format_duration(*d)),
+        None => serializer.serialize_none(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration_units() {
+        assert_eq!(parse_duration("90").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_duration("30s").unwrap(), Duration::from_secs(30));
+        assert_eq!(parse_duration("10m").unwrap(), Duration::from_secs(600));
+        assert_eq!(parse_duration("2h").unwrap(), Duration::from_secs(7200));
+        assert_eq!(parse_duration("1d").unwrap(), Duration::from_secs(86400));
+        assert_eq!(parse_duration("500ms").unwrap(), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_parse_duration_compound() {
+        assert_eq!(parse_duration("1h30m").unwrap(), Duration::from_secs(5400));
+        assert_eq!(parse_duration("1m30s").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_duration(" 5m ").unwrap(), Duration::from_secs(300));
+    }
+
+    #[test]
+    fn test_parse_duration_invalid() {
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("m").is_err());
+        assert!(parse_duration("10x").is_err());
+        assert!(parse_duration("ten minutes").is_err());
+    }
+
+    #[test]
+    fn test_format_duration_roundtrip() {
+        for s in ["30s", "10m", "1h30m", "500ms", "1d2h", "0s"] {
+            assert_eq!(format_duration(parse_duration(s).unwrap()), s);
+        }
+    }
+}
@@ -1,4 +1,5 @@
 pub mod config;
+pub mod duration;
 pub mod env;
 pub mod error;
 pub mod otto;
@@ -52,6 +52,10 @@ fn default_prune_interval_hours() -> u64 {
     24
 }
 
+fn default_max_cache_mb() -> u64 {
+    1024
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
 pub struct RetentionSpec {
     /// Delete runs older than this many days (default: 30)
@@ -73,6 +77,10 @@ pub struct RetentionSpec {
     /// Minimum hours between auto-prune runs (default: 24)
     #[serde(default = "default_prune_interval_hours")]
     pub prune_interval_hours: u64,
+
+    /// Maximum size of the local output cache in MB; 0 disables it (default: 1024)
+    #[serde(default = "default_max_cache_mb")]
+    pub max_cache_mb: u64,
 }
 
 impl Default for RetentionSpec {
@@ -83,10 +91,58 @@ impl Default for RetentionSpec {
             keep_failed: default_keep_failed(),
             auto_prune: default_auto_prune(),
             prune_interval_hours: default_prune_interval_hours(),
+            max_cache_mb: default_max_cache_mb(),
         }
     }
 }
 
+/// How much terminal output a run produces
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    /// Hide output from successful tasks; replay it only for tasks that fail
+    Quiet,
+    /// Stream task output and print start/finish messages
+    #[default]
+    Normal,
+    /// Also print scheduler decisions and resolved env values
+    Verbose,
+}
+
+impl Verbosity {
+    /// Map an `otto.verbosity` level: 0 is quiet, 1 is normal, 2 or more is verbose
+    pub fn from_level(level: u8) -> Self {
+        match level {
+            0 => Verbosity::Quiet,
+            1 => Verbosity::Normal,
+            _ => Verbosity::Verbose,
+        }
+    }
+
+    /// Resolve `--quiet`/`--verbose` against the ottofile setting; the flags win
+    pub fn resolve(quiet: bool, verbose: bool, level: u8) -> Self {
+        if quiet {
+            Verbosity::Quiet
+        } else if verbose {
+            Verbosity::Verbose
+        } else {
+            Verbosity::from_level(level)
+        }
+    }
+}
+
+/// How task output is shown on the terminal (`--output`, `otto.output`)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputMode {
+    /// Stream lines as they arrive, each prefixed with its task name
+    #[default]
+    Prefixed,
+    /// Hold each task's output and print it as one block when the task finishes
+    Grouped,
+    /// Stream lines without a prefix, for single-task runs
+    Raw,
+}
+
 #[must_use]
 pub fn default_otto() -> OttoSpec {
     OttoSpec {
@@ -99,6 +155,10 @@ pub fn default_otto() -> OttoSpec {
         verbosity: default_verbosity(),
         envs: HashMap::new(),
         retention: RetentionSpec::default(),
+        finally: Vec::new(),
+        interpreters: HashMap::new(),
+        strip_ansi: false,
+        output: OutputMode::default(),
     }
 }
 
@@ -130,6 +190,22 @@ pub struct OttoSpec {
 
     #[serde(default)]
     pub retention: RetentionSpec,
+
+    /// Tasks that always run once their dependencies finish, even after a failure
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub finally: Vec<String>,
+
+    /// Commands for shebang interpreters, keyed by program name (e.g. `deno: deno run -A`)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub interpreters: HashMap<String, String>,
+
+    /// Remove ANSI escape sequences (colors, cursor movement) from task log files
+    #[serde(default)]
+    pub strip_ansi: bool,
+
+    /// How task output is shown on the terminal: prefixed, grouped or raw
+    #[serde(default)]
+    pub output: OutputMode,
 }
 
 impl Default for OttoSpec {
@@ -144,6 +220,10 @@ impl Default for OttoSpec {
             verbosity: default_verbosity(),
             envs: HashMap::new(),
             retention: RetentionSpec::default(),
+            finally: Vec::new(),
+            interpreters: HashMap::new(),
+            strip_ansi: false,
+            output: OutputMode::default(),
         }
     }
 }
@@ -160,6 +240,7 @@ mod tests {
         assert_eq!(spec.keep_failed, 60);
         assert!(spec.auto_prune);
         assert_eq!(spec.prune_interval_hours, 24);
+        assert_eq!(spec.max_cache_mb, 1024);
     }
 
     #[test]
@@ -188,6 +269,7 @@ keep_last: 3
 keep_failed: 14
 auto_prune: false
 prune_interval_hours: 12
+max_cache_mb: 256
 "#;
         let spec: RetentionSpec = serde_yaml::from_str(yaml).unwrap();
         assert_eq!(spec.keep_days, 7);
@@ -195,6 +277,7 @@ prune_interval_hours: 12
         assert_eq!(spec.keep_failed, 14);
         assert!(!spec.auto_prune);
         assert_eq!(spec.prune_interval_hours, 12);
+        assert_eq!(spec.max_cache_mb, 256);
     }
 
     #[test]
@@ -212,6 +295,20 @@ retention:
         assert_eq!(spec.retention.keep_failed, 60); // default
     }
 
+    #[test]
+    fn test_otto_spec_finally() {
+        let spec: OttoSpec = serde_yaml::from_str("finally: [db-down]").unwrap();
+        assert_eq!(spec.finally, vec!["db-down"]);
+        assert!(default_otto().finally.is_empty());
+    }
+
+    #[test]
+    fn test_otto_spec_interpreters() {
+        let spec: OttoSpec = serde_yaml::from_str("interpreters:\n  deno: deno run -A").unwrap();
+        assert_eq!(spec.interpreters["deno"], "deno run -A");
+        assert!(default_otto().interpreters.is_empty());
+    }
+
     #[test]
     fn test_otto_spec_without_retention() {
         let yaml = "name: test-project";
@@ -227,9 +324,25 @@ retention:
             keep_failed: 14,
             auto_prune: false,
             prune_interval_hours: 12,
+            max_cache_mb: 0,
         };
         let yaml = serde_yaml::to_string(&spec).unwrap();
         let deserialized: RetentionSpec = serde_yaml::from_str(&yaml).unwrap();
         assert_eq!(spec, deserialized);
     }
+
+    #[test]
+    fn test_verbosity_resolution() {
+        assert_eq!(Verbosity::from_level(0), Verbosity::Quiet);
+        assert_eq!(Verbosity::from_level(1), Verbosity::Normal);
+        assert_eq!(Verbosity::from_level(3), Verbosity::Verbose);
+
+        assert_eq!(Verbosity::resolve(true, false, 2), Verbosity::Quiet);
+        assert_eq!(Verbosity::resolve(false, true, 0), Verbosity::Verbose);
+        assert_eq!(Verbosity::resolve(false, false, 0), Verbosity::Quiet);
+        assert_eq!(
+            Verbosity::resolve(false, false, default_otto().verbosity),
+            Verbosity::Normal
+        );
+    }
 }
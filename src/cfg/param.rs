@@ -1,13 +1,15 @@
 //#![allow(unused_imports, unused_variables, dead_code)]
 
 use eyre::Result;
+use indexmap::IndexMap;
 use serde::de::{Deserializer, Error, MapAccess, SeqAccess, Visitor};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt;
 use std::vec::Vec;
 
-pub type ParamSpecs = HashMap<String, ParamSpec>;
+/// A task's params in the order the ottofile declares them, which is the order of positionals
+pub type ParamSpecs = IndexMap<String, ParamSpec>;
 
 #[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
 pub struct ParamSpec {
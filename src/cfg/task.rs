@@ -4,11 +4,13 @@ use eyre::{Result, eyre};
 use serde::de::{Deserializer, MapAccess, Visitor};
 use serde::ser::{SerializeMap, Serializer};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 use std::vec::Vec;
 
+use crate::cfg::duration::{deserialize_option_duration, format_duration, serialize_option_duration};
 use crate::cfg::param::{ParamSpecs, deserialize_param_map};
 
 pub type TaskSpecs = HashMap<String, TaskSpec>;
@@ -71,6 +73,227 @@ pub struct ForeachSpec {
     pub max_items: usize,
 }
 
+// ============================================================================
+// RetrySpec - Automatic retries for flaky tasks
+// ============================================================================
+
+fn default_attempts() -> u32 {
+    3
+}
+
+/// How the delay between retry attempts grows
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Backoff {
+    /// Wait the same delay before every retry
+    #[default]
+    Fixed,
+    /// Double the delay after every failed attempt
+    Exponential,
+}
+
+/// Configuration for retrying a failed task
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct RetrySpec {
+    /// Total number of attempts, including the first run (default: 3)
+    #[serde(default = "default_attempts")]
+    pub attempts: u32,
+
+    /// Delay before the first retry (e.g. "5s"); no delay when omitted
+    #[serde(
+        default,
+        deserialize_with = "deserialize_option_duration",
+        serialize_with = "serialize_option_duration",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub delay: Option<Duration>,
+
+    /// How the delay grows between attempts (default: fixed)
+    #[serde(default)]
+    pub backoff: Backoff,
+
+    /// Only retry when the task exits with one of these codes (default: any failure)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub on_exit_codes: Vec<i32>,
+}
+
+impl Default for RetrySpec {
+    fn default() -> Self {
+        Self {
+            attempts: default_attempts(),
+            delay: None,
+            backoff: Backoff::default(),
+            on_exit_codes: Vec::new(),
+        }
+    }
+}
+
+impl RetrySpec {
+    /// Delay to wait before the given retry attempt (attempt 2 is the first retry)
+    #[must_use]
+    pub fn delay_before(&self, attempt: u32) -> Duration {
+        let base = self.delay.unwrap_or_default();
+        match self.backoff {
+            Backoff::Fixed => base,
+            Backoff::Exponential => base.saturating_mul(2u32.saturating_pow(attempt.saturating_sub(2))),
+        }
+    }
+
+    /// Whether a failure with the given exit code should be retried
+    #[must_use]
+    pub fn should_retry(&self, exit_code: Option<i32>) -> bool {
+        self.on_exit_codes.is_empty() || exit_code.is_some_and(|code| self.on_exit_codes.contains(&code))
+    }
+}
+
+// ============================================================================
+// Fingerprint - How up-to-date checks compare inputs against outputs
+// ============================================================================
+
+/// Strategy used to decide whether a task with inputs/outputs can be skipped
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Fingerprint {
+    /// Compare the newest input mtime against the oldest output mtime
+    #[default]
+    Mtime,
+    /// Hash input contents, script, envs and params; skip when unchanged since the last success
+    Content,
+}
+
+// ============================================================================
+// ValueType - Types of the structured values a task passes to its dependents
+// ============================================================================
+
+/// Type declared for a key of a task's structured output (`outputs:`)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ValueType {
+    String,
+    Number,
+    Integer,
+    Bool,
+    List,
+    Dict,
+    /// Any JSON value, as long as the key is present
+    Any,
+}
+
+impl ValueType {
+    /// Whether a JSON value has this type
+    #[must_use]
+    pub fn matches(self, value: &serde_json::Value) -> bool {
+        use serde_json::Value as Json;
+        match self {
+            ValueType::String => value.is_string(),
+            ValueType::Number => value.is_number(),
+            ValueType::Integer => value.is_i64() || value.is_u64(),
+            ValueType::Bool => value.is_boolean(),
+            ValueType::List => value.is_array(),
+            ValueType::Dict => value.is_object(),
+            ValueType::Any => !matches!(value, Json::Null),
+        }
+    }
+
+    /// Name of the type a JSON value actually has, for error messages
+    #[must_use]
+    pub fn describe(value: &serde_json::Value) -> &'static str {
+        use serde_json::Value as Json;
+        match value {
+            Json::Null => "null",
+            Json::Bool(_) => "a bool",
+            Json::Number(_) => "a number",
+            Json::String(_) => "a string",
+            Json::Array(_) => "a list",
+            Json::Object(_) => "a dict",
+        }
+    }
+}
+
+impl fmt::Display for ValueType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ValueType::String => "string",
+            ValueType::Number => "number",
+            ValueType::Integer => "integer",
+            ValueType::Bool => "bool",
+            ValueType::List => "list",
+            ValueType::Dict => "dict",
+            ValueType::Any => "any",
+        };
+        f.write_str(name)
+    }
+}
+
+// ============================================================================
+// ReadySpec - Readiness probes for long-running service tasks
+// ============================================================================
+
+fn default_ready_timeout() -> Duration {
+    Duration::from_secs(60)
+}
+
+fn default_ready_interval() -> Duration {
+    Duration::from_millis(500)
+}
+
+/// Decides when a `service: true` task is up; every configured probe must pass
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ReadySpec {
+    /// TCP port on localhost that accepts connections once the service is up
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+
+    /// Regex matched against each line the service prints on stdout
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log: Option<String>,
+
+    /// Shell command that exits 0 once the service is up
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+
+    /// How long to wait for the service to become ready (default: 60s)
+    #[serde(
+        default,
+        deserialize_with = "deserialize_option_duration",
+        serialize_with = "serialize_option_duration",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub timeout: Option<Duration>,
+
+    /// Delay between probe attempts (default: 500ms)
+    #[serde(
+        default,
+        deserialize_with = "deserialize_option_duration",
+        serialize_with = "serialize_option_duration",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub interval: Option<Duration>,
+}
+
+impl ReadySpec {
+    #[must_use]
+    pub fn timeout(&self) -> Duration {
+        self.timeout.unwrap_or_else(default_ready_timeout)
+    }
+
+    #[must_use]
+    pub fn interval(&self) -> Duration {
+        self.interval.unwrap_or_else(default_ready_interval)
+    }
+
+    /// Check that at least one probe is configured and the log regex compiles
+    pub fn validate(&self) -> Result<()> {
+        if self.port.is_none() && self.log.is_none() && self.command.is_none() {
+            return Err(eyre!("ready: needs at least one of port, log or command"));
+        }
+        if let Some(pattern) = &self.log {
+            regex::Regex::new(pattern).map_err(|e| eyre!("ready.log is not a valid regex: {}", e))?;
+        }
+        Ok(())
+    }
+}
+
 /// Represents a single item from foreach expansion
 #[derive(Clone, Debug)]
 pub struct ForeachItem {
@@ -229,9 +452,66 @@ pub struct TaskSpec {
     pub foreach: Option<ForeachSpec>,
     /// True for foreach-created virtual parent tasks (no action, just dependency tracking)
     pub virtual_parent: bool,
+    /// Maximum wall-clock time the task may run before it is terminated
+    pub timeout: Option<Duration>,
+    /// How long to wait after SIGTERM before sending SIGKILL on timeout
+    pub timeout_grace: Option<Duration>,
+    /// Optional retry policy for flaky tasks
+    pub retry: Option<RetrySpec>,
+    /// Up-to-date check strategy (default: mtime)
+    pub fingerprint: Fingerprint,
+    /// Always run once its dependencies finish, even if they failed (cleanup/teardown)
+    pub finally: bool,
+    /// Handler tasks to run when this task fails
+    pub on_failure: Vec<String>,
+    /// Long-running background process; done for its dependents once ready
+    pub service: bool,
+    /// When a service counts as ready (default: as soon as it starts)
+    pub ready: Option<ReadySpec>,
+    /// Working directory of the action; `input`/`output` globs resolve against it
+    pub dir: Option<String>,
+    /// Start from an empty environment instead of inheriting otto's
+    pub env_clear: bool,
+    /// Variables still inherited from otto's environment when `env_clear` is set
+    pub env_passthrough: Vec<String>,
+    /// Directories put in front of PATH
+    pub path_prepend: Vec<String>,
+    /// A failing exit code is reported as a warning and still unblocks dependents
+    pub allow_failure: bool,
+    /// Exit codes that count as success (default: 0)
+    pub success_codes: Vec<i32>,
+    /// Gets the controlling terminal (stdin included) and runs with no other task alongside
+    pub interactive: bool,
+    /// Runs under a pseudo-terminal so tools keep their colors and progress bars
+    pub pty: bool,
+    /// Declared types of the structured output keys, checked after the task succeeds
+    pub outputs: BTreeMap<String, ValueType>,
+}
+
+/// Script keys besides `bash:` and `python:`, with the shebang each implies
+const SCRIPT_KEYS: [(&str, &str); 4] = [
+    ("sh", "#!/bin/sh"),
+    ("zsh", "#!/usr/bin/env zsh"),
+    ("node", "#!/usr/bin/env node"),
+    ("ruby", "#!/usr/bin/env ruby"),
+];
+
+/// Program named by a script's shebang, looking through `/usr/bin/env`
+/// (`#!/usr/bin/env -S deno run` names `deno`)
+#[must_use]
+pub fn shebang_program(action: &str) -> Option<&str> {
+    let line = action.trim_start().lines().next()?.strip_prefix("#!")?;
+    let mut words = line.split_whitespace();
+    let program = words.next()?;
+    let program = if program.rsplit('/').next() == Some("env") {
+        words.find(|word| !word.starts_with('-'))?
+    } else {
+        program
+    };
+    program.rsplit('/').next()
 }
 
-// Helper struct for deserialization that accepts bash:, python:, or action: fields
+// Helper struct for deserialization that accepts bash:, python:, sh:, zsh:, node:, ruby: or action: fields
 #[derive(Debug, Deserialize)]
 struct TaskSpecHelper {
     #[serde(default)]
@@ -263,6 +543,18 @@ struct TaskSpecHelper {
     #[serde(default)]
     python: Option<String>,
 
+    #[serde(default)]
+    sh: Option<String>,
+
+    #[serde(default)]
+    zsh: Option<String>,
+
+    #[serde(default)]
+    node: Option<String>,
+
+    #[serde(default)]
+    ruby: Option<String>,
+
     // Legacy support for action: field (deprecated)
     #[serde(default)]
     action: Option<String>,
@@ -270,6 +562,72 @@ struct TaskSpecHelper {
     // Support for foreach subtask generation
     #[serde(default)]
     foreach: Option<ForeachSpec>,
+
+    // Per-task timeout (e.g. "30s", "10m", "1h")
+    #[serde(default, deserialize_with = "deserialize_option_duration")]
+    timeout: Option<Duration>,
+
+    // Grace period between SIGTERM and SIGKILL when the timeout expires
+    #[serde(default, deserialize_with = "deserialize_option_duration")]
+    timeout_grace: Option<Duration>,
+
+    // Retry policy for flaky tasks
+    #[serde(default)]
+    retry: Option<RetrySpec>,
+
+    // Up-to-date check strategy (mtime or content)
+    #[serde(default)]
+    fingerprint: Fingerprint,
+
+    // Cleanup task that runs whether its dependencies succeeded or failed
+    #[serde(default)]
+    finally: bool,
+
+    // Handler tasks scheduled when this task fails
+    #[serde(default)]
+    on_failure: Vec<String>,
+
+    // Long-running background process (dev server, mock API)
+    #[serde(default)]
+    service: bool,
+
+    // Readiness probe deciding when dependents of a service may start
+    #[serde(default)]
+    ready: Option<ReadySpec>,
+
+    // Working directory, relative to where otto runs
+    #[serde(default)]
+    dir: Option<String>,
+
+    // Hermetic environment: drop everything not in env_passthrough
+    #[serde(default)]
+    env_clear: bool,
+
+    #[serde(default)]
+    env_passthrough: Vec<String>,
+
+    // Extra PATH entries, relative to the task's dir
+    #[serde(default)]
+    path_prepend: Vec<String>,
+
+    // Exit-code policy: tolerate failures, or treat other codes as success
+    #[serde(default)]
+    allow_failure: bool,
+
+    #[serde(default)]
+    success_codes: Vec<i32>,
+
+    // Needs the terminal for user input (confirmation prompts, logins)
+    #[serde(default)]
+    interactive: bool,
+
+    // Present the output streams to the task as terminals instead of pipes
+    #[serde(default)]
+    pty: bool,
+
+    // Schema of the values passed on through otto_set_output / OTTO_OUTPUT
+    #[serde(default)]
+    outputs: BTreeMap<String, ValueType>,
 }
 
 impl<'de> Deserialize<'de> for TaskSpec {
@@ -279,6 +637,29 @@ impl<'de> Deserialize<'de> for TaskSpec {
     {
         let helper = TaskSpecHelper::deserialize(deserializer)?;
 
+        if let Some(ready) = &helper.ready {
+            if !helper.service {
+                return Err(serde::de::Error::custom("ready: requires service: true"));
+            }
+            ready.validate().map_err(serde::de::Error::custom)?;
+        }
+
+        if helper.interactive && helper.service {
+            return Err(serde::de::Error::custom(
+                "interactive: can't be combined with service: true",
+            ));
+        }
+
+        if helper.pty && helper.interactive {
+            return Err(serde::de::Error::custom(
+                "pty: can't be combined with interactive: true, which already uses the real terminal",
+            ));
+        }
+
+        if !helper.env_passthrough.is_empty() && !helper.env_clear {
+            return Err(serde::de::Error::custom("env_passthrough: requires env_clear: true"));
+        }
+
         let action = if let Some(bash_script) = helper.bash {
             let bash_script = deserialize_script_string(&bash_script);
             if bash_script.trim_start().starts_with("#!") {
@@ -293,6 +674,17 @@ impl<'de> Deserialize<'de> for TaskSpec {
             } else {
                 format!("#!/usr/bin/env python3\n{python_script}")
             }
+        } else if let Some((script, shebang)) = [helper.sh, helper.zsh, helper.node, helper.ruby]
+            .into_iter()
+            .zip(SCRIPT_KEYS.map(|(_, shebang)| shebang))
+            .find_map(|(script, shebang)| Some((script?, shebang)))
+        {
+            let script = deserialize_script_string(&script);
+            if script.trim_start().starts_with("#!") {
+                script
+            } else {
+                format!("{shebang}\n{script}")
+            }
         } else if let Some(action_script) = helper.action {
             deserialize_script_string(&action_script)
         } else {
@@ -311,6 +703,23 @@ impl<'de> Deserialize<'de> for TaskSpec {
             action,
             foreach: helper.foreach,
             virtual_parent: false,
+            timeout: helper.timeout,
+            timeout_grace: helper.timeout_grace,
+            retry: helper.retry,
+            fingerprint: helper.fingerprint,
+            finally: helper.finally,
+            on_failure: helper.on_failure,
+            service: helper.service,
+            ready: helper.ready,
+            dir: helper.dir,
+            env_clear: helper.env_clear,
+            env_passthrough: helper.env_passthrough,
+            path_prepend: helper.path_prepend,
+            allow_failure: helper.allow_failure,
+            success_codes: helper.success_codes,
+            interactive: helper.interactive,
+            pty: helper.pty,
+            outputs: helper.outputs,
         })
     }
 }
@@ -354,6 +763,73 @@ impl Serialize for TaskSpec {
             map.serialize_entry("foreach", foreach)?;
         }
 
+        if let Some(timeout) = self.timeout {
+            map.serialize_entry("timeout", &format_duration(timeout))?;
+        }
+
+        if let Some(grace) = self.timeout_grace {
+            map.serialize_entry("timeout_grace", &format_duration(grace))?;
+        }
+
+        if let Some(ref retry) = self.retry {
+            map.serialize_entry("retry", retry)?;
+        }
+        if self.fingerprint != Fingerprint::default() {
+            map.serialize_entry("fingerprint", &self.fingerprint)?;
+        }
+
+        if self.finally {
+            map.serialize_entry("finally", &self.finally)?;
+        }
+
+        if !self.on_failure.is_empty() {
+            map.serialize_entry("on_failure", &self.on_failure)?;
+        }
+
+        if self.service {
+            map.serialize_entry("service", &self.service)?;
+        }
+
+        if let Some(ref ready) = self.ready {
+            map.serialize_entry("ready", ready)?;
+        }
+
+        if let Some(ref dir) = self.dir {
+            map.serialize_entry("dir", dir)?;
+        }
+
+        if self.env_clear {
+            map.serialize_entry("env_clear", &self.env_clear)?;
+        }
+
+        if !self.env_passthrough.is_empty() {
+            map.serialize_entry("env_passthrough", &self.env_passthrough)?;
+        }
+
+        if !self.path_prepend.is_empty() {
+            map.serialize_entry("path_prepend", &self.path_prepend)?;
+        }
+
+        if self.allow_failure {
+            map.serialize_entry("allow_failure", &self.allow_failure)?;
+        }
+
+        if !self.success_codes.is_empty() {
+            map.serialize_entry("success_codes", &self.success_codes)?;
+        }
+
+        if self.interactive {
+            map.serialize_entry("interactive", &self.interactive)?;
+        }
+
+        if self.pty {
+            map.serialize_entry("pty", &self.pty)?;
+        }
+
+        if !self.outputs.is_empty() {
+            map.serialize_entry("outputs", &self.outputs)?;
+        }
+
         // Serialize action as "bash:" if it starts with #!/bin/bash
         if !self.action.is_empty() {
             if self.action.trim_start().starts_with("#!/bin/bash") {
@@ -372,6 +848,11 @@ impl Serialize for TaskSpec {
                     .or_else(|| self.action.trim_start().strip_prefix("#!/usr/bin/env python3"))
                     .unwrap_or(&self.action);
                 map.serialize_entry("python", python_script)?;
+            } else if let Some((key, script)) = SCRIPT_KEYS.iter().find_map(|(key, shebang)| {
+                let script = self.action.trim_start().strip_prefix(shebang)?;
+                script.starts_with('\n').then(|| (key, &script[1..]))
+            }) {
+                map.serialize_entry(key, script)?;
             } else {
                 map.serialize_entry("action", &self.action)?;
             }
@@ -435,6 +916,23 @@ impl TaskSpec {
             action,
             foreach: None,
             virtual_parent: false,
+            timeout: None,
+            timeout_grace: None,
+            retry: None,
+            fingerprint: Fingerprint::default(),
+            finally: false,
+            on_failure: Vec::new(),
+            service: false,
+            ready: None,
+            dir: None,
+            env_clear: false,
+            env_passthrough: Vec::new(),
+            path_prepend: Vec::new(),
+            allow_failure: false,
+            success_codes: Vec::new(),
+            interactive: false,
+            pty: false,
+            outputs: BTreeMap::new(),
         }
     }
 
@@ -484,6 +982,30 @@ impl TaskSpec {
             .collect()
     }
 
+    /// Directory the task runs in: `dir` resolved against `cwd`
+    #[must_use]
+    pub fn working_dir(&self, cwd: &Path) -> PathBuf {
+        match &self.dir {
+            Some(dir) => {
+                let dir = cwd.join(dir);
+                std::fs::canonicalize(&dir).unwrap_or(dir)
+            }
+            None => cwd.to_path_buf(),
+        }
+    }
+
+    /// `path_prepend` entries resolved against the task's working directory
+    #[must_use]
+    pub fn resolved_path_prepend(&self, working_dir: &Path) -> Vec<PathBuf> {
+        self.path_prepend
+            .iter()
+            .map(|entry| {
+                let entry = Path::new(entry);
+                working_dir.join(entry.strip_prefix("./").unwrap_or(entry))
+            })
+            .collect()
+    }
+
     /// Create a virtual parent task (no action, just for dependency tracking)
     #[must_use]
     pub fn as_virtual_parent(&self) -> TaskSpec {
@@ -499,6 +1021,23 @@ impl TaskSpec {
             action: String::new(), // No action - virtual task
             foreach: None,
             virtual_parent: true,
+            timeout: None,
+            timeout_grace: None,
+            retry: None,
+            fingerprint: Fingerprint::default(),
+            finally: self.finally,
+            on_failure: vec![],
+            service: false,
+            ready: None,
+            dir: None,
+            env_clear: false,
+            env_passthrough: Vec::new(),
+            path_prepend: Vec::new(),
+            allow_failure: false,
+            success_codes: Vec::new(),
+            interactive: false,
+            pty: false,
+            outputs: BTreeMap::new(),
         }
     }
 }
@@ -843,4 +1382,263 @@ mod foreach_tests {
         assert_eq!(foreach.range, Some("1-10".to_string()));
         assert_eq!(foreach.var_name, "num");
     }
+
+    #[test]
+    fn test_timeout_yaml_deserialization() {
+        let yaml = r#"
+            timeout: 10m
+            timeout_grace: 5s
+            bash: |
+              sleep 1
+        "#;
+
+        let task: TaskSpec = serde_yaml::from_str(yaml).unwrap();
+
+        assert_eq!(task.timeout, Some(Duration::from_secs(600)));
+        assert_eq!(task.timeout_grace, Some(Duration::from_secs(5)));
+
+        let serialized = serde_yaml::to_string(&task).unwrap();
+        assert!(serialized.contains("timeout: 10m"));
+        assert!(serialized.contains("timeout_grace: 5s"));
+    }
+
+    #[test]
+    fn test_timeout_yaml_invalid_duration() {
+        let yaml = r#"
+            timeout: forever
+            bash: echo hi
+        "#;
+
+        let result: Result<TaskSpec, _> = serde_yaml::from_str(yaml);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_retry_yaml_deserialization() {
+        let yaml = r#"
+            retry:
+              attempts: 4
+              delay: 2s
+              backoff: exponential
+              on_exit_codes: [75, 137]
+            bash: echo hi
+        "#;
+
+        let task: TaskSpec = serde_yaml::from_str(yaml).unwrap();
+        let retry = task.retry.unwrap();
+
+        assert_eq!(retry.attempts, 4);
+        assert_eq!(retry.backoff, Backoff::Exponential);
+        assert_eq!(retry.delay_before(2), Duration::from_secs(2));
+        assert_eq!(retry.delay_before(3), Duration::from_secs(4));
+        assert_eq!(retry.delay_before(4), Duration::from_secs(8));
+        assert!(retry.should_retry(Some(75)));
+        assert!(!retry.should_retry(Some(1)));
+        assert!(!retry.should_retry(None));
+    }
+
+    #[test]
+    fn test_retry_defaults() {
+        let yaml = r#"
+            retry: {}
+            bash: echo hi
+        "#;
+
+        let task: TaskSpec = serde_yaml::from_str(yaml).unwrap();
+        let retry = task.retry.unwrap();
+
+        assert_eq!(retry.attempts, 3);
+        assert_eq!(retry.backoff, Backoff::Fixed);
+        assert_eq!(retry.delay_before(3), Duration::ZERO);
+        assert!(retry.should_retry(Some(1)));
+    }
+
+    #[test]
+    fn test_fingerprint_yaml_roundtrip() {
+        let yaml = r#"
+            fingerprint: content
+            input: ["src/*.rs"]
+            bash: cargo build
+        "#;
+
+        let task: TaskSpec = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(task.fingerprint, Fingerprint::Content);
+        assert!(serde_yaml::to_string(&task).unwrap().contains("fingerprint: content"));
+
+        let task: TaskSpec = serde_yaml::from_str("bash: echo hi").unwrap();
+        assert_eq!(task.fingerprint, Fingerprint::Mtime);
+        assert!(!serde_yaml::to_string(&task).unwrap().contains("fingerprint"));
+    }
+
+    #[test]
+    fn test_finally_and_on_failure_yaml_roundtrip() {
+        let yaml = r#"
+            finally: true
+            on_failure: [notify]
+            bash: docker compose down
+        "#;
+
+        let task: TaskSpec = serde_yaml::from_str(yaml).unwrap();
+        assert!(task.finally);
+        assert_eq!(task.on_failure, vec!["notify"]);
+
+        let serialized = serde_yaml::to_string(&task).unwrap();
+        let roundtrip: TaskSpec = serde_yaml::from_str(&serialized).unwrap();
+        assert!(roundtrip.finally);
+        assert_eq!(roundtrip.on_failure, vec!["notify"]);
+
+        let task: TaskSpec = serde_yaml::from_str("bash: echo hi").unwrap();
+        assert!(!task.finally);
+        assert!(!serde_yaml::to_string(&task).unwrap().contains("finally"));
+    }
+
+    #[test]
+    fn test_service_ready_probe() {
+        let yaml = r#"
+            service: true
+            ready:
+              port: 8080
+              log: "Listening on \\d+"
+              timeout: 30s
+            bash: ./serve
+        "#;
+
+        let task: TaskSpec = serde_yaml::from_str(yaml).unwrap();
+        assert!(task.service);
+        let ready = task.ready.clone().unwrap();
+        assert_eq!(ready.port, Some(8080));
+        assert_eq!(ready.timeout(), Duration::from_secs(30));
+        assert_eq!(ready.interval(), Duration::from_millis(500));
+
+        let roundtrip: TaskSpec = serde_yaml::from_str(&serde_yaml::to_string(&task).unwrap()).unwrap();
+        assert_eq!(roundtrip.ready, task.ready);
+    }
+
+    #[test]
+    fn test_dir_and_environment_controls() {
+        let yaml = r#"
+            dir: frontend
+            env_clear: true
+            env_passthrough: [PATH, HOME]
+            path_prepend: [./node_modules/.bin, /opt/tools/bin]
+            bash: npm test
+        "#;
+
+        let task: TaskSpec = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(task.dir.as_deref(), Some("frontend"));
+        assert!(task.env_clear);
+        assert_eq!(task.env_passthrough, vec!["PATH", "HOME"]);
+
+        let working_dir = task.working_dir(Path::new("/repo"));
+        assert_eq!(working_dir, PathBuf::from("/repo/frontend"));
+        assert_eq!(
+            task.resolved_path_prepend(&working_dir),
+            vec![
+                PathBuf::from("/repo/frontend/node_modules/.bin"),
+                PathBuf::from("/opt/tools/bin")
+            ]
+        );
+
+        let roundtrip: TaskSpec = serde_yaml::from_str(&serde_yaml::to_string(&task).unwrap()).unwrap();
+        assert_eq!(roundtrip, task);
+
+        let err = serde_yaml::from_str::<TaskSpec>("env_passthrough: [HOME]\nbash: env")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("requires env_clear: true"), "{err}");
+    }
+
+    #[test]
+    fn test_exit_code_policy() {
+        let task: TaskSpec = serde_yaml::from_str("allow_failure: true\nsuccess_codes: [0, 3]\nbash: lint").unwrap();
+        assert!(task.allow_failure);
+        assert_eq!(task.success_codes, vec![0, 3]);
+
+        let roundtrip: TaskSpec = serde_yaml::from_str(&serde_yaml::to_string(&task).unwrap()).unwrap();
+        assert_eq!(roundtrip, task);
+
+        let task: TaskSpec = serde_yaml::from_str("bash: lint").unwrap();
+        assert!(!task.allow_failure);
+        assert!(task.success_codes.is_empty());
+    }
+
+    #[test]
+    fn test_interactive_task() {
+        let task: TaskSpec = serde_yaml::from_str("interactive: true\nbash: terraform apply").unwrap();
+        assert!(task.interactive);
+
+        let roundtrip: TaskSpec = serde_yaml::from_str(&serde_yaml::to_string(&task).unwrap()).unwrap();
+        assert_eq!(roundtrip, task);
+
+        let err = serde_yaml::from_str::<TaskSpec>("interactive: true\nservice: true\nbash: ./serve")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("can't be combined with service"), "{err}");
+    }
+
+    #[test]
+    fn test_output_schema() {
+        let task: TaskSpec = serde_yaml::from_str("outputs:\n  files: list\n  count: integer\nbash: ls").unwrap();
+        assert_eq!(task.outputs["files"], ValueType::List);
+        assert_eq!(task.outputs["count"], ValueType::Integer);
+
+        let roundtrip: TaskSpec = serde_yaml::from_str(&serde_yaml::to_string(&task).unwrap()).unwrap();
+        assert_eq!(roundtrip, task);
+
+        assert!(serde_yaml::from_str::<TaskSpec>("outputs:\n  files: array\nbash: ls").is_err());
+
+        assert!(ValueType::Integer.matches(&serde_json::json!(3)));
+        assert!(!ValueType::Integer.matches(&serde_json::json!(3.5)));
+        assert!(ValueType::Number.matches(&serde_json::json!(3.5)));
+        assert!(!ValueType::Any.matches(&serde_json::Value::Null));
+        assert_eq!(ValueType::describe(&serde_json::json!({})), "a dict");
+    }
+
+    #[test]
+    fn test_pty_task() {
+        let task: TaskSpec = serde_yaml::from_str("pty: true\nbash: cargo test").unwrap();
+        assert!(task.pty);
+
+        let roundtrip: TaskSpec = serde_yaml::from_str(&serde_yaml::to_string(&task).unwrap()).unwrap();
+        assert_eq!(roundtrip, task);
+
+        let err = serde_yaml::from_str::<TaskSpec>("pty: true\ninteractive: true\nbash: ./deploy")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("can't be combined with interactive"), "{err}");
+    }
+
+    #[test]
+    fn test_interpreter_script_keys() {
+        let task: TaskSpec = serde_yaml::from_str("node: console.log('hi')").unwrap();
+        assert_eq!(task.action, "#!/usr/bin/env node\nconsole.log('hi')");
+        assert_eq!(shebang_program(&task.action), Some("node"));
+
+        let roundtrip: TaskSpec = serde_yaml::from_str(&serde_yaml::to_string(&task).unwrap()).unwrap();
+        assert_eq!(roundtrip.action, task.action);
+        assert!(serde_yaml::to_string(&task).unwrap().contains("node:"));
+
+        let task: TaskSpec = serde_yaml::from_str("sh: echo hi").unwrap();
+        assert_eq!(task.action, "#!/bin/sh\necho hi");
+        assert_eq!(shebang_program(&task.action), Some("sh"));
+
+        assert_eq!(
+            shebang_program("#!/usr/bin/env -S deno run -A\nconsole.log(1)"),
+            Some("deno")
+        );
+        assert_eq!(shebang_program("echo no shebang"), None);
+    }
+
+    #[test]
+    fn test_invalid_ready_probes() {
+        let errors = [
+            ("ready: {port: 80}\nbash: ./serve", "requires service: true"),
+            ("service: true\nready: {}\nbash: ./serve", "needs at least one of"),
+            ("service: true\nready: {log: \"(\"}\nbash: ./serve", "not a valid regex"),
+        ];
+        for (yaml, expected) in errors {
+            let err = serde_yaml::from_str::<TaskSpec>(yaml).unwrap_err().to_string();
+            assert!(err.contains(expected), "{err}");
+        }
+    }
 }
@@ -0,0 +1,242 @@
+use chrono::{Local, TimeZone};
+use colored::Colorize;
+use eyre::{Result, eyre};
+use std::sync::Arc;
+
+use crate::executor::diff::{DiffOptions, KeyChange, RunDiff, RunSide, Trend, diff_runs};
+use crate::executor::{RunRecord, RunStatus, StateManager};
+use crate::ports::StateStore;
+
+/// Compare two runs
+#[derive(Debug, clap::Parser)]
+#[command(name = "diff")]
+pub struct DiffCommand {
+    /// Run to compare against, as shown in the ID column of `otto History`
+    #[arg(value_name = "RUN_A")]
+    pub run_a: i64,
+
+    /// Run to compare
+    #[arg(value_name = "RUN_B")]
+    pub run_b: i64,
+
+    /// Percentage by which a task's duration must change to count as slower or faster
+    #[arg(long, value_name = "PERCENT", default_value = "20")]
+    pub threshold: f64,
+
+    /// Seconds by which a task's duration must change to count as slower or faster
+    #[arg(long, value_name = "SECONDS", default_value = "1")]
+    pub min_seconds: f64,
+
+    /// Show a unified diff of stdout logs that differ
+    #[arg(long)]
+    pub logs: bool,
+
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+impl DiffCommand {
+    pub fn execute(&self) -> Result<()> {
+        self.execute_with_store(None)
+    }
+
+    /// Execute with an optional injected StateStore (for testing)
+    pub fn execute_with_store(&self, store: Option<Arc<dyn StateStore>>) -> Result<()> {
+        let store: Arc<dyn StateStore> = match store {
+            Some(s) => s,
+            None => match StateManager::try_new() {
+                Some(m) => Arc::new(m),
+                None => {
+                    eprintln!("{}", "No history database found. Run otto to create it.".yellow());
+                    return Ok(());
+                }
+            },
+        };
+
+        let a = load_run(store.as_ref(), self.run_a)?;
+        let b = load_run(store.as_ref(), self.run_b)?;
+        let options = DiffOptions {
+            threshold_percent: self.threshold,
+            min_seconds: self.min_seconds,
+            logs: self.logs,
+        };
+        let diff = diff_runs(&a, &b, &options);
+
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&diff)?);
+        } else {
+            print!("{}", render(&diff, &a.run, &b.run));
+        }
+        Ok(())
+    }
+}
+
+fn load_run(store: &dyn StateStore, run_id: i64) -> Result<RunSide> {
+    let run = store.get_run(run_id)?.ok_or_else(|| eyre!("Run {run_id} not found"))?;
+    let tasks = store.get_run_tasks(run_id)?;
+    Ok(RunSide::new(run, tasks))
+}
+
+fn render(diff: &RunDiff, a: &RunRecord, b: &RunRecord) -> String {
+    let mut out = format!("\n{}  →  {}\n", format_run(a), format_run(b));
+
+    for task in &diff.tasks {
+        out.push_str(&format!("\n{}\n", task.name.bold()));
+        if let Some(status) = &task.status {
+            let before = status.before.as_deref().unwrap_or("not run");
+            let after = status.after.as_deref().unwrap_or("not run");
+            out.push_str(&format!("  {:<9} {} → {}\n", "status", before, after.bold()));
+        }
+        if let Some(duration) = &task.duration {
+            let trend = match duration.trend {
+                Trend::Slower => "slower".yellow(),
+                Trend::Faster => "faster".green(),
+            };
+            let percent = match duration.before {
+                before if before > 0.0 => format!("{:+.0}%, ", (duration.after - before) / before * 100.0),
+                _ => String::new(),
+            };
+            out.push_str(&format!(
+                "  {:<9} {} → {} ({}{})\n",
+                "duration",
+                format_seconds(duration.before),
+                format_seconds(duration.after),
+                percent,
+                trend
+            ));
+        }
+        if let Some(hash) = &task.script_hash {
+            let short = |hash: &Option<String>| hash.as_deref().map(|h| h.chars().take(8).collect::<String>());
+            out.push_str(&format!(
+                "  {:<9} {} → {}\n",
+                "script",
+                short(&hash.before).unwrap_or_default(),
+                short(&hash.after).unwrap_or_default()
+            ));
+        }
+        render_changes(&mut out, "param", &task.params);
+        render_changes(&mut out, "env", &task.env);
+        render_changes(&mut out, "output", &task.output);
+        if let Some(stdout) = &task.stdout {
+            out.push_str("  stdout\n");
+            for line in stdout.lines() {
+                let line = match line.chars().next() {
+                    Some('+') if !line.starts_with("+++") => line.green().to_string(),
+                    Some('-') if !line.starts_with("---") => line.red().to_string(),
+                    Some('@') => line.cyan().to_string(),
+                    _ => line.to_string(),
+                };
+                out.push_str(&format!("    {line}\n"));
+            }
+        }
+    }
+
+    if diff.tasks.is_empty() {
+        out.push_str(&format!("\nNo differences in {} task(s)\n", diff.unchanged));
+    } else {
+        out.push_str(&format!(
+            "\n{} task(s) changed, {} unchanged\n",
+            diff.tasks.len(),
+            diff.unchanged
+        ));
+    }
+    for run_id in &diff.missing_snapshots {
+        out.push_str(&format!(
+            "{}\n",
+            format!("Params and env weren't recorded for run {run_id}").dimmed()
+        ));
+    }
+    out
+}
+
+fn render_changes(out: &mut String, label: &str, changes: &[KeyChange]) {
+    for change in changes {
+        out.push_str(&format!(
+            "  {:<9} {}: {} → {}\n",
+            label,
+            change.key,
+            change.before.as_deref().unwrap_or("(unset)"),
+            change.after.as_deref().unwrap_or("(unset)")
+        ));
+    }
+}
+
+fn format_run(run: &RunRecord) -> String {
+    let status = match run.status {
+        RunStatus::Success => "✓".green(),
+        RunStatus::Failed => "✗".red(),
+        RunStatus::Running => "⋯".yellow(),
+        RunStatus::Cancelled => "⊖".yellow(),
+    };
+    let started = Local.timestamp_opt(run.timestamp as i64, 0).unwrap();
+    format!(
+        "{} {} {} {}",
+        "Run".bold(),
+        run.id.to_string().cyan(),
+        status,
+        started.format("%Y-%m-%d %H:%M:%S")
+    )
+}
+
+fn format_seconds(seconds: f64) -> String {
+    if seconds < 60.0 {
+        format!("{seconds:.1}s")
+    } else {
+        format!("{}m{}s", (seconds / 60.0) as u64, (seconds % 60.0) as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::state::{RunMetadata, TaskStatus};
+    use crate::ports::MemoryStateStore;
+
+    fn command(run_a: i64, run_b: i64) -> DiffCommand {
+        DiffCommand {
+            run_a,
+            run_b,
+            threshold: 20.0,
+            min_seconds: 1.0,
+            logs: false,
+            json: false,
+        }
+    }
+
+    #[test]
+    fn test_diff_command() {
+        let store = Arc::new(MemoryStateStore::new());
+        let mut runs = Vec::new();
+        for (timestamp, status) in [(100, TaskStatus::Completed), (200, TaskStatus::Failed)] {
+            let run_id = store
+                .record_run_start(&RunMetadata::minimal(None, "abc123".to_string(), timestamp))
+                .unwrap();
+            let task_id = store
+                .record_task_start(run_id, "build", Some("hash1"), None, None, None)
+                .unwrap();
+            store.record_task_complete(task_id, 1, status).unwrap();
+            runs.push(run_id);
+        }
+
+        let a = load_run(store.as_ref(), runs[0]).unwrap();
+        let b = load_run(store.as_ref(), runs[1]).unwrap();
+        let diff = diff_runs(&a, &b, &DiffOptions::default());
+        let text = render(&diff, &a.run, &b.run);
+        assert!(text.contains("completed → "), "{text}");
+        assert!(text.contains("1 task(s) changed, 0 unchanged"), "{text}");
+
+        assert!(
+            command(runs[0], runs[1])
+                .execute_with_store(Some(store.clone()))
+                .is_ok()
+        );
+        assert!(command(runs[0], 99).execute_with_store(Some(store)).is_err());
+    }
+
+    #[test]
+    fn test_format_seconds() {
+        assert_eq!(format_seconds(12.34), "12.3s");
+        assert_eq!(format_seconds(95.0), "1m35s");
+    }
+}
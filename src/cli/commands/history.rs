@@ -1,10 +1,13 @@
 use chrono::{Local, TimeZone};
 use colored::Colorize;
 use console::measure_text_width;
-use eyre::Result;
+use eyre::{Context, Result, eyre};
+use std::path::PathBuf;
 use std::sync::Arc;
 
-use crate::executor::{RunStatus, StateManager};
+use crate::executor::snapshot::RunSnapshot;
+use crate::executor::trace::chrome_trace;
+use crate::executor::{RunStatus, StateManager, TaskRecord};
 use crate::ports::StateStore;
 
 fn display_width(s: &str) -> usize {
@@ -56,7 +59,7 @@ pub struct HistoryCommand {
     #[arg(short = 'n', long, default_value = "20")]
     pub limit: usize,
 
-    /// Filter by status (success, failed, running)
+    /// Filter by status (success, failed, running, cancelled)
     #[arg(short, long)]
     pub status: Option<String>,
 
@@ -67,6 +70,14 @@ pub struct HistoryCommand {
     /// Output as JSON
     #[arg(long)]
     pub json: bool,
+
+    /// Show the tasks of one run
+    #[arg(long, value_name = "ID")]
+    pub run: Option<i64>,
+
+    /// Write a Chrome trace of the run (--run, or the latest) for chrome://tracing or Perfetto
+    #[arg(long, value_name = "PATH")]
+    pub trace: Option<PathBuf>,
 }
 
 impl HistoryCommand {
@@ -88,18 +99,115 @@ impl HistoryCommand {
             },
         };
 
-        if let Some(ref task_name) = self.task_name {
+        if let Some(ref path) = self.trace {
+            self.write_trace(store.as_ref(), path)
+        } else if let Some(run_id) = self.run {
+            self.show_run_tasks(store.as_ref(), run_id)
+        } else if let Some(ref task_name) = self.task_name {
             self.show_task_history(store.as_ref(), task_name)
         } else {
             self.show_run_history(store.as_ref())
         }
     }
 
+    fn write_trace(&self, store: &dyn StateStore, path: &PathBuf) -> Result<()> {
+        let run = match self.run {
+            Some(run_id) => store.get_run(run_id)?.ok_or_else(|| eyre!("Run {run_id} not found"))?,
+            None => store
+                .get_recent_runs(1, self.project.as_deref())?
+                .into_iter()
+                .next()
+                .ok_or_else(|| eyre!("No runs found"))?,
+        };
+        let tasks = store.get_run_tasks(run.id)?;
+        let snapshot = RunSnapshot::for_tasks(&tasks);
+        let trace = chrome_trace(&run, &tasks, snapshot.as_ref());
+        std::fs::write(path, serde_json::to_string(&trace)?)
+            .with_context(|| format!("Failed to write trace {}", path.display()))?;
+        println!(
+            "Wrote trace of run {} ({} tasks) to {}",
+            run.id,
+            tasks.len(),
+            path.display()
+        );
+        Ok(())
+    }
+
+    fn show_run_tasks(&self, store: &dyn StateStore, run_id: i64) -> Result<()> {
+        let run = store.get_run(run_id)?.ok_or_else(|| eyre!("Run {run_id} not found"))?;
+        let tasks = store.get_run_tasks(run_id)?;
+
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&tasks)?);
+            return Ok(());
+        }
+
+        let resumed = match run.resume_count {
+            0 => String::new(),
+            1 => " (resumed once)".dimmed().to_string(),
+            count => format!(" (resumed {count} times)").dimmed().to_string(),
+        };
+        println!(
+            "\n{} {} {} {}{}",
+            "Run".bold(),
+            run_id.to_string().cyan(),
+            format_run_status(&run.status),
+            format_timestamp(run.timestamp),
+            resumed
+        );
+
+        let rows: Vec<(String, String, String, String)> = tasks
+            .iter()
+            .map(|task| {
+                (
+                    task.name.clone(),
+                    format_task_status(&task.status),
+                    format_duration(task.duration_seconds),
+                    task.exit_code.map(|c| c.to_string()).unwrap_or_else(|| "-".to_string()),
+                )
+            })
+            .collect();
+
+        let w1 = rows
+            .iter()
+            .map(|row| display_width(&row.0))
+            .fold(display_width("Task"), usize::max);
+        let w2 = display_width("Status");
+        let w3 = rows
+            .iter()
+            .map(|row| display_width(&row.2))
+            .fold(display_width("Duration"), usize::max);
+        let w4 = display_width("Exit Code");
+
+        println!();
+        println!(
+            "{}  {}  {}  {}",
+            pad_left("Task", w1).bold(),
+            pad_center("Status", w2).bold(),
+            pad_right("Duration", w3).bold(),
+            pad_center("Exit Code", w4).bold(),
+        );
+        println!("{}", "─".repeat(w1 + w2 + w3 + w4 + 6).dimmed());
+        for (c1, c2, c3, c4) in &rows {
+            println!(
+                "{}  {}  {}  {}",
+                pad_left(c1, w1),
+                pad_center(c2, w2),
+                pad_right(c3, w3),
+                pad_center(c4, w4),
+            );
+        }
+
+        println!("\nTotal tasks: {}", tasks.len());
+        Ok(())
+    }
+
     fn show_run_history(&self, store: &dyn StateStore) -> Result<()> {
         let status_filter = self.status.as_ref().and_then(|s| match s.as_str() {
             "success" => Some(RunStatus::Success),
             "failed" => Some(RunStatus::Failed),
             "running" => Some(RunStatus::Running),
+            "cancelled" => Some(RunStatus::Cancelled),
             _ => None,
         });
 
@@ -115,7 +223,7 @@ impl HistoryCommand {
             return Ok(());
         }
 
-        let mut rows: Vec<(String, String, String, String, String, String)> = Vec::new();
+        let mut rows: Vec<(String, String, String, String, String, String, String)> = Vec::new();
 
         for run in &runs {
             let path = run
@@ -134,6 +242,7 @@ impl HistoryCommand {
                 .unwrap_or_else(|| "-".to_string());
 
             rows.push((
+                run.id.to_string(),
                 format_timestamp(run.timestamp),
                 format_run_status(&run.status),
                 format_duration(run.duration_seconds),
@@ -144,6 +253,7 @@ impl HistoryCommand {
         }
 
         // Calculate max width for each column
+        let mut w0 = display_width("ID");
         let mut w1 = display_width("Timestamp");
         let mut w2 = display_width("Status");
         let mut w3 = display_width("Duration");
@@ -151,7 +261,8 @@ impl HistoryCommand {
         let mut w5 = display_width("User");
         let mut w6 = display_width("Path");
 
-        for (c1, c2, c3, c4, c5, c6) in &rows {
+        for (c0, c1, c2, c3, c4, c5, c6) in &rows {
+            w0 = w0.max(display_width(c0));
             w1 = w1.max(display_width(c1));
             w2 = w2.max(display_width(c2));
             w3 = w3.max(display_width(c3));
@@ -163,7 +274,8 @@ impl HistoryCommand {
         // Print header
         println!();
         println!(
-            "{}  {}  {}  {}  {}  {}",
+            "{}  {}  {}  {}  {}  {}  {}",
+            pad_right("ID", w0).bold(),
             pad_left("Timestamp", w1).bold(),
             pad_center("Status", w2).bold(),
             pad_right("Duration", w3).bold(),
@@ -172,13 +284,14 @@ impl HistoryCommand {
             pad_left("Path", w6).bold(),
         );
 
-        let total_width = w1 + w2 + w3 + w4 + w5 + w6 + 10;
+        let total_width = w0 + w1 + w2 + w3 + w4 + w5 + w6 + 12;
         println!("{}", "─".repeat(total_width).dimmed());
 
         // Print rows
-        for (c1, c2, c3, c4, c5, c6) in &rows {
+        for (c0, c1, c2, c3, c4, c5, c6) in &rows {
             println!(
-                "{}  {}  {}  {}  {}  {}",
+                "{}  {}  {}  {}  {}  {}  {}",
+                pad_right(c0, w0),
                 pad_left(c1, w1),
                 pad_center(c2, w2),
                 pad_right(c3, w3),
@@ -207,15 +320,16 @@ impl HistoryCommand {
 
         println!("\n{} for task '{}'", "History".bold(), task_name.cyan());
 
-        let mut rows: Vec<(String, String, String, String, String)> = Vec::new();
+        let mut rows: Vec<(String, String, String, String, String, String)> = Vec::new();
 
-        for task in &history {
+        for (index, task) in history.iter().enumerate() {
             rows.push((
                 task.started_at.map(format_timestamp).unwrap_or_else(|| "-".to_string()),
                 format_task_status(&task.status),
                 format_duration(task.duration_seconds),
                 task.exit_code.map(|c| c.to_string()).unwrap_or_else(|| "-".to_string()),
                 task.run_id.to_string(),
+                task_note(&history, index),
             ));
         }
 
@@ -225,7 +339,7 @@ impl HistoryCommand {
         let mut w4 = display_width("Exit Code");
         let mut w5 = display_width("Run ID");
 
-        for (c1, c2, c3, c4, c5) in &rows {
+        for (c1, c2, c3, c4, c5, _) in &rows {
             w1 = w1.max(display_width(c1));
             w2 = w2.max(display_width(c2));
             w3 = w3.max(display_width(c3));
@@ -235,25 +349,33 @@ impl HistoryCommand {
 
         println!();
         println!(
-            "{}  {}  {}  {}  {}",
+            "{}  {}  {}  {}  {}  {}",
             pad_left("Timestamp", w1).bold(),
             pad_center("Status", w2).bold(),
             pad_right("Duration", w3).bold(),
             pad_center("Exit Code", w4).bold(),
             pad_right("Run ID", w5).bold(),
+            "Notes".bold(),
         );
 
-        let total_width = w1 + w2 + w3 + w4 + w5 + 8;
+        let w6 = rows
+            .iter()
+            .map(|row| display_width(&row.5))
+            .max()
+            .unwrap_or(0)
+            .max(display_width("Notes"));
+        let total_width = w1 + w2 + w3 + w4 + w5 + w6 + 10;
         println!("{}", "─".repeat(total_width).dimmed());
 
-        for (c1, c2, c3, c4, c5) in &rows {
+        for (c1, c2, c3, c4, c5, c6) in &rows {
             println!(
-                "{}  {}  {}  {}  {}",
+                "{}  {}  {}  {}  {}  {}",
                 pad_left(c1, w1),
                 pad_center(c2, w2),
                 pad_right(c3, w3),
                 pad_center(c4, w4),
                 pad_right(c5, w5),
+                c6.dimmed(),
             );
         }
 
@@ -267,16 +389,45 @@ impl HistoryCommand {
             .iter()
             .filter(|t| matches!(t.status, crate::executor::state::TaskStatus::Failed))
             .count();
+        let timed_out = history
+            .iter()
+            .filter(|t| matches!(t.status, crate::executor::state::TaskStatus::TimedOut))
+            .count();
 
-        if successful + failed > 0 {
-            let success_rate = (successful as f64 / (successful + failed) as f64) * 100.0;
+        if successful + failed + timed_out > 0 {
+            let success_rate = (successful as f64 / (successful + failed + timed_out) as f64) * 100.0;
             println!("Success rate: {:.1}%", success_rate);
         }
+        if timed_out > 0 {
+            println!("Timed out: {}", timed_out);
+        }
 
         Ok(())
     }
 }
 
+/// Explanatory note for a history row (history is ordered newest first)
+fn task_note(history: &[TaskRecord], index: usize) -> String {
+    use crate::executor::state::TaskStatus;
+
+    let task = &history[index];
+    if task.status == TaskStatus::Skipped {
+        return match &task.skip_reason {
+            Some(reason) => format!("skipped ({reason})"),
+            None => "skipped".to_string(),
+        };
+    }
+
+    let last_success_hash = history[index + 1..]
+        .iter()
+        .find(|older| older.status == TaskStatus::Completed)
+        .and_then(|older| older.script_hash.as_ref());
+    match (&task.script_hash, last_success_hash) {
+        (Some(current), Some(previous)) if current != previous => "script changed since last success".to_string(),
+        _ => String::new(),
+    }
+}
+
 fn format_timestamp(timestamp: u64) -> String {
     let dt = Local.timestamp_opt(timestamp as i64, 0).unwrap();
     dt.format("%Y-%m-%d %H:%M:%S").to_string()
@@ -315,6 +466,7 @@ fn format_run_status(status: &RunStatus) -> String {
         RunStatus::Success => "✓".green().to_string(),
         RunStatus::Failed => "✗".red().to_string(),
         RunStatus::Running => "⋯".yellow().to_string(),
+        RunStatus::Cancelled => "⊖".yellow().to_string(),
     }
 }
 
@@ -323,8 +475,13 @@ fn format_task_status(status: &crate::executor::state::TaskStatus) -> String {
     match status {
         TaskStatus::Completed => "✓".green().to_string(),
         TaskStatus::Failed => "✗".red().to_string(),
+        TaskStatus::FailedAllowed => "⚠".yellow().to_string(),
         TaskStatus::Running => "⋯".yellow().to_string(),
         TaskStatus::Skipped => "○".blue().to_string(),
+        TaskStatus::Cached => "↺".cyan().to_string(),
+        TaskStatus::TimedOut => "⏱".magenta().to_string(),
+        TaskStatus::DependencyFailed => "⊗".dimmed().to_string(),
+        TaskStatus::Cancelled => "⊖".yellow().to_string(),
         TaskStatus::Pending => "·".dimmed().to_string(),
     }
 }
@@ -419,6 +576,7 @@ mod tests {
         assert!(success.contains("✓") || success.contains("green"));
         assert!(failed.contains("✗") || failed.contains("red"));
         assert!(running.contains("⋯") || running.contains("yellow"));
+        assert!(format_run_status(&RunStatus::Cancelled).contains("⊖"));
     }
 
     #[test]
@@ -428,12 +586,47 @@ mod tests {
         let running = format_task_status(&TaskStatus::Running);
         let skipped = format_task_status(&TaskStatus::Skipped);
         let pending = format_task_status(&TaskStatus::Pending);
+        let timed_out = format_task_status(&TaskStatus::TimedOut);
 
         assert!(!completed.is_empty());
         assert!(!failed.is_empty());
         assert!(!running.is_empty());
         assert!(!skipped.is_empty());
         assert!(!pending.is_empty());
+        assert!(timed_out.contains("⏱"));
+    }
+
+    #[test]
+    fn test_task_note() {
+        let record = |status: TaskStatus, hash: &str, skip_reason: Option<&str>| TaskRecord {
+            id: 0,
+            run_id: 1,
+            name: "build".to_string(),
+            status,
+            script_hash: Some(hash.to_string()),
+            exit_code: None,
+            started_at: None,
+            ended_at: None,
+            duration_seconds: None,
+            stdout_path: None,
+            stderr_path: None,
+            script_path: None,
+            attempt: 1,
+            skip_reason: skip_reason.map(String::from),
+        };
+
+        // Newest first
+        let history = vec![
+            record(TaskStatus::Skipped, "bbbb", Some("outputs up to date")),
+            record(TaskStatus::Failed, "bbbb", None),
+            record(TaskStatus::Completed, "aaaa", None),
+            record(TaskStatus::Skipped, "aaaa", None),
+        ];
+
+        assert_eq!(task_note(&history, 0), "skipped (outputs up to date)");
+        assert_eq!(task_note(&history, 1), "script changed since last success");
+        assert_eq!(task_note(&history, 2), "");
+        assert_eq!(task_note(&history, 3), "skipped");
     }
 
     #[test]
@@ -473,6 +666,8 @@ mod tests {
             status: None,
             project: None,
             json: false,
+            run: None,
+            trace: None,
         };
 
         let result = cmd.execute_with_store(Some(store));
@@ -488,6 +683,8 @@ mod tests {
             status: None,
             project: None,
             json: false,
+            run: None,
+            trace: None,
         };
 
         let result = cmd.execute_with_store(Some(store));
@@ -503,6 +700,8 @@ mod tests {
             status: None,
             project: None,
             json: true,
+            run: None,
+            trace: None,
         };
 
         let result = cmd.execute_with_store(Some(store));
@@ -518,6 +717,8 @@ mod tests {
             status: Some("success".to_string()),
             project: None,
             json: false,
+            run: None,
+            trace: None,
         };
 
         let result = cmd.execute_with_store(Some(store));
@@ -533,6 +734,8 @@ mod tests {
             status: Some("failed".to_string()),
             project: None,
             json: false,
+            run: None,
+            trace: None,
         };
 
         let result = cmd.execute_with_store(Some(store));
@@ -548,6 +751,8 @@ mod tests {
             status: Some("running".to_string()),
             project: None,
             json: false,
+            run: None,
+            trace: None,
         };
 
         let result = cmd.execute_with_store(Some(store));
@@ -563,6 +768,8 @@ mod tests {
             status: Some("invalid".to_string()),
             project: None,
             json: false,
+            run: None,
+            trace: None,
         };
 
         let result = cmd.execute_with_store(Some(store));
@@ -578,6 +785,8 @@ mod tests {
             status: None,
             project: Some("abc123".to_string()),
             json: false,
+            run: None,
+            trace: None,
         };
 
         let result = cmd.execute_with_store(Some(store));
@@ -593,6 +802,8 @@ mod tests {
             status: None,
             project: None,
             json: false,
+            run: None,
+            trace: None,
         };
 
         let result = cmd.execute_with_store(Some(store));
@@ -608,6 +819,8 @@ mod tests {
             status: None,
             project: None,
             json: false,
+            run: None,
+            trace: None,
         };
 
         let result = cmd.execute_with_store(Some(store));
@@ -623,6 +836,8 @@ mod tests {
             status: None,
             project: None,
             json: true,
+            run: None,
+            trace: None,
         };
 
         let result = cmd.execute_with_store(Some(store));
@@ -638,6 +853,8 @@ mod tests {
             status: None,
             project: None,
             json: false,
+            run: None,
+            trace: None,
         };
 
         let result = cmd.execute_with_store(Some(store));
@@ -653,6 +870,8 @@ mod tests {
             status: None,
             project: None,
             json: false,
+            run: None,
+            trace: None,
         };
 
         let result = cmd.execute_with_store(Some(store));
@@ -668,6 +887,8 @@ mod tests {
             status: None,
             project: None,
             json: false,
+            run: None,
+            trace: None,
         };
 
         let result = cmd.show_run_history(store.as_ref());
@@ -683,9 +904,44 @@ mod tests {
             status: None,
             project: None,
             json: false,
+            run: None,
+            trace: None,
         };
 
         let result = cmd.show_task_history(store.as_ref(), "build");
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_run_tasks_and_trace() {
+        let store = create_test_store_with_runs();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let trace_path = temp_dir.path().join("trace.json");
+        let run_id = store.get_recent_runs(10, None).unwrap().last().unwrap().id;
+        let cmd = |run: Option<i64>, trace: Option<PathBuf>| HistoryCommand {
+            task_name: None,
+            limit: 20,
+            status: None,
+            project: None,
+            json: false,
+            run,
+            trace,
+        };
+
+        assert!(cmd(Some(run_id), None).execute_with_store(Some(store.clone())).is_ok());
+        assert!(cmd(Some(999), None).execute_with_store(Some(store.clone())).is_err());
+
+        cmd(Some(run_id), Some(trace_path.clone()))
+            .execute_with_store(Some(store))
+            .unwrap();
+        let trace: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(trace_path).unwrap()).unwrap();
+        let names: Vec<&str> = trace["traceEvents"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|event| event["ph"] == "X")
+            .filter_map(|event| event["name"].as_str())
+            .collect();
+        assert_eq!(names, vec!["build", "test"]);
+    }
 }
@@ -1,11 +1,15 @@
 pub mod clean;
 pub mod convert;
+pub mod diff;
 pub mod history;
+pub mod rerun;
 pub mod stats;
 pub mod upgrade;
 
 pub use clean::CleanCommand;
 pub use convert::ConvertCommand;
+pub use diff::DiffCommand;
 pub use history::HistoryCommand;
+pub use rerun::RerunCommand;
 pub use stats::StatsCommand;
 pub use upgrade::UpgradeCommand;
@@ -0,0 +1,301 @@
+//! Replay a recorded run (`otto Rerun [RUN_ID] [--failed]`) or continue an
+//! interrupted one (`otto --resume`)
+//!
+//! Every run records the command line and working directory it was started
+//! with, so a run can be replayed as it was. With `--failed` the tasks that
+//! passed are not run again: their `output.<task>.json` is carried over from
+//! the earlier run so the tasks that do run still find their inputs.
+//!
+//! `--resume` goes one step further and reattaches to the interrupted run's
+//! directory, so the tasks that completed keep their outputs where they are.
+
+use eyre::{Result, bail, eyre};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use crate::executor::state::{RunRecord, RunStatus, TaskStatus};
+use crate::ports::StateStore;
+
+/// How many recent runs are searched for the latest run of this project
+const RECENT_RUNS: usize = 500;
+
+/// Which run to replay, from the `Rerun` built-in's params
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RerunCommand {
+    /// Run to replay; defaults to the latest run of the current ottofile
+    pub run: Option<i64>,
+    /// Only run the tasks that failed or never ran
+    pub failed: bool,
+}
+
+/// What to execute to replay a run
+#[derive(Debug, Clone, PartialEq)]
+pub struct RerunPlan {
+    pub run_id: i64,
+    /// When the run started; runs are keyed by their start second
+    pub timestamp: u64,
+    /// Command line of the run, including the program name
+    pub args: Vec<String>,
+    /// Directory the run was started from
+    pub cwd: Option<PathBuf>,
+    /// Tasks that passed and aren't run again, with their output file if they wrote one
+    pub reused_outputs: HashMap<String, Option<PathBuf>>,
+}
+
+impl RerunCommand {
+    /// Look up the run to replay. `ottofile` picks the latest run when no run id was given.
+    pub fn plan(&self, store: &dyn StateStore, ottofile: Option<&Path>) -> Result<RerunPlan> {
+        let run = self.find_run(store, ottofile)?;
+        let args = run
+            .args
+            .clone()
+            .filter(|args| !args.is_empty())
+            .ok_or_else(|| eyre!("Run {} has no recorded command line to replay", run.id))?;
+
+        let mut reused_outputs = HashMap::new();
+        if self.failed {
+            if run.status == RunStatus::Success {
+                bail!("Run {} succeeded, there are no failed tasks to rerun", run.id);
+            }
+
+            // Only the last attempt of a retried task counts
+            let mut tasks = store.get_run_tasks(run.id)?;
+            tasks.sort_by_key(|task| task.id);
+            let mut last_status = HashMap::new();
+            for task in tasks {
+                let output = task
+                    .stdout_path
+                    .as_deref()
+                    .and_then(Path::parent)
+                    .map(|dir| dir.join(format!("output.{}.json", task.name)));
+                last_status.insert(task.name, (task.status, output));
+            }
+            reused_outputs = last_status
+                .into_iter()
+                .filter(|(_, (status, _))| {
+                    matches!(
+                        status,
+                        TaskStatus::Completed | TaskStatus::Cached | TaskStatus::Skipped | TaskStatus::FailedAllowed
+                    )
+                })
+                .map(|(name, (_, output))| (name, output.filter(|output| output.exists())))
+                .collect();
+        }
+
+        Ok(RerunPlan {
+            run_id: run.id,
+            timestamp: run.timestamp,
+            args,
+            cwd: run.cwd,
+            reused_outputs,
+        })
+    }
+
+    fn find_run(&self, store: &dyn StateStore, ottofile: Option<&Path>) -> Result<RunRecord> {
+        if let Some(run_id) = self.run {
+            return store.get_run(run_id)?.ok_or_else(|| eyre!("Run {run_id} not found"));
+        }
+        runs_of(store, ottofile)?
+            .into_iter()
+            .find(|run| run.status != RunStatus::Running)
+            .ok_or_else(|| match ottofile {
+                Some(path) => eyre!("No earlier run of {} to rerun", path.display()),
+                None => eyre!("No earlier run to rerun"),
+            })
+    }
+}
+
+/// The interrupted run `otto --resume` continues
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResumePoint {
+    pub run_id: i64,
+    /// When the run started, which names its run directory
+    pub timestamp: u64,
+    /// Command line of the run, including the program name
+    pub args: Vec<String>,
+    /// Directory the run was started from
+    pub cwd: Option<PathBuf>,
+    /// Tasks that completed before the run was interrupted
+    pub completed_tasks: HashSet<String>,
+}
+
+/// Find the latest run of `ottofile` and check that it was interrupted.
+/// Runs left `running` by a killed otto must have been reconciled first.
+pub fn resume_point(store: &dyn StateStore, ottofile: Option<&Path>) -> Result<ResumePoint> {
+    let run = runs_of(store, ottofile)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| eyre!("No earlier run to resume"))?;
+    match run.status {
+        RunStatus::Cancelled => {}
+        RunStatus::Running => bail!("Run {} is still running", run.id),
+        status => bail!(
+            "The last run ({}) finished as {}, there is nothing to resume",
+            run.id,
+            status.as_str()
+        ),
+    }
+    let args = run
+        .args
+        .filter(|args| !args.is_empty())
+        .ok_or_else(|| eyre!("Run {} has no recorded command line to resume", run.id))?;
+
+    // Only the last attempt of a retried task counts
+    let mut tasks = store.get_run_tasks(run.id)?;
+    tasks.sort_by_key(|task| task.id);
+    let mut last_status = HashMap::new();
+    for task in tasks {
+        last_status.insert(task.name, task.status);
+    }
+    let completed_tasks = last_status
+        .into_iter()
+        .filter(|(_, status)| *status == TaskStatus::Completed)
+        .map(|(name, _)| name)
+        .collect();
+
+    Ok(ResumePoint {
+        run_id: run.id,
+        timestamp: run.timestamp,
+        args,
+        cwd: run.cwd,
+        completed_tasks,
+    })
+}
+
+/// Recent runs of one ottofile, newest first
+fn runs_of(store: &dyn StateStore, ottofile: Option<&Path>) -> Result<Vec<RunRecord>> {
+    Ok(store
+        .get_recent_runs(RECENT_RUNS, None)?
+        .into_iter()
+        .filter(|run| run.ottofile_path.as_deref() == ottofile)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::state::RunMetadata;
+    use crate::ports::MemoryStateStore;
+
+    fn record_run(store: &MemoryStateStore, ottofile: &str, timestamp: u64, args: &[&str]) -> i64 {
+        store
+            .record_run_start(&RunMetadata::full(
+                Some(PathBuf::from(ottofile)),
+                "abc123".to_string(),
+                timestamp,
+                Some(PathBuf::from("/work")),
+                None,
+                None,
+                Some(args.iter().map(|arg| arg.to_string()).collect()),
+            ))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_plan_latest_run_of_ottofile() {
+        let store = MemoryStateStore::new();
+        let ours = record_run(&store, "/work/otto.yml", 100, &["otto", "ci"]);
+        store.record_run_complete(100, RunStatus::Success, None).unwrap();
+        record_run(&store, "/other/otto.yml", 200, &["otto", "build"]);
+        store.record_run_complete(200, RunStatus::Success, None).unwrap();
+
+        let plan = RerunCommand::default()
+            .plan(&store, Some(Path::new("/work/otto.yml")))
+            .unwrap();
+        assert_eq!(plan.run_id, ours);
+        assert_eq!(plan.args, vec!["otto", "ci"]);
+        assert_eq!(plan.cwd, Some(PathBuf::from("/work")));
+        assert!(plan.reused_outputs.is_empty());
+
+        let missing = RerunCommand::default().plan(&store, Some(Path::new("/none/otto.yml")));
+        assert!(missing.is_err());
+        let unknown = RerunCommand {
+            run: Some(99),
+            failed: false,
+        };
+        assert!(unknown.plan(&store, None).is_err());
+    }
+
+    #[test]
+    fn test_plan_failed_reuses_passed_tasks() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let task_dir = |name: &str| {
+            let dir = temp_dir.path().join(name);
+            std::fs::create_dir_all(&dir).unwrap();
+            dir
+        };
+        let build_dir = task_dir("build");
+        std::fs::write(build_dir.join("output.build.json"), "{}").unwrap();
+        let test_dir = task_dir("test");
+
+        let store = MemoryStateStore::new();
+        let run_id = record_run(&store, "/work/otto.yml", 100, &["otto", "ci"]);
+        let start = |name: &str, dir: &Path| {
+            store
+                .record_task_start(run_id, name, None, Some(&dir.join("stdout.log")), None, None)
+                .unwrap()
+        };
+        let build = start("build", &build_dir);
+        store.record_task_complete(build, 0, TaskStatus::Completed).unwrap();
+        store.record_task_skipped(run_id, "lint", None, "up to date").unwrap();
+        let flaky = start("flaky", &task_dir("flaky"));
+        store.record_task_complete(flaky, 1, TaskStatus::Failed).unwrap();
+        let flaky = start("flaky", &task_dir("flaky"));
+        store.record_task_complete(flaky, 0, TaskStatus::Completed).unwrap();
+        let test = start("test", &test_dir);
+        store.record_task_complete(test, 1, TaskStatus::Failed).unwrap();
+        store.record_task_dependency_failed(run_id, "deploy").unwrap();
+        store.record_run_complete(100, RunStatus::Failed, None).unwrap();
+
+        let rerun = RerunCommand {
+            run: Some(run_id),
+            failed: true,
+        };
+        let plan = rerun.plan(&store, None).unwrap();
+        assert_eq!(
+            plan.reused_outputs,
+            HashMap::from([
+                ("build".to_string(), Some(build_dir.join("output.build.json"))),
+                ("lint".to_string(), None),
+                ("flaky".to_string(), None),
+            ])
+        );
+
+        // Nothing to do for a run that passed
+        let passed = record_run(&store, "/work/otto.yml", 200, &["otto", "ci"]);
+        store.record_run_complete(200, RunStatus::Success, None).unwrap();
+        let rerun = RerunCommand {
+            run: Some(passed),
+            failed: true,
+        };
+        assert!(rerun.plan(&store, None).is_err());
+    }
+
+    #[test]
+    fn test_resume_point_of_interrupted_run() {
+        let store = MemoryStateStore::new();
+        let ottofile = Some(Path::new("/work/otto.yml"));
+        let run_id = record_run(&store, "/work/otto.yml", 100, &["otto", "deploy"]);
+        let build = store
+            .record_task_start(run_id, "build", None, None, None, None)
+            .unwrap();
+        store.record_task_complete(build, 0, TaskStatus::Completed).unwrap();
+        let test = store.record_task_start(run_id, "test", None, None, None, None).unwrap();
+        store.record_task_complete(test, 130, TaskStatus::Cancelled).unwrap();
+
+        // Still running
+        assert!(resume_point(&store, ottofile).is_err());
+
+        store.record_run_complete(100, RunStatus::Cancelled, None).unwrap();
+        let resume = resume_point(&store, ottofile).unwrap();
+        assert_eq!(resume.run_id, run_id);
+        assert_eq!(resume.timestamp, 100);
+        assert_eq!(resume.args, vec!["otto", "deploy"]);
+        assert_eq!(resume.completed_tasks, HashSet::from(["build".to_string()]));
+
+        // A run that finished has nothing to resume
+        record_run(&store, "/work/otto.yml", 200, &["otto", "deploy"]);
+        store.record_run_complete(200, RunStatus::Failed, None).unwrap();
+        assert!(resume_point(&store, ottofile).is_err());
+    }
+}
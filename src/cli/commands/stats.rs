@@ -4,7 +4,7 @@ use comfy_table::{Cell, CellAlignment, Table, modifiers::UTF8_ROUND_CORNERS, pre
 use eyre::Result;
 use std::sync::Arc;
 
-use crate::executor::StateManager;
+use crate::executor::{StateManager, TaskStats};
 use crate::ports::StateStore;
 
 /// Show execution statistics
@@ -128,12 +128,15 @@ impl StatsCommand {
                     Cell::new("Total").set_alignment(CellAlignment::Right),
                     Cell::new("Success").set_alignment(CellAlignment::Right),
                     Cell::new("Failed").set_alignment(CellAlignment::Right),
+                    Cell::new("Timed Out").set_alignment(CellAlignment::Right),
                     Cell::new("Success Rate").set_alignment(CellAlignment::Right),
+                    Cell::new("Retry Rate").set_alignment(CellAlignment::Right),
+                    Cell::new("Cache Hit Rate").set_alignment(CellAlignment::Right),
                     Cell::new("Avg Duration").set_alignment(CellAlignment::Right),
                 ]);
 
             for task in &task_stats {
-                let total_attempted = task.successful_executions + task.failed_executions;
+                let total_attempted = task.successful_executions + task.failed_executions + task.timed_out_executions;
                 let success_rate = if total_attempted > 0 {
                     (task.successful_executions as f64 / total_attempted as f64) * 100.0
                 } else {
@@ -146,7 +149,10 @@ impl StatsCommand {
                     Cell::new(task.total_executions.to_string()).set_alignment(CellAlignment::Right),
                     Cell::new(task.successful_executions.to_string()).set_alignment(CellAlignment::Right),
                     Cell::new(task.failed_executions.to_string()).set_alignment(CellAlignment::Right),
+                    Cell::new(task.timed_out_executions.to_string()).set_alignment(CellAlignment::Right),
                     Cell::new(format_percentage(success_rate)).set_alignment(CellAlignment::Right),
+                    Cell::new(format_percentage(retry_rate(task))).set_alignment(CellAlignment::Right),
+                    Cell::new(format_percentage(cache_hit_rate(task))).set_alignment(CellAlignment::Right),
                     Cell::new(
                         task.avg_duration_seconds
                             .map(format_duration)
@@ -180,7 +186,7 @@ impl StatsCommand {
         // If there's only one project, show simplified view
         if stats.len() == 1 {
             let stat = &stats[0];
-            let total_attempted = stat.successful_executions + stat.failed_executions;
+            let total_attempted = stat.successful_executions + stat.failed_executions + stat.timed_out_executions;
             let success_rate = if total_attempted > 0 {
                 (stat.successful_executions as f64 / total_attempted as f64) * 100.0
             } else {
@@ -217,6 +223,28 @@ impl StatsCommand {
                 Cell::new("Failed").set_alignment(CellAlignment::Left),
                 Cell::new(stat.failed_executions.to_string()).set_alignment(CellAlignment::Right),
             ]);
+            table.add_row(vec![
+                Cell::new("Timed Out").set_alignment(CellAlignment::Left),
+                Cell::new(stat.timed_out_executions.to_string()).set_alignment(CellAlignment::Right),
+            ]);
+            table.add_row(vec![
+                Cell::new("Retried").set_alignment(CellAlignment::Left),
+                Cell::new(format!(
+                    "{} ({})",
+                    stat.retried_executions,
+                    format_percentage(retry_rate(stat))
+                ))
+                .set_alignment(CellAlignment::Right),
+            ]);
+            table.add_row(vec![
+                Cell::new("Cached").set_alignment(CellAlignment::Left),
+                Cell::new(format!(
+                    "{} ({} hit rate)",
+                    stat.cached_executions,
+                    format_percentage(cache_hit_rate(stat))
+                ))
+                .set_alignment(CellAlignment::Right),
+            ]);
             table.add_row(vec![
                 Cell::new("Skipped").set_alignment(CellAlignment::Left),
                 Cell::new(stat.skipped_executions.to_string()).set_alignment(CellAlignment::Right),
@@ -270,6 +298,15 @@ impl StatsCommand {
                 )
                 .set_alignment(CellAlignment::Right),
             ]);
+            table.add_row(vec![
+                Cell::new("Script").set_alignment(CellAlignment::Left),
+                Cell::new(if stat.script_changed_since_success {
+                    "changed since last success".yellow().to_string()
+                } else {
+                    "unchanged".to_string()
+                })
+                .set_alignment(CellAlignment::Right),
+            ]);
 
             println!("{}", table);
         } else {
@@ -283,12 +320,15 @@ impl StatsCommand {
                     Cell::new("Total").set_alignment(CellAlignment::Right),
                     Cell::new("Success").set_alignment(CellAlignment::Right),
                     Cell::new("Failed").set_alignment(CellAlignment::Right),
+                    Cell::new("Timed Out").set_alignment(CellAlignment::Right),
                     Cell::new("Success Rate").set_alignment(CellAlignment::Right),
+                    Cell::new("Retry Rate").set_alignment(CellAlignment::Right),
+                    Cell::new("Cache Hit Rate").set_alignment(CellAlignment::Right),
                     Cell::new("Avg Duration").set_alignment(CellAlignment::Right),
                 ]);
 
             for stat in &stats {
-                let total_attempted = stat.successful_executions + stat.failed_executions;
+                let total_attempted = stat.successful_executions + stat.failed_executions + stat.timed_out_executions;
                 let success_rate = if total_attempted > 0 {
                     (stat.successful_executions as f64 / total_attempted as f64) * 100.0
                 } else {
@@ -300,7 +340,10 @@ impl StatsCommand {
                     Cell::new(stat.total_executions.to_string()).set_alignment(CellAlignment::Right),
                     Cell::new(stat.successful_executions.to_string()).set_alignment(CellAlignment::Right),
                     Cell::new(stat.failed_executions.to_string()).set_alignment(CellAlignment::Right),
+                    Cell::new(stat.timed_out_executions.to_string()).set_alignment(CellAlignment::Right),
                     Cell::new(format_percentage(success_rate)).set_alignment(CellAlignment::Right),
+                    Cell::new(format_percentage(retry_rate(stat))).set_alignment(CellAlignment::Right),
+                    Cell::new(format_percentage(cache_hit_rate(stat))).set_alignment(CellAlignment::Right),
                     Cell::new(
                         stat.avg_duration_seconds
                             .map(format_duration)
@@ -349,13 +392,40 @@ fn format_percentage(rate: f64) -> String {
     format!("{:.1}%", rate)
 }
 
+/// Share of task executions that needed at least one retry. Retry attempts are
+/// stored as their own rows, so they are excluded from the denominator.
+fn retry_rate(stat: &TaskStats) -> f64 {
+    let executions = stat.total_executions.saturating_sub(stat.retry_attempts);
+    if executions > 0 {
+        (stat.retried_executions as f64 / executions as f64) * 100.0
+    } else {
+        0.0
+    }
+}
+
+/// Share of successful outcomes that were restored from the output cache
+/// instead of being executed
+fn cache_hit_rate(stat: &TaskStats) -> f64 {
+    let produced = stat.cached_executions + stat.successful_executions;
+    if produced > 0 {
+        (stat.cached_executions as f64 / produced as f64) * 100.0
+    } else {
+        0.0
+    }
+}
+
 fn format_task_status(status: &crate::executor::state::TaskStatus) -> String {
     use crate::executor::state::TaskStatus;
     match status {
         TaskStatus::Completed => "✓ Completed".green().to_string(),
         TaskStatus::Failed => "✗ Failed".red().to_string(),
+        TaskStatus::FailedAllowed => "⚠ Failed (allowed)".yellow().to_string(),
         TaskStatus::Running => "⋯ Running".yellow().to_string(),
         TaskStatus::Skipped => "○ Skipped".blue().to_string(),
+        TaskStatus::Cached => "↺ Cached".cyan().to_string(),
+        TaskStatus::TimedOut => "⏱ Timed Out".magenta().to_string(),
+        TaskStatus::DependencyFailed => "⊗ Dependency Failed".dimmed().to_string(),
+        TaskStatus::Cancelled => "⊖ Cancelled".yellow().to_string(),
         TaskStatus::Pending => "· Pending".dimmed().to_string(),
     }
 }
@@ -453,6 +523,43 @@ mod tests {
         assert_eq!(format_percentage(0.0), "0.0%");
     }
 
+    #[test]
+    fn test_retry_rate() {
+        let mut stat = TaskStats {
+            project_id: 1,
+            project_hash: "abc".to_string(),
+            project_name: "demo".to_string(),
+            task_name: "flaky".to_string(),
+            total_executions: 0,
+            successful_executions: 0,
+            failed_executions: 0,
+            skipped_executions: 0,
+            timed_out_executions: 0,
+            cached_executions: 0,
+            retried_executions: 0,
+            retry_attempts: 0,
+            avg_duration_seconds: None,
+            min_duration_seconds: None,
+            max_duration_seconds: None,
+            last_executed: None,
+            last_status: None,
+            script_changed_since_success: false,
+        };
+        assert_eq!(retry_rate(&stat), 0.0);
+
+        // 4 executions, one of which needed two retries (6 rows total)
+        stat.total_executions = 6;
+        stat.retried_executions = 1;
+        stat.retry_attempts = 2;
+        assert_eq!(retry_rate(&stat), 25.0);
+        assert_eq!(cache_hit_rate(&stat), 0.0);
+
+        // 3 executed successfully, 1 restored from the output cache
+        stat.successful_executions = 3;
+        stat.cached_executions = 1;
+        assert_eq!(cache_hit_rate(&stat), 25.0);
+    }
+
     #[test]
     fn test_format_task_status() {
         let completed = format_task_status(&TaskStatus::Completed);
@@ -460,12 +567,15 @@ mod tests {
         let running = format_task_status(&TaskStatus::Running);
         let skipped = format_task_status(&TaskStatus::Skipped);
         let pending = format_task_status(&TaskStatus::Pending);
+        let timed_out = format_task_status(&TaskStatus::TimedOut);
 
         assert!(completed.contains("Completed"));
         assert!(failed.contains("Failed"));
         assert!(running.contains("Running"));
         assert!(skipped.contains("Skipped"));
         assert!(pending.contains("Pending"));
+        assert!(timed_out.contains("Timed Out"));
+        assert!(format_task_status(&TaskStatus::Cancelled).contains("Cancelled"));
     }
 
     #[test]
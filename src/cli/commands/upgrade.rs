@@ -422,7 +422,7 @@ impl UpgradeCommand {
         }
 
         // Sort by timestamp descending (newest first)
-        backups.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+        backups.sort_by_key(|r| std::cmp::Reverse(r.timestamp));
 
         Ok(backups)
     }
@@ -6,5 +6,5 @@ pub mod error;
 pub mod parser;
 
 pub use builtins::{BUILTIN_COMMANDS, BUILTIN_PARAMS, is_builtin, is_builtin_param};
-pub use commands::{CleanCommand, ConvertCommand, HistoryCommand, StatsCommand};
+pub use commands::{CleanCommand, ConvertCommand, DiffCommand, HistoryCommand, RerunCommand, StatsCommand};
 pub use parser::{Parser, is_valid_ottofile_name};
@@ -1,11 +1,13 @@
 //#![allow(unused_imports, unused_variables, unused_attributes, unused_mut, dead_code)]
 
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::env;
 use std::fmt::Debug;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use clap::{Arg, ArgMatches, Command, value_parser};
 use daggy::Dag;
@@ -16,11 +18,14 @@ use hex;
 use once_cell::sync::Lazy;
 use sha2::{Digest, Sha256};
 
-use crate::cfg::config::{ConfigSpec, ParamSpec, TaskSpec, Value};
+use crate::cfg::config::{ConfigSpec, ParamSpec, ParamSpecs, TaskSpec, Value};
 use crate::cfg::env as env_eval;
+use crate::cfg::otto::{OutputMode, Verbosity};
 use crate::cfg::param::ParamType;
-use crate::cfg::task::TaskSpecs;
+use crate::cfg::task::{Fingerprint, ReadySpec, RetrySpec, TaskSpecs, ValueType, shebang_program};
 use crate::cli::builtins::BUILTIN_COMMANDS;
+use crate::cli::commands::rerun::ResumePoint;
+use crate::executor::report::ReportSpec;
 
 pub type DAG<T> = Dag<T, (), u32>;
 
@@ -87,6 +92,24 @@ pub struct Task {
     pub values: HashMap<String, Value>,
     pub action: String,
     pub hash: String,
+    pub timeout: Option<Duration>,
+    pub timeout_grace: Option<Duration>,
+    pub retry: Option<RetrySpec>,
+    pub fingerprint: Fingerprint,
+    pub finally: bool,
+    pub handles_failure_of: Vec<String>,
+    pub service: bool,
+    pub ready: Option<ReadySpec>,
+    pub dir: Option<PathBuf>,
+    pub env_clear: bool,
+    pub env_passthrough: Vec<String>,
+    pub path_prepend: Vec<PathBuf>,
+    pub interpreter: Option<String>,
+    pub allow_failure: bool,
+    pub success_codes: Vec<i32>,
+    pub interactive: bool,
+    pub pty: bool,
+    pub outputs: BTreeMap<String, ValueType>,
 }
 
 impl Task {
@@ -110,6 +133,24 @@ impl Task {
             values,
             action,
             hash,
+            timeout: None,
+            timeout_grace: None,
+            retry: None,
+            fingerprint: Fingerprint::default(),
+            finally: false,
+            handles_failure_of: Vec::new(),
+            service: false,
+            ready: None,
+            dir: None,
+            env_clear: false,
+            env_passthrough: Vec::new(),
+            path_prepend: Vec::new(),
+            interpreter: None,
+            allow_failure: false,
+            success_codes: Vec::new(),
+            interactive: false,
+            pty: false,
+            outputs: BTreeMap::new(),
         }
     }
 
@@ -121,23 +162,42 @@ impl Task {
     ) -> Self {
         let name = task_spec.name.clone();
         let task_deps = task_spec.before.clone();
+        let working_dir = task_spec.working_dir(cwd);
 
-        // Resolve file globs from input to canonical paths using explicit cwd
-        let file_deps = Self::resolve_file_globs(&task_spec.input, cwd);
+        // Resolve file globs from input to canonical paths relative to the task's dir
+        let file_deps = Self::resolve_file_globs(&task_spec.input, &working_dir);
 
-        // Resolve output globs to canonical paths using explicit cwd
-        let output_deps = Self::resolve_file_globs(&task_spec.output, cwd);
+        // Resolve output globs to canonical paths relative to the task's dir
+        let output_deps = Self::resolve_output_globs(&task_spec.output, &working_dir);
 
-        let evaluated_envs = Self::evaluate_merged_envs(global_envs, &task_spec.envs, cwd).unwrap_or_else(|e| {
-            eprintln!("Warning: Failed to evaluate environment variables for task '{name}': {e}");
-            HashMap::new()
-        });
+        let evaluated_envs =
+            Self::evaluate_merged_envs(global_envs, &task_spec.envs, &working_dir).unwrap_or_else(|e| {
+                eprintln!("Warning: Failed to evaluate environment variables for task '{name}': {e}");
+                HashMap::new()
+            });
 
         // Note: We do NOT add after tasks here since they depend on us, not vice versa
         // The after dependencies will be handled during DAG construction
         let values = HashMap::new();
         let action = task_spec.action.trim().to_string(); // Trim whitespace from script content
-        Self::new(name, task_deps, file_deps, output_deps, evaluated_envs, values, action)
+        let mut task = Self::new(name, task_deps, file_deps, output_deps, evaluated_envs, values, action);
+        task.timeout = task_spec.timeout;
+        task.timeout_grace = task_spec.timeout_grace;
+        task.retry = task_spec.retry.clone();
+        task.fingerprint = task_spec.fingerprint;
+        task.finally = task_spec.finally;
+        task.service = task_spec.service;
+        task.ready = task_spec.ready.clone();
+        task.path_prepend = task_spec.resolved_path_prepend(&working_dir);
+        task.dir = task_spec.dir.is_some().then_some(working_dir);
+        task.env_clear = task_spec.env_clear;
+        task.env_passthrough = task_spec.env_passthrough.clone();
+        task.allow_failure = task_spec.allow_failure;
+        task.success_codes = task_spec.success_codes.clone();
+        task.interactive = task_spec.interactive;
+        task.pty = task_spec.pty;
+        task.outputs = task_spec.outputs.clone();
+        task
     }
 
     /// Evaluate and merge environment variables from global and task-level sources
@@ -163,6 +223,23 @@ impl Task {
         Ok(merged_envs)
     }
 
+    /// Like `resolve_file_globs`, but keeps outputs that don't exist yet as absolute
+    /// paths so a missing output is still seen by the up-to-date check
+    fn resolve_output_globs(patterns: &[String], cwd: &std::path::Path) -> Vec<String> {
+        let mut resolved_paths = Vec::new();
+
+        for pattern in patterns {
+            let matches = Self::resolve_file_globs(std::slice::from_ref(pattern), cwd);
+            if matches.is_empty() {
+                resolved_paths.push(cwd.join(pattern).to_string_lossy().to_string());
+            } else {
+                resolved_paths.extend(matches);
+            }
+        }
+
+        resolved_paths
+    }
+
     /// Resolve file globs to canonical paths
     fn resolve_file_globs(patterns: &[String], cwd: &std::path::Path) -> Vec<String> {
         let mut resolved_paths = Vec::new();
@@ -207,6 +284,36 @@ impl Task {
     }
 }
 
+/// Global flags that change how the selected tasks are executed
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RunFlags {
+    /// Keep running independent tasks after a failure (`--keep-going`)
+    pub keep_going: bool,
+    /// Print the execution plan without running anything (`--dry-run`)
+    pub dry_run: bool,
+    /// Run tasks even if their outputs are up to date (`--force`)
+    pub force: bool,
+    /// Run only the named tasks, not their dependencies (`--no-deps`)
+    pub no_deps: bool,
+    /// Terminal output level from `--quiet`/`--verbose` or `otto.verbosity`
+    pub verbosity: Verbosity,
+    /// Run every task under a pseudo-terminal (`--pty`)
+    pub pty: bool,
+    /// Remove ANSI escape sequences from task log files (`otto.strip_ansi`)
+    pub strip_ansi: bool,
+    /// How task output is shown on the terminal (`--output` or `otto.output`)
+    pub output: OutputMode,
+    /// Reports to write once the run finishes (`--report junit:path.xml`)
+    pub reports: Vec<ReportSpec>,
+    /// Tasks that passed in the run `otto Rerun --failed` replays, with their output file;
+    /// they aren't run again
+    pub reused_outputs: HashMap<String, Option<PathBuf>>,
+    /// Continue the last interrupted run instead of starting a new one (`--resume`)
+    pub resume: bool,
+    /// The interrupted run being continued, once `--resume` has looked it up
+    pub resume_point: Option<ResumePoint>,
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub struct Parser {
     prog: String,
@@ -218,6 +325,7 @@ pub struct Parser {
     pargs: Vec<Vec<String>>,
     ottofile: Option<PathBuf>,
     jobs: usize,
+    flags: RunFlags,
 }
 
 impl Parser {
@@ -236,6 +344,7 @@ impl Parser {
             pargs: Vec::new(),
             ottofile: None,
             jobs: num_cpus::get(), // Default to number of CPUs
+            flags: RunFlags::default(),
         })
     }
 
@@ -264,6 +373,16 @@ impl Parser {
         self.config_spec.otto.retention.clone()
     }
 
+    /// Returns the command line otto was started with, including the program name.
+    pub fn args(&self) -> &[String] {
+        &self.args
+    }
+
+    /// Returns the global run flags (`--keep-going`, `--dry-run`, ...) from the command line.
+    pub fn flags(&self) -> RunFlags {
+        self.flags.clone()
+    }
+
     #[allow(clippy::type_complexity)]
     pub fn parse(&mut self) -> Result<(Vec<Task>, String, Option<PathBuf>, usize, bool)> {
         let help_requested = self.args.contains(&"--help".to_string()) || self.args.contains(&"-h".to_string());
@@ -298,6 +417,7 @@ impl Parser {
                                                 pargs: Vec::new(),
                                                 ottofile: None,
                                                 jobs: num_cpus::get(),
+                                                flags: RunFlags::default(),
                                             };
                                             temp_parser.inject_builtin_commands();
                                             let mut help_cmd = temp_parser.build_help_command();
@@ -351,6 +471,25 @@ impl Parser {
         // Extract tui flag
         let tui_mode = matches.get_flag("tui");
 
+        // Extract run flags
+        self.flags = RunFlags {
+            keep_going: matches.get_flag("keep-going"),
+            dry_run: matches.get_flag("dry-run"),
+            force: matches.get_flag("force"),
+            no_deps: matches.get_flag("no-deps"),
+            verbosity: Verbosity::default(),
+            pty: matches.get_flag("pty"),
+            strip_ansi: false,
+            output: OutputMode::default(),
+            reports: matches
+                .get_many::<ReportSpec>("report")
+                .map(|reports| reports.cloned().collect())
+                .unwrap_or_default(),
+            reused_outputs: HashMap::new(),
+            resume: matches.get_flag("resume"),
+            resume_point: None,
+        };
+
         let ottofile_path = Self::divine_ottofile(ottofile_value)?;
         let (config_spec, hash, ottofile) = Self::load_config_from_path(ottofile_path)?;
 
@@ -358,6 +497,18 @@ impl Parser {
         self.hash = hash;
         self.ottofile = ottofile;
 
+        // CLI flags override the ottofile's verbosity setting
+        self.flags.verbosity = Verbosity::resolve(
+            matches.get_flag("quiet"),
+            matches.get_flag("verbose"),
+            self.config_spec.otto.verbosity,
+        );
+        self.flags.strip_ansi = self.config_spec.otto.strip_ansi;
+        self.flags.output = matches
+            .get_one::<OutputMode>("output")
+            .copied()
+            .unwrap_or(self.config_spec.otto.output);
+
         // Inject built-in commands
         self.inject_builtin_commands();
 
@@ -459,7 +610,7 @@ impl Parser {
     }
 
     fn otto_command() -> Command {
-        Command::new("otto")
+        let cmd = Command::new("otto")
             .version(env!("GIT_DESCRIBE"))
             .about("A task runner")
             .arg(
@@ -488,7 +639,8 @@ impl Parser {
                     .help("Enable interactive TUI dashboard for task monitoring")
                     .action(clap::ArgAction::SetTrue)
                     .global(true),
-            )
+            );
+        Self::with_run_flags(cmd)
             .arg(
                 Arg::new("list-subtasks")
                     .long("list-subtasks")
@@ -498,6 +650,77 @@ impl Parser {
             .allow_external_subcommands(true)
     }
 
+    /// Add the flags that control how tasks run, shared by the parser and the help commands
+    fn with_run_flags(cmd: Command) -> Command {
+        cmd.arg(
+            Arg::new("keep-going")
+                .short('k')
+                .long("keep-going")
+                .help("Keep running independent tasks after a task fails")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("dry-run")
+                .long("dry-run")
+                .help("Print the execution plan without running any tasks")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("force")
+                .long("force")
+                .help("Run tasks even if their outputs are up to date")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("no-deps")
+                .long("no-deps")
+                .help("Run only the named tasks, skipping their dependencies")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("pty")
+                .long("pty")
+                .help("Run tasks under a pseudo-terminal so they keep colored output")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("output")
+                .long("output")
+                .value_name("MODE")
+                .help("How task output is shown: prefixed lines, grouped per task, or raw")
+                .value_parser(value_parser!(OutputMode)),
+        )
+        .arg(
+            Arg::new("report")
+                .long("report")
+                .value_name("FORMAT:PATH")
+                .help("Write a junit or json report of the run, e.g. junit:report.xml (repeatable)")
+                .action(clap::ArgAction::Append)
+                .value_parser(ReportSpec::parse),
+        )
+        .arg(
+            Arg::new("resume")
+                .long("resume")
+                .help("Continue the last run of this project where it was interrupted")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("verbose")
+                .short('v')
+                .long("verbose")
+                .help("Print scheduler decisions and resolved environment values")
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with("quiet"),
+        )
+        .arg(
+            Arg::new("quiet")
+                .short('q')
+                .long("quiet")
+                .help("Only show output from tasks that fail")
+                .action(clap::ArgAction::SetTrue),
+        )
+    }
+
     fn extract_remaining_args(&self, matches: &ArgMatches) -> Vec<String> {
         // Handle external subcommands properly
         if let Some((subcommand_name, sub_matches)) = matches.subcommand() {
@@ -620,7 +843,7 @@ impl Parser {
                     self.config_spec
                         .tasks
                         .keys()
-                        .filter(|name| *name != "graph") // Exclude meta-tasks
+                        .filter(|name| *name != "graph" && !crate::cli::is_builtin(name)) // Exclude meta-tasks
                         .cloned(),
                 );
             } else {
@@ -680,15 +903,24 @@ impl Parser {
         let serial_tasks: HashSet<String> = self.detect_serial_tasks(requested_tasks);
 
         // Step 0.5: Expand foreach tasks into subtasks
-        let expanded_tasks = self.expand_foreach_tasks_with_serial(&serial_tasks)?;
+        let mut expanded_tasks = self.expand_foreach_tasks_with_serial(&serial_tasks)?;
+        self.apply_global_finally(&mut expanded_tasks)?;
 
         // Step 1: Compute all task dependencies using simple linear algorithm
         let task_deps = Self::compute_task_deps_from_specs(&expanded_tasks)?;
 
         let mut tasks_needed = HashSet::new();
         for task_name in requested_tasks {
-            Self::collect_transitive_deps(task_name, &task_deps, &expanded_tasks, &mut tasks_needed)?;
+            if self.flags.no_deps {
+                Self::collect_requested_task(task_name, &expanded_tasks, &mut tasks_needed);
+            } else {
+                Self::collect_transitive_deps(task_name, &task_deps, &expanded_tasks, &mut tasks_needed)?;
+            }
         }
+        if !self.flags.no_deps {
+            Self::collect_finally_tasks(&task_deps, &expanded_tasks, &mut tasks_needed)?;
+        }
+        let failure_handlers = Self::collect_failure_handlers(&expanded_tasks, &mut tasks_needed)?;
 
         // Param resolution uses a multi-phase approach to support propagation:
         //   Phase 1: Apply CLI-provided values only
@@ -712,6 +944,9 @@ impl Parser {
             }
 
             let mut task = Task::from_task_with_cwd_and_global_envs(task_spec, &self.cwd, &global_envs);
+            task.interpreter = shebang_program(&task.action)
+                .and_then(|program| self.config_spec.otto.interpreters.get(program))
+                .cloned();
             let mut cli_provided = HashSet::new();
 
             // Find the partition for this task's arguments
@@ -759,6 +994,21 @@ impl Parser {
 
             // Override task_deps with computed dependencies
             task.task_deps = task_deps.get(task_name).map(|deps| deps.to_vec()).unwrap_or_default();
+            if self.flags.no_deps {
+                // Only keep ordering between tasks that were explicitly requested
+                task.task_deps.retain(|dep| tasks_needed.contains(dep));
+            }
+            if let Some(triggers) = failure_handlers.get(task_name) {
+                // A handler waits for the tasks it handles; its own dependencies only
+                // order it against tasks that are already part of the run
+                task.task_deps.retain(|dep| tasks_needed.contains(dep));
+                for trigger in triggers {
+                    if !task.task_deps.contains(trigger) {
+                        task.task_deps.push(trigger.clone());
+                    }
+                }
+                task.handles_failure_of = triggers.clone();
+            }
 
             cli_provided_params.insert(task_name.clone(), cli_provided);
             task_entries.push((task_name.clone(), task));
@@ -1136,6 +1386,106 @@ impl Parser {
         Ok(expanded)
     }
 
+    /// Mark the tasks listed in `otto.finally` (and their foreach subtasks) as `finally`
+    fn apply_global_finally(&self, task_specs: &mut HashMap<String, TaskSpec>) -> Result<()> {
+        for name in &self.config_spec.otto.finally {
+            if !task_specs.contains_key(name) {
+                return Err(eyre!("otto.finally references unknown task '{}'", name));
+            }
+            let prefix = format!("{}:", name);
+            for (task_name, spec) in task_specs.iter_mut() {
+                if task_name == name || task_name.starts_with(&prefix) {
+                    spec.finally = true;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Pull `finally` tasks into the run whenever one of their dependencies is part of it,
+    /// so cleanup always accompanies the tasks it cleans up after
+    fn collect_finally_tasks(
+        task_deps: &HashMap<String, Vec<String>>,
+        task_specs: &HashMap<String, TaskSpec>,
+        collected: &mut HashSet<String>,
+    ) -> Result<()> {
+        loop {
+            let mut finally_tasks: Vec<&String> = task_specs
+                .iter()
+                .filter(|(name, spec)| spec.finally && !spec.virtual_parent && !collected.contains(*name))
+                .filter(|(name, _)| {
+                    task_deps
+                        .get(*name)
+                        .is_some_and(|deps| deps.iter().any(|dep| collected.contains(dep)))
+                })
+                .map(|(name, _)| name)
+                .collect();
+            if finally_tasks.is_empty() {
+                return Ok(());
+            }
+            finally_tasks.sort();
+            for name in finally_tasks {
+                Self::collect_transitive_deps(name, task_deps, task_specs, collected)?;
+            }
+        }
+    }
+
+    /// Add the `on_failure` handlers of the collected tasks to the run.
+    ///
+    /// Returns each handler that is only part of the run because of `on_failure`,
+    /// mapped to the tasks whose failure triggers it.
+    fn collect_failure_handlers(
+        task_specs: &HashMap<String, TaskSpec>,
+        collected: &mut HashSet<String>,
+    ) -> Result<HashMap<String, Vec<String>>> {
+        let mut task_names: Vec<&String> = collected.iter().collect();
+        task_names.sort();
+
+        let mut handlers: HashMap<String, Vec<String>> = HashMap::new();
+        for task_name in task_names {
+            let Some(spec) = task_specs.get(task_name) else {
+                continue;
+            };
+            for handler in &spec.on_failure {
+                if !task_specs.contains_key(handler) {
+                    return Err(eyre!(
+                        "Task '{}' has unknown on_failure handler '{}'\n\
+                         Hint: Check for typos in the task name.",
+                        task_name,
+                        handler
+                    ));
+                }
+                if !collected.contains(handler) || handlers.contains_key(handler) {
+                    handlers.entry(handler.clone()).or_default().push(task_name.clone());
+                }
+            }
+        }
+
+        collected.extend(handlers.keys().cloned());
+        Ok(handlers)
+    }
+
+    /// Collect a requested task without its dependencies (`--no-deps`).
+    ///
+    /// Foreach parents still bring in their own subtasks, since those are the
+    /// task itself rather than something it depends on.
+    fn collect_requested_task(
+        task_name: &str,
+        task_specs: &HashMap<String, TaskSpec>,
+        collected: &mut HashSet<String>,
+    ) {
+        collected.insert(task_name.to_string());
+
+        if !task_name.contains(':') {
+            let prefix = format!("{}:", task_name);
+            for subtask_name in task_specs.keys() {
+                if subtask_name.starts_with(&prefix) {
+                    collected.insert(subtask_name.clone());
+                }
+            }
+        }
+    }
+
     /// Collect all tasks needed to run a given task, including:
     /// - Transitive dependencies (before/upstream tasks)
     /// - After tasks (downstream tasks that should auto-run)
@@ -1296,8 +1646,8 @@ impl Parser {
                     .long("tui")
                     .help("Enable interactive TUI dashboard for task monitoring")
                     .action(clap::ArgAction::SetTrue),
-            )
-            .allow_external_subcommands(true);
+            );
+        cmd = Self::with_run_flags(cmd).allow_external_subcommands(true);
 
         if !self.config_spec.tasks.is_empty() {
             // Separate regular tasks from built-in commands
@@ -1333,7 +1683,7 @@ impl Parser {
     }
 
     fn build_help_command_with_error() -> Command {
-        Command::new("otto")
+        let cmd = Command::new("otto")
             .version(env!("GIT_DESCRIBE"))
             .about("A task runner")
             .arg(
@@ -1351,7 +1701,8 @@ impl Parser {
                     .long("tui")
                     .help("Enable interactive TUI dashboard for task monitoring")
                     .action(clap::ArgAction::SetTrue),
-            )
+            );
+        Self::with_run_flags(cmd)
             .after_help(ottofile_not_found_message())
             .allow_external_subcommands(true)
     }
@@ -1368,7 +1719,7 @@ impl Parser {
             output: vec![],
             envs: HashMap::new(),
             params: {
-                let mut params = HashMap::new();
+                let mut params = ParamSpecs::new();
 
                 params.insert(
                     "format".to_string(),
@@ -1417,6 +1768,7 @@ impl Parser {
             action: "# Built-in graph command".to_string(),
             foreach: None,
             virtual_parent: false,
+            ..Default::default()
         };
 
         self.config_spec.tasks.insert("Graph".to_string(), graph_task);
@@ -1434,7 +1786,7 @@ impl Parser {
             output: vec![],
             envs: HashMap::new(),
             params: {
-                let mut params = HashMap::new();
+                let mut params = ParamSpecs::new();
 
                 params.insert(
                     "keep".to_string(),
@@ -1495,11 +1847,145 @@ impl Parser {
             action: "# Built-in clean command".to_string(),
             foreach: None,
             virtual_parent: false,
+            ..Default::default()
         };
 
         self.config_spec.tasks.insert("Clean".to_string(), clean_task);
     }
 
+    fn inject_diff_meta_task(&mut self) {
+        use crate::cfg::param::{Nargs, ParamType};
+
+        let diff_task = TaskSpec {
+            name: "Diff".to_string(),
+            help: Some("[built-in] Compare two runs: statuses, durations, params, env and outputs".to_string()),
+            after: vec![],
+            before: vec![],
+            input: vec![],
+            output: vec![],
+            envs: HashMap::new(),
+            params: {
+                let mut params = ParamSpecs::new();
+
+                params.insert(
+                    "run_a".to_string(),
+                    ParamSpec {
+                        name: "run_a".to_string(),
+                        short: None,
+                        long: None,
+                        param_type: ParamType::POS,
+                        dest: None,
+                        metavar: Some("RUN_A".to_string()),
+                        default: None,
+                        constant: Value::Empty,
+                        choices: vec![],
+                        nargs: Nargs::One,
+                        help: Some("Run to compare against".to_string()),
+                        value: Value::Empty,
+                    },
+                );
+
+                params.insert(
+                    "run_b".to_string(),
+                    ParamSpec {
+                        name: "run_b".to_string(),
+                        short: None,
+                        long: None,
+                        param_type: ParamType::POS,
+                        dest: None,
+                        metavar: Some("RUN_B".to_string()),
+                        default: None,
+                        constant: Value::Empty,
+                        choices: vec![],
+                        nargs: Nargs::One,
+                        help: Some("Run to compare".to_string()),
+                        value: Value::Empty,
+                    },
+                );
+
+                params.insert(
+                    "threshold".to_string(),
+                    ParamSpec {
+                        name: "threshold".to_string(),
+                        short: None,
+                        long: Some("threshold".to_string()),
+                        param_type: ParamType::OPT,
+                        dest: None,
+                        metavar: Some("PERCENT".to_string()),
+                        default: None,
+                        constant: Value::Empty,
+                        choices: vec![],
+                        nargs: Nargs::One,
+                        help: Some("Percentage by which a duration must change to count (default: 20)".to_string()),
+                        value: Value::Empty,
+                    },
+                );
+
+                params.insert(
+                    "min_seconds".to_string(),
+                    ParamSpec {
+                        name: "min_seconds".to_string(),
+                        short: None,
+                        long: Some("min-seconds".to_string()),
+                        param_type: ParamType::OPT,
+                        dest: None,
+                        metavar: Some("SECONDS".to_string()),
+                        default: None,
+                        constant: Value::Empty,
+                        choices: vec![],
+                        nargs: Nargs::One,
+                        help: Some("Seconds by which a duration must change to count (default: 1)".to_string()),
+                        value: Value::Empty,
+                    },
+                );
+
+                params.insert(
+                    "logs".to_string(),
+                    ParamSpec {
+                        name: "logs".to_string(),
+                        short: None,
+                        long: Some("logs".to_string()),
+                        param_type: ParamType::FLG,
+                        dest: None,
+                        metavar: None,
+                        default: None,
+                        constant: Value::Empty,
+                        choices: vec![],
+                        nargs: Nargs::Zero,
+                        help: Some("Show a unified diff of stdout logs that differ".to_string()),
+                        value: Value::Empty,
+                    },
+                );
+
+                params.insert(
+                    "json".to_string(),
+                    ParamSpec {
+                        name: "json".to_string(),
+                        short: None,
+                        long: Some("json".to_string()),
+                        param_type: ParamType::FLG,
+                        dest: None,
+                        metavar: None,
+                        default: None,
+                        constant: Value::Empty,
+                        choices: vec![],
+                        nargs: Nargs::Zero,
+                        help: Some("Output as JSON".to_string()),
+                        value: Value::Empty,
+                    },
+                );
+
+                params
+            },
+            action: "# Built-in diff command".to_string(),
+            foreach: None,
+            virtual_parent: false,
+            ..Default::default()
+        };
+
+        self.config_spec.tasks.insert("Diff".to_string(), diff_task);
+    }
+
     fn inject_history_meta_task(&mut self) {
         use crate::cfg::param::{Nargs, ParamType};
 
@@ -1512,7 +1998,7 @@ impl Parser {
             output: vec![],
             envs: HashMap::new(),
             params: {
-                let mut params = HashMap::new();
+                let mut params = ParamSpecs::new();
 
                 params.insert(
                     "task".to_string(),
@@ -1561,7 +2047,12 @@ impl Parser {
                         metavar: Some("STATUS".to_string()),
                         default: None,
                         constant: Value::Empty,
-                        choices: vec!["success".to_string(), "failed".to_string(), "running".to_string()],
+                        choices: vec![
+                            "success".to_string(),
+                            "failed".to_string(),
+                            "running".to_string(),
+                            "cancelled".to_string(),
+                        ],
                         nargs: Nargs::One,
                         help: Some("Filter by status".to_string()),
                         value: Value::Empty,
@@ -1604,16 +2095,116 @@ impl Parser {
                     },
                 );
 
+                params.insert(
+                    "run".to_string(),
+                    ParamSpec {
+                        name: "run".to_string(),
+                        short: Some('r'),
+                        long: Some("run".to_string()),
+                        param_type: ParamType::OPT,
+                        dest: None,
+                        metavar: Some("ID".to_string()),
+                        default: None,
+                        constant: Value::Empty,
+                        choices: vec![],
+                        nargs: Nargs::One,
+                        help: Some("Show the tasks of one run".to_string()),
+                        value: Value::Empty,
+                    },
+                );
+
+                params.insert(
+                    "trace".to_string(),
+                    ParamSpec {
+                        name: "trace".to_string(),
+                        short: None,
+                        long: Some("trace".to_string()),
+                        param_type: ParamType::OPT,
+                        dest: None,
+                        metavar: Some("PATH".to_string()),
+                        default: None,
+                        constant: Value::Empty,
+                        choices: vec![],
+                        nargs: Nargs::One,
+                        help: Some("Write a Chrome trace of the run (--run, or the latest)".to_string()),
+                        value: Value::Empty,
+                    },
+                );
+
                 params
             },
             action: "# Built-in history command".to_string(),
             foreach: None,
             virtual_parent: false,
+            ..Default::default()
         };
 
         self.config_spec.tasks.insert("History".to_string(), history_task);
     }
 
+    fn inject_rerun_meta_task(&mut self) {
+        use crate::cfg::param::{Nargs, ParamType};
+
+        let rerun_task = TaskSpec {
+            name: "Rerun".to_string(),
+            help: Some("[built-in] Replay a previous run, or only its failed tasks".to_string()),
+            after: vec![],
+            before: vec![],
+            input: vec![],
+            output: vec![],
+            envs: HashMap::new(),
+            params: {
+                let mut params = ParamSpecs::new();
+
+                params.insert(
+                    "run".to_string(),
+                    ParamSpec {
+                        name: "run".to_string(),
+                        short: None,
+                        long: None,
+                        param_type: ParamType::POS,
+                        dest: None,
+                        metavar: Some("RUN_ID".to_string()),
+                        default: None,
+                        constant: Value::Empty,
+                        choices: vec![],
+                        nargs: Nargs::One,
+                        help: Some("Run to replay (default: the latest run of this project)".to_string()),
+                        value: Value::Empty,
+                    },
+                );
+
+                params.insert(
+                    "failed".to_string(),
+                    ParamSpec {
+                        name: "failed".to_string(),
+                        short: None,
+                        long: Some("failed".to_string()),
+                        param_type: ParamType::FLG,
+                        dest: None,
+                        metavar: None,
+                        default: None,
+                        constant: Value::Empty,
+                        choices: vec![],
+                        nargs: Nargs::Zero,
+                        help: Some(
+                            "Only run the tasks that failed or never ran, reusing the outputs of the rest".to_string(),
+                        ),
+                        value: Value::Empty,
+                    },
+                );
+
+                params
+            },
+            action: "# Built-in rerun command".to_string(),
+            foreach: None,
+            virtual_parent: false,
+            ..Default::default()
+        };
+
+        self.config_spec.tasks.insert("Rerun".to_string(), rerun_task);
+    }
+
     fn inject_stats_meta_task(&mut self) {
         use crate::cfg::param::{Nargs, ParamType};
 
@@ -1626,7 +2217,7 @@ impl Parser {
             output: vec![],
             envs: HashMap::new(),
             params: {
-                let mut params = HashMap::new();
+                let mut params = ParamSpecs::new();
 
                 params.insert(
                     "task".to_string(),
@@ -1687,6 +2278,7 @@ impl Parser {
             action: "# Built-in stats command".to_string(),
             foreach: None,
             virtual_parent: false,
+            ..Default::default()
         };
 
         self.config_spec.tasks.insert("Stats".to_string(), stats_task);
@@ -1704,7 +2296,7 @@ impl Parser {
             output: vec![],
             envs: HashMap::new(),
             params: {
-                let mut params = HashMap::new();
+                let mut params = ParamSpecs::new();
 
                 params.insert(
                     "strict".to_string(),
@@ -1747,6 +2339,7 @@ impl Parser {
             action: "# Built-in convert command".to_string(),
             foreach: None,
             virtual_parent: false,
+            ..Default::default()
         };
 
         self.config_spec.tasks.insert("Convert".to_string(), convert_task);
@@ -1764,7 +2357,7 @@ impl Parser {
             output: vec![],
             envs: HashMap::new(),
             params: {
-                let mut params = HashMap::new();
+                let mut params = ParamSpecs::new();
 
                 params.insert(
                     "dry-run".to_string(),
@@ -1879,6 +2472,7 @@ impl Parser {
             action: "# Built-in upgrade command".to_string(),
             foreach: None,
             virtual_parent: false,
+            ..Default::default()
         };
 
         self.config_spec.tasks.insert("Upgrade".to_string(), upgrade_task);
@@ -1887,8 +2481,10 @@ impl Parser {
     fn inject_builtin_commands(&mut self) {
         self.inject_clean_meta_task();
         self.inject_convert_meta_task();
+        self.inject_diff_meta_task();
         self.inject_graph_meta_task();
         self.inject_history_meta_task();
+        self.inject_rerun_meta_task();
         self.inject_stats_meta_task();
         self.inject_upgrade_meta_task();
     }
@@ -2267,6 +2863,330 @@ mod tests {
         assert_eq!(jobs, 4);
     }
 
+    #[test]
+    fn test_keep_going_flag_parsing() {
+        use std::fs;
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let ottofile_path = temp_dir.path().join("otto.yml");
+        fs::write(&ottofile_path, "tasks:\n  test:\n    action: echo test\n").unwrap();
+
+        let args = vec![
+            "otto".to_string(),
+            "-k".to_string(),
+            "--ottofile".to_string(),
+            ottofile_path.to_string_lossy().to_string(),
+            "test".to_string(),
+        ];
+
+        let mut parser = Parser::new(args).unwrap();
+        assert!(!parser.flags().keep_going);
+        parser.parse().unwrap();
+        assert!(parser.flags().keep_going);
+    }
+
+    #[test]
+    fn test_run_flags_parsing() {
+        use std::fs;
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let ottofile_path = temp_dir.path().join("otto.yml");
+        fs::write(&ottofile_path, "tasks:\n  test:\n    action: echo test\n").unwrap();
+
+        let args = vec![
+            "otto".to_string(),
+            "--dry-run".to_string(),
+            "--force".to_string(),
+            "--ottofile".to_string(),
+            ottofile_path.to_string_lossy().to_string(),
+            "test".to_string(),
+        ];
+
+        let mut parser = Parser::new(args).unwrap();
+        parser.parse().unwrap();
+        let flags = parser.flags();
+        assert!(flags.dry_run);
+        assert!(flags.force);
+        assert!(!flags.no_deps);
+        assert!(!flags.keep_going);
+        assert!(!flags.pty);
+    }
+
+    #[test]
+    fn test_verbosity_flags_override_config() {
+        use std::fs;
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let ottofile_path = temp_dir.path().join("otto.yml");
+        fs::write(
+            &ottofile_path,
+            "otto:\n  verbosity: 2\ntasks:\n  test:\n    action: echo test\n",
+        )
+        .unwrap();
+
+        let parse = |flag: Option<&str>| {
+            let mut args = vec!["otto".to_string()];
+            args.extend(flag.map(str::to_string));
+            args.extend([
+                "--ottofile".to_string(),
+                ottofile_path.to_string_lossy().to_string(),
+                "test".to_string(),
+            ]);
+            let mut parser = Parser::new(args).unwrap();
+            parser.parse().unwrap();
+            parser.flags().verbosity
+        };
+
+        assert_eq!(parse(None), Verbosity::Verbose);
+        assert_eq!(parse(Some("-q")), Verbosity::Quiet);
+        assert_eq!(parse(Some("--verbose")), Verbosity::Verbose);
+    }
+
+    #[test]
+    fn test_output_flag_overrides_config() {
+        use std::fs;
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let ottofile_path = temp_dir.path().join("otto.yml");
+        fs::write(
+            &ottofile_path,
+            "otto:\n  output: grouped\ntasks:\n  test:\n    action: echo test\n",
+        )
+        .unwrap();
+
+        let parse = |flag: Option<&str>| {
+            let mut args = vec!["otto".to_string()];
+            args.extend(flag.map(str::to_string));
+            args.extend([
+                "--ottofile".to_string(),
+                ottofile_path.to_string_lossy().to_string(),
+                "test".to_string(),
+            ]);
+            let mut parser = Parser::new(args).unwrap();
+            parser.parse().unwrap();
+            parser.flags().output
+        };
+
+        assert_eq!(parse(None), OutputMode::Grouped);
+        assert_eq!(parse(Some("--output=raw")), OutputMode::Raw);
+        assert_eq!(parse(Some("--output=prefixed")), OutputMode::Prefixed);
+    }
+
+    #[test]
+    fn test_default_all_tasks_excludes_builtins() {
+        use std::fs;
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let ottofile_path = temp_dir.path().join("otto.yml");
+        fs::write(
+            &ottofile_path,
+            "tasks:\n  build:\n    action: echo build\n  test:\n    action: echo test\n",
+        )
+        .unwrap();
+
+        let args = vec![
+            "otto".to_string(),
+            "--ottofile".to_string(),
+            ottofile_path.to_string_lossy().to_string(),
+        ];
+        let mut parser = Parser::new(args).unwrap();
+        let (tasks, ..) = parser.parse().unwrap();
+        let mut names: Vec<&str> = tasks.iter().map(|task| task.name.as_str()).collect();
+        names.sort();
+        assert_eq!(names, vec!["build", "test"]);
+    }
+
+    #[test]
+    fn test_positional_params_keep_their_declared_order() {
+        use std::fs;
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let ottofile_path = temp_dir.path().join("otto.yml");
+        fs::write(
+            &ottofile_path,
+            "tasks:\n  copy:\n    params:\n      to:\n        help: destination\n      from:\n        help: source\n    action: cp $from $to\n",
+        )
+        .unwrap();
+
+        let args = vec![
+            "otto".to_string(),
+            "--ottofile".to_string(),
+            ottofile_path.to_string_lossy().to_string(),
+            "copy".to_string(),
+            "b.txt".to_string(),
+            "a.txt".to_string(),
+        ];
+        let mut parser = Parser::new(args).unwrap();
+        let (tasks, ..) = parser.parse().unwrap();
+        let copy = tasks.iter().find(|task| task.name == "copy").unwrap();
+        assert_eq!(copy.values.get("to"), Some(&Value::Item("b.txt".to_string())));
+        assert_eq!(copy.values.get("from"), Some(&Value::Item("a.txt".to_string())));
+    }
+
+    #[test]
+    fn test_diff_run_ids_keep_their_positions() {
+        use std::fs;
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let ottofile_path = temp_dir.path().join("otto.yml");
+        fs::write(&ottofile_path, "tasks:\n  test:\n    action: echo test\n").unwrap();
+
+        let args = vec![
+            "otto".to_string(),
+            "--ottofile".to_string(),
+            ottofile_path.to_string_lossy().to_string(),
+            "Diff".to_string(),
+            "41".to_string(),
+            "42".to_string(),
+        ];
+        let mut parser = Parser::new(args).unwrap();
+        let (tasks, ..) = parser.parse().unwrap();
+        let diff = tasks.iter().find(|task| task.name == "Diff").unwrap();
+        assert_eq!(diff.values.get("run_a"), Some(&Value::Item("41".to_string())));
+        assert_eq!(diff.values.get("run_b"), Some(&Value::Item("42".to_string())));
+    }
+
+    #[test]
+    fn test_missing_outputs_are_kept() {
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("existing.txt"), "").unwrap();
+        let outputs = vec!["existing.txt".to_string(), "missing.txt".to_string()];
+
+        let resolved = Task::resolve_output_globs(&outputs, temp_dir.path());
+        assert_eq!(resolved.len(), 2);
+        assert!(resolved[1].ends_with("missing.txt"));
+        assert!(std::path::Path::new(&resolved[1]).is_absolute());
+    }
+
+    #[test]
+    fn test_no_deps_runs_only_named_tasks() {
+        use std::fs;
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let ottofile_path = temp_dir.path().join("otto.yml");
+        fs::write(
+            &ottofile_path,
+            "tasks:\n  check:\n    bash: echo check\n  test:\n    before: [check]\n    bash: echo test\n  build:\n    before: [test]\n    bash: echo build\n",
+        )
+        .unwrap();
+
+        let parse = |names: &[&str]| {
+            let mut args = vec![
+                "otto".to_string(),
+                "--no-deps".to_string(),
+                "--ottofile".to_string(),
+                ottofile_path.to_string_lossy().to_string(),
+            ];
+            args.extend(names.iter().map(|n| n.to_string()));
+            let mut parser = Parser::new(args).unwrap();
+            let (mut tasks, _, _, _, _) = parser.parse().unwrap();
+            tasks.sort_by(|a, b| a.name.cmp(&b.name));
+            tasks
+        };
+
+        let tasks = parse(&["build"]);
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].name, "build");
+        assert!(tasks[0].task_deps.is_empty());
+
+        // Ordering between explicitly named tasks is kept
+        let tasks = parse(&["build", "test"]);
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(tasks[0].name, "build");
+        assert_eq!(tasks[0].task_deps, vec!["test".to_string()]);
+        assert!(tasks[1].task_deps.is_empty());
+    }
+
+    #[test]
+    fn test_finally_tasks_and_failure_handlers_join_the_run() {
+        use std::fs;
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let ottofile_path = temp_dir.path().join("otto.yml");
+        fs::write(
+            &ottofile_path,
+            r#"
+otto:
+  finally: [db-down]
+tasks:
+  db-up:
+    bash: echo up
+  integration:
+    before: [db-up]
+    on_failure: [notify]
+    bash: echo test
+  db-down:
+    before: [integration]
+    bash: echo down
+  report:
+    finally: true
+    before: [integration]
+    bash: echo report
+  notify:
+    bash: echo notify
+  unrelated:
+    bash: echo unrelated
+"#,
+        )
+        .unwrap();
+
+        let args = vec![
+            "otto".to_string(),
+            "--ottofile".to_string(),
+            ottofile_path.to_string_lossy().to_string(),
+            "integration".to_string(),
+        ];
+        let mut parser = Parser::new(args).unwrap();
+        let (mut tasks, _, _, _, _) = parser.parse().unwrap();
+        tasks.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let names: Vec<&str> = tasks.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, vec!["db-down", "db-up", "integration", "notify", "report"]);
+
+        let task = |name: &str| tasks.iter().find(|t| t.name == name).unwrap();
+        assert!(task("db-down").finally);
+        assert!(task("report").finally);
+        assert!(!task("integration").finally);
+        assert_eq!(task("notify").handles_failure_of, vec!["integration".to_string()]);
+        assert_eq!(task("notify").task_deps, vec!["integration".to_string()]);
+        assert!(task("integration").handles_failure_of.is_empty());
+    }
+
+    #[test]
+    fn test_unknown_failure_handler_errors() {
+        use std::fs;
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let ottofile_path = temp_dir.path().join("otto.yml");
+        fs::write(
+            &ottofile_path,
+            "tasks:\n  build:\n    on_failure: [notfy]\n    bash: echo build\n",
+        )
+        .unwrap();
+
+        let args = vec![
+            "otto".to_string(),
+            "--ottofile".to_string(),
+            ottofile_path.to_string_lossy().to_string(),
+            "build".to_string(),
+        ];
+        let mut parser = Parser::new(args).unwrap();
+        let err = parser.parse().unwrap_err().to_string();
+        assert!(err.contains("unknown on_failure handler 'notfy'"), "{err}");
+    }
+
     #[test]
     fn test_jobs_parameter_default() {
         use std::fs;
@@ -7,6 +7,7 @@ use std::sync::Arc;
 
 use super::task::Task;
 use super::workspace::Workspace;
+use crate::cfg::task::shebang_program;
 
 /// Processed action with script type encoded in the enum variant
 pub enum ProcessedAction {
@@ -20,6 +21,40 @@ pub enum ProcessedAction {
         script: String,
         hash: String,
     },
+    /// Any other interpreter. It gets no builtins: params and the output file
+    /// are passed as environment variables instead.
+    Generic {
+        path: PathBuf,
+        script: String,
+        hash: String,
+        /// Program and leading arguments; the script path is appended
+        command: Vec<String>,
+        /// Extra environment for the process
+        envs: Vec<(String, String)>,
+    },
+}
+
+/// Interpreter family of a script, decided by its shebang
+#[derive(Debug, PartialEq)]
+enum ScriptKind {
+    Bash,
+    Python3,
+    Generic { command: Vec<String>, program: String },
+}
+
+/// Render a parameter value for assignment in a generated script
+fn param_value_string(value: &crate::cfg::param::Value) -> String {
+    match value {
+        crate::cfg::param::Value::Item(s) => s.clone(),
+        crate::cfg::param::Value::List(l) => l.join(" "),
+        crate::cfg::param::Value::Dict(d) => {
+            // Convert dict to space-separated key=value pairs
+            let mut pairs: Vec<String> = d.iter().map(|(k, v)| format!("{k}={v}")).collect();
+            pairs.sort();
+            pairs.join(" ")
+        }
+        crate::cfg::param::Value::Empty => String::new(),
+    }
 }
 
 /// Main coordinator for action processing
@@ -37,33 +72,89 @@ impl<F: FileSystem> ActionProcessor<F> {
     }
 
     pub fn process(&self, user_action: &str, task: &Task) -> Result<ProcessedAction> {
+        match Self::script_kind(user_action, task) {
+            ScriptKind::Bash => {
+                let processor = BashProcessor::new(self.workspace.clone(), &self.task_name);
+                processor.create_builtins()?;
+                let script = self.build_script(&processor, user_action, task)?;
+                let path = self.write_script(&processor, &script)?;
+                let hash = self.calculate_hash(&script)?;
+                Ok(ProcessedAction::Bash { path, script, hash })
+            }
+            ScriptKind::Python3 => {
+                let processor = PythonProcessor::new(self.workspace.clone(), &self.task_name);
+                processor.create_builtins()?;
+                let script = self.build_script(&processor, user_action, task)?;
+                let path = self.write_script(&processor, &script)?;
+                let hash = self.calculate_hash(&script)?;
+                Ok(ProcessedAction::Python3 { path, script, hash })
+            }
+            ScriptKind::Generic { command, program } => {
+                let processor = GenericProcessor::new(self.workspace.clone(), &self.task_name, command, &program);
+                processor.create_builtins()?;
+                let script = self.build_script(&processor, user_action, task)?;
+                let path = self.write_script(&processor, &script)?;
+                let hash = self.calculate_hash(&script)?;
+                let envs = processor.process_envs(task);
+                Ok(ProcessedAction::Generic {
+                    path,
+                    script,
+                    hash,
+                    command: processor.command,
+                    envs,
+                })
+            }
+        }
+    }
+
+    /// Hash of the script `process` would generate, without writing anything to disk
+    pub fn script_hash(&self, user_action: &str, task: &Task) -> Result<String> {
+        let script = match Self::script_kind(user_action, task) {
+            ScriptKind::Bash => {
+                let processor = BashProcessor::new(self.workspace.clone(), &self.task_name);
+                self.build_script(&processor, user_action, task)?
+            }
+            ScriptKind::Python3 => {
+                let processor = PythonProcessor::new(self.workspace.clone(), &self.task_name);
+                self.build_script(&processor, user_action, task)?
+            }
+            ScriptKind::Generic { command, program } => {
+                let processor = GenericProcessor::new(self.workspace.clone(), &self.task_name, command, &program);
+                self.build_script(&processor, user_action, task)?
+            }
+        };
+
+        self.calculate_hash(&script)
+    }
+
+    /// Detect the script language from its shebang. Interpreters registered in
+    /// `otto.interpreters` win; scripts without a shebang run in bash.
+    fn script_kind(user_action: &str, task: &Task) -> ScriptKind {
         let trimmed_action = user_action.trim_start();
+        let program = shebang_program(trimmed_action).unwrap_or_default().to_string();
 
-        // Detect script language from shebang
-        if trimmed_action.starts_with("#!/usr/bin/env bash") || trimmed_action.starts_with("#!/bin/bash") {
-            let processor = BashProcessor::new(self.workspace.clone(), &self.task_name);
-            processor.create_builtins()?;
-            let script = self.build_script(&processor, user_action, task)?;
-            let path = self.write_script(&processor, &script)?;
-            let hash = self.calculate_hash(&script)?;
-            Ok(ProcessedAction::Bash { path, script, hash })
-        } else if trimmed_action.starts_with("#!/usr/bin/env python3")
-            || trimmed_action.starts_with("#!/usr/bin/python3")
-        {
-            let processor = PythonProcessor::new(self.workspace.clone(), &self.task_name);
-            processor.create_builtins()?;
-            let script = self.build_script(&processor, user_action, task)?;
-            let path = self.write_script(&processor, &script)?;
-            let hash = self.calculate_hash(&script)?;
-            Ok(ProcessedAction::Python3 { path, script, hash })
-        } else {
+        if let Some(command) = &task.interpreter {
+            return ScriptKind::Generic {
+                command: command.split_whitespace().map(String::from).collect(),
+                program,
+            };
+        }
+
+        match program.as_str() {
             // Default to bash if no shebang is detected (for backward compatibility)
-            let processor = BashProcessor::new(self.workspace.clone(), &self.task_name);
-            processor.create_builtins()?;
-            let script = self.build_script(&processor, user_action, task)?;
-            let path = self.write_script(&processor, &script)?;
-            let hash = self.calculate_hash(&script)?;
-            Ok(ProcessedAction::Bash { path, script, hash })
+            "" | "bash" | "sh" => ScriptKind::Bash,
+            "python3" | "python" => ScriptKind::Python3,
+            _ => {
+                let shebang = trimmed_action
+                    .lines()
+                    .next()
+                    .unwrap_or_default()
+                    .trim_start_matches("#!");
+                ScriptKind::Generic {
+                    command: shebang.split_whitespace().map(String::from).collect(),
+                    program,
+                }
+            }
         }
     }
 
@@ -177,8 +268,8 @@ impl<F: FileSystem> BashProcessor<F> {
 
     /// Separate environment variables from CLI parameters
     /// Only include variables that don't exist in task.values (CLI parameters)
-    fn get_yaml_env_vars(&self, task: &Task) -> std::collections::HashMap<String, String> {
-        let mut yaml_envs = std::collections::HashMap::new();
+    fn get_yaml_env_vars(&self, task: &Task) -> std::collections::BTreeMap<String, String> {
+        let mut yaml_envs = std::collections::BTreeMap::new();
         for (key, value) in &task.envs {
             // Include all environment variables - CLI parameters are handled separately
             // The task.envs already contains both global and task-level env vars
@@ -237,17 +328,10 @@ impl<F: FileSystem> BashProcessor<F> {
             "################################################################################".to_string(),
         ];
 
-        // Simple parameter assignments for CLI parameters only
-        for (param_name, param_value) in &task.values {
-            let value_str = match param_value {
-                crate::cfg::param::Value::Item(s) => s.clone(),
-                crate::cfg::param::Value::List(l) => l.join(" "),
-                crate::cfg::param::Value::Dict(d) => {
-                    // Convert dict to space-separated key=value pairs
-                    d.iter().map(|(k, v)| format!("{k}={v}")).collect::<Vec<_>>().join(" ")
-                }
-                crate::cfg::param::Value::Empty => String::new(),
-            };
+        // Simple parameter assignments for CLI parameters only, sorted so the script hash is stable
+        let values: std::collections::BTreeMap<_, _> = task.values.iter().collect();
+        for (param_name, param_value) in values {
+            let value_str = param_value_string(param_value);
             // Convert hyphens to underscores for valid bash variable names
             let bash_var_name = param_name.replace('-', "_");
             param_section.push(format!("{bash_var_name}=\"{value_str}\""));
@@ -271,11 +355,11 @@ set -euo pipefail
 declare -a OTTO_INPUT
 declare -a OTTO_OUTPUT
 
-# Set Otto environment variables
-export OTTO_TASK_DIR="$(dirname "$0")"
+# Set Otto environment variables (no dirname: PATH may be cleared)
+export OTTO_TASK_DIR="${{0%/*}}"
 
 # Source Otto builtins
-source "$(dirname "$0")/builtins.sh"
+source "${{0%/*}}/builtins.sh"
 
 {env_section}
 {input_section}
@@ -371,6 +455,15 @@ otto_deserialize_input() {
                     key=$(echo "$key" | tr '[:upper:]' '[:lower:]')
                     OTTO_INPUT+=("${task_name}.${key}=${value}")
                 fi
+            # Lists are loaded as bash arrays; OTTO_INPUT gets their JSON form
+            elif [[ "$line" == *"=("*")" ]]; then
+                local var_name="${line%%=*}"
+                if [[ "$var_name" == "${prefix}"* ]]; then
+                    local key="${var_name:$prefix_len}"
+                    local json_var="OTTO_JSON_${task_upper}_${key}"
+                    key=$(echo "$key" | tr '[:upper:]' '[:lower:]')
+                    OTTO_INPUT+=("${task_name}.${key}=${!json_var}")
+                fi
             fi
         done < "$env_file"
     fi
@@ -430,11 +523,27 @@ otto_get_input() {
     echo "$result"
 }
 
+# Print an input value as JSON, keeping lists, dicts and numbers intact
+# Usage: otto_get_input_json "task.key" (prints null if the key is missing)
+otto_get_input_json() {
+    local key="$1"
+    local var_name
+    var_name="OTTO_JSON_$(echo "${key%%.*}_${key#*.}" | tr '[:lower:]-.' '[:upper:]__')"
+    echo "${!var_name:-null}"
+}
+
+# Set an output value from JSON so downstream tasks receive its type
+# Usage: otto_set_output_json "files" '["a.txt", "b.txt"]'
+otto_set_output_json() {
+    otto_set_output "$1:json" "$2"
+}
+
 # Helper function to set output value by key
 # Replaces existing key if present, otherwise appends (Bash 3.2 compatible)
 otto_set_output() {
     local key="$1"
     local value="$2"
+    local base="${key%:json}"
 
     # Remove existing key if present (to allow updates)
     local new_array=()
@@ -444,7 +553,7 @@ otto_set_output() {
     set +u  # Temporarily disable for array operations
     if [ "${#OTTO_OUTPUT[@]}" -gt 0 ]; then
         for item in "${OTTO_OUTPUT[@]}"; do
-            if [[ "$item" != "$key="* ]]; then
+            if [[ "$item" != "$base="* && "$item" != "$base:json="* ]]; then
                 new_array+=("$item")
                 has_items=true
             fi
@@ -493,8 +602,8 @@ impl<F: FileSystem> PythonProcessor<F> {
 
     /// Separate environment variables from CLI parameters
     /// Only include variables that don't exist in task.values (CLI parameters)
-    fn get_yaml_env_vars(&self, task: &Task) -> std::collections::HashMap<String, String> {
-        let mut yaml_envs = std::collections::HashMap::new();
+    fn get_yaml_env_vars(&self, task: &Task) -> std::collections::BTreeMap<String, String> {
+        let mut yaml_envs = std::collections::BTreeMap::new();
         for (key, value) in &task.envs {
             // Include all environment variables - CLI parameters are handled separately
             // The task.envs already contains both global and task-level env vars
@@ -553,17 +662,10 @@ impl<F: FileSystem> PythonProcessor<F> {
             "################################################################################".to_string(),
         ];
 
-        // Simple parameter assignments for CLI parameters only
-        for (param_name, param_value) in &task.values {
-            let value_str = match param_value {
-                crate::cfg::param::Value::Item(s) => s.clone(),
-                crate::cfg::param::Value::List(l) => l.join(" "),
-                crate::cfg::param::Value::Dict(d) => {
-                    // Convert dict to space-separated key=value pairs
-                    d.iter().map(|(k, v)| format!("{k}={v}")).collect::<Vec<_>>().join(" ")
-                }
-                crate::cfg::param::Value::Empty => String::new(),
-            };
+        // Simple parameter assignments for CLI parameters only, sorted so the script hash is stable
+        let values: std::collections::BTreeMap<_, _> = task.values.iter().collect();
+        for (param_name, param_value) in values {
+            let value_str = param_value_string(param_value);
             param_section.push(format!("{param_name} = '{value_str}'"));
         }
 
@@ -723,6 +825,81 @@ def otto_set_output(key, value):
     }
 }
 
+/// Processor for interpreters otto has no builtins for (sh, node, ruby, ...).
+/// The script runs unchanged; params are exported as environment variables
+/// and `OTTO_OUTPUT_FILE` names the JSON file to write outputs to. Outputs of
+/// dependencies are in `$OTTO_TASK_DIR/input.<task>.json`.
+pub struct GenericProcessor<F: FileSystem = crate::ports::RealFs> {
+    workspace: Arc<Workspace<F>>,
+    task_name: String,
+    command: Vec<String>,
+    extension: &'static str,
+}
+
+impl<F: FileSystem> GenericProcessor<F> {
+    /// `command` runs the script (its path is appended); `program` is the
+    /// interpreter named by the shebang and picks the script's file extension
+    pub fn new(workspace: Arc<Workspace<F>>, task_name: &str, command: Vec<String>, program: &str) -> Self {
+        let extension = match program {
+            "sh" | "bash" | "zsh" | "dash" | "ksh" => "sh",
+            "node" | "bun" => "js",
+            "deno" => "ts",
+            "ruby" => "rb",
+            "perl" => "pl",
+            "python" | "python3" => "py",
+            "php" => "php",
+            "lua" => "lua",
+            _ => "script",
+        };
+        Self {
+            workspace,
+            task_name: task_name.to_string(),
+            command,
+            extension,
+        }
+    }
+
+    /// Environment standing in for the builtins: params under their bash
+    /// variable names and the output file path
+    pub fn process_envs(&self, task: &Task) -> Vec<(String, String)> {
+        let values: std::collections::BTreeMap<_, _> = task.values.iter().collect();
+        let mut envs: Vec<(String, String)> = values
+            .into_iter()
+            .map(|(name, value)| (name.replace('-', "_"), param_value_string(value)))
+            .collect();
+        envs.push((
+            "OTTO_OUTPUT_FILE".to_string(),
+            self.workspace
+                .task_output_file(&self.task_name)
+                .to_string_lossy()
+                .to_string(),
+        ));
+        envs
+    }
+}
+
+impl<F: FileSystem> ScriptProcessor for GenericProcessor<F> {
+    fn generate_prologue(&self, _dependencies: &[String], _task: &Task) -> Result<String> {
+        Ok(String::new())
+    }
+
+    fn generate_epilogue(&self) -> Result<String> {
+        Ok(String::new())
+    }
+
+    fn get_interpreter(&self) -> &str {
+        self.command.first().map_or("", String::as_str)
+    }
+
+    fn get_file_extension(&self) -> &str {
+        self.extension
+    }
+
+    fn create_builtins(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -917,4 +1094,135 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_generic_action_processing() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        setup_test_db(temp_dir.path());
+        let workspace = Arc::new(Workspace::new(temp_dir.path().to_path_buf()).await?);
+        workspace.init().await?;
+
+        let processor = ActionProcessor::new(workspace.clone(), "test_task")?;
+
+        let mut task_values = HashMap::new();
+        task_values.insert("dry-run".to_string(), Value::Item("true".to_string()));
+        let mut task = Task::new(
+            "test_task".to_string(),
+            None,
+            vec![],
+            vec![],
+            vec![],
+            HashMap::new(),
+            task_values,
+            "#!/usr/bin/env ruby\nputs ENV['dry_run']".to_string(),
+        );
+
+        match processor.process(&task.action, &task)? {
+            ProcessedAction::Generic {
+                path,
+                script,
+                command,
+                envs,
+                ..
+            } => {
+                assert!(path.exists());
+                assert_eq!(path.extension().and_then(|ext| ext.to_str()), Some("rb"));
+                assert!(script.starts_with("#!/usr/bin/env ruby\n"));
+                assert!(!script.contains("otto_serialize_output"));
+                assert_eq!(command, vec!["/usr/bin/env", "ruby"]);
+                assert!(envs.contains(&("dry_run".to_string(), "true".to_string())));
+                assert!(
+                    envs.iter()
+                        .any(|(name, value)| name == "OTTO_OUTPUT_FILE" && value.ends_with("output.test_task.json"))
+                );
+            }
+            _ => panic!("Expected Generic variant"),
+        }
+
+        // A registered interpreter replaces the shebang's command
+        task.interpreter = Some("ruby --disable-gems".to_string());
+        match processor.process(&task.action, &task)? {
+            ProcessedAction::Generic { command, .. } => assert_eq!(command, vec!["ruby", "--disable-gems"]),
+            _ => panic!("Expected Generic variant"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_script_kind_from_shebang_program() {
+        let task = Task::new(
+            "test_task".to_string(),
+            None,
+            vec![],
+            vec![],
+            vec![],
+            HashMap::new(),
+            HashMap::new(),
+            String::new(),
+        );
+        let kind = |action: &str| ActionProcessor::<crate::ports::RealFs>::script_kind(action, &task);
+
+        assert_eq!(kind("echo hi"), ScriptKind::Bash);
+        assert_eq!(kind("#!/usr/bin/bash\necho hi"), ScriptKind::Bash);
+        assert_eq!(kind("#!/usr/bin/env -S bash -e\necho hi"), ScriptKind::Bash);
+        assert_eq!(kind("#!/bin/sh\necho hi"), ScriptKind::Bash);
+        assert_eq!(kind("#!/usr/local/bin/python3\nprint(1)"), ScriptKind::Python3);
+        assert_eq!(
+            kind("#!/usr/bin/env node\nconsole.log(1)"),
+            ScriptKind::Generic {
+                command: vec!["/usr/bin/env".to_string(), "node".to_string()],
+                program: "node".to_string(),
+            }
+        );
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_script_hash_matches_processed_hash() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        setup_test_db(temp_dir.path());
+        let workspace = Arc::new(Workspace::new(temp_dir.path().to_path_buf()).await?);
+        workspace.init().await?;
+
+        let processor = ActionProcessor::new(workspace.clone(), "test_task")?;
+
+        let mut task_envs = HashMap::new();
+        let mut task_values = HashMap::new();
+        for name in ["alpha", "beta", "gamma", "delta", "epsilon"] {
+            task_envs.insert(name.to_string(), name.to_uppercase());
+            task_values.insert(name.to_string(), Value::Item(name.to_string()));
+        }
+
+        for action in [
+            "echo hi",
+            "#!/usr/bin/env python3\nprint('hi')",
+            "#!/usr/bin/env node\nconsole.log('hi')",
+        ] {
+            let task = Task::new(
+                "test_task".to_string(),
+                None,
+                vec![],
+                vec![],
+                vec![],
+                task_envs.clone(),
+                task_values.clone(),
+                action.to_string(),
+            );
+
+            // Nothing is written, and the same inputs always hash the same
+            let hash = processor.script_hash(&task.action, &task)?;
+            assert_eq!(processor.script_hash(&task.action, &task)?, hash);
+
+            let processed_hash = match processor.process(&task.action, &task)? {
+                ProcessedAction::Bash { hash, .. }
+                | ProcessedAction::Python3 { hash, .. }
+                | ProcessedAction::Generic { hash, .. } => hash,
+            };
+            assert_eq!(hash, processed_hash);
+        }
+
+        Ok(())
+    }
 }
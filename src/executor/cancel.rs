@@ -0,0 +1,157 @@
+//! Cancellation of a running scheduler (Ctrl-C / SIGTERM)
+//!
+//! Task processes run in their own process groups, so a terminal Ctrl-C only
+//! reaches otto itself. The first signal is forwarded to every running task
+//! so it can shut down cleanly; a second one escalates to SIGKILL.
+
+use tokio::sync::watch;
+
+/// How forcefully running tasks are being stopped. Levels only ever increase.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CancelLevel {
+    /// Not cancelled
+    #[default]
+    None,
+    /// Forward SIGINT to running tasks (first Ctrl-C)
+    Interrupt,
+    /// Forward SIGTERM to running tasks (otto received SIGTERM)
+    Terminate,
+    /// SIGKILL running tasks (second signal)
+    Kill,
+}
+
+impl CancelLevel {
+    /// Signal to deliver to a task's process group at this level
+    #[cfg(unix)]
+    pub fn signal(self) -> Option<libc::c_int> {
+        match self {
+            CancelLevel::None => None,
+            CancelLevel::Interrupt => Some(libc::SIGINT),
+            CancelLevel::Terminate => Some(libc::SIGTERM),
+            CancelLevel::Kill => Some(libc::SIGKILL),
+        }
+    }
+}
+
+/// Shared handle used to cancel a scheduler and to observe cancellation
+#[derive(Clone, Debug)]
+pub struct CancelHandle {
+    tx: watch::Sender<CancelLevel>,
+}
+
+impl Default for CancelHandle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CancelHandle {
+    pub fn new() -> Self {
+        Self {
+            tx: watch::Sender::new(CancelLevel::None),
+        }
+    }
+
+    /// Request cancellation as after a Ctrl-C; a repeated request force-kills
+    pub fn interrupt(&self) {
+        self.escalate(CancelLevel::Interrupt);
+    }
+
+    /// Request cancellation as after a SIGTERM; a repeated request force-kills
+    pub fn terminate(&self) {
+        self.escalate(CancelLevel::Terminate);
+    }
+
+    fn escalate(&self, requested: CancelLevel) {
+        self.tx.send_modify(|level| {
+            *level = if *level == CancelLevel::None { requested } else { CancelLevel::Kill };
+        });
+    }
+
+    pub fn level(&self) -> CancelLevel {
+        *self.tx.borrow()
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.level() != CancelLevel::None
+    }
+
+    /// Receiver notified whenever the cancellation level changes
+    pub fn subscribe(&self) -> watch::Receiver<CancelLevel> {
+        self.tx.subscribe()
+    }
+}
+
+/// Translate SIGINT/SIGTERM received by otto into cancellation requests.
+/// Runs until the process exits; meant to be spawned for terminal-mode runs.
+pub async fn forward_signals(handle: CancelHandle) {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+
+        let (mut sigint, mut sigterm) = match (signal(SignalKind::interrupt()), signal(SignalKind::terminate())) {
+            (Ok(sigint), Ok(sigterm)) => (sigint, sigterm),
+            (Err(e), _) | (_, Err(e)) => {
+                log::warn!("Failed to install signal handlers: {e}");
+                return;
+            }
+        };
+
+        loop {
+            tokio::select! {
+                Some(()) = sigint.recv() => handle.interrupt(),
+                Some(()) = sigterm.recv() => handle.terminate(),
+                else => break,
+            }
+            announce(handle.level());
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        while tokio::signal::ctrl_c().await.is_ok() {
+            handle.interrupt();
+            announce(handle.level());
+        }
+    }
+}
+
+fn announce(level: CancelLevel) {
+    if level == CancelLevel::Kill {
+        eprintln!("\nForce killing running tasks");
+    } else {
+        eprintln!("\nCancelling running tasks (press Ctrl-C again to force kill)");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancel_escalates_to_kill() {
+        let handle = CancelHandle::new();
+        assert!(!handle.is_cancelled());
+
+        handle.interrupt();
+        assert_eq!(handle.level(), CancelLevel::Interrupt);
+
+        handle.interrupt();
+        assert_eq!(handle.level(), CancelLevel::Kill);
+
+        let handle = CancelHandle::new();
+        handle.terminate();
+        assert_eq!(handle.level(), CancelLevel::Terminate);
+        handle.interrupt();
+        assert_eq!(handle.level(), CancelLevel::Kill);
+    }
+
+    #[tokio::test]
+    async fn test_subscribers_observe_cancellation() {
+        let handle = CancelHandle::new();
+        let mut rx = handle.subscribe();
+
+        handle.clone().interrupt();
+        rx.changed().await.unwrap();
+        assert_eq!(*rx.borrow(), CancelLevel::Interrupt);
+    }
+}
@@ -0,0 +1,480 @@
+//! Comparison of two runs (`otto Diff <run-a> <run-b>`)
+//!
+//! Tasks are compared by their last attempt: status, duration, script hash,
+//! the params and env each run's `snapshot.yaml` recorded, and the values in
+//! `output.<task>.json`. Stdout logs are only diffed when asked for, since
+//! timestamps and paths make most logs differ between any two runs.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use serde_json::Value;
+use similar::TextDiff;
+
+use super::snapshot::{RunSnapshot, TaskSnapshot};
+use super::state::{RunRecord, TaskRecord};
+
+/// Lines of context around each change in a stdout diff
+const LOG_CONTEXT_LINES: usize = 3;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffOptions {
+    /// Relative change in duration that makes a task slower or faster
+    pub threshold_percent: f64,
+    /// Changes in duration smaller than this are noise, whatever the percentage
+    pub min_seconds: f64,
+    /// Include a unified diff of stdout logs that differ
+    pub logs: bool,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        Self {
+            threshold_percent: 20.0,
+            min_seconds: 1.0,
+            logs: false,
+        }
+    }
+}
+
+/// One of the runs being compared
+#[derive(Debug, Clone)]
+pub struct RunSide {
+    pub run: RunRecord,
+    /// Last attempt of each task
+    pub tasks: BTreeMap<String, TaskRecord>,
+    pub snapshot: Option<RunSnapshot>,
+}
+
+impl RunSide {
+    /// Collect a run's task rows and find its snapshot through the task log paths
+    pub fn new(run: RunRecord, mut records: Vec<TaskRecord>) -> Self {
+        records.sort_by_key(|task| task.id);
+        let snapshot = RunSnapshot::for_tasks(&records);
+        let tasks = records.into_iter().map(|task| (task.name.clone(), task)).collect();
+        Self { run, tasks, snapshot }
+    }
+
+    fn task_dir(&self, name: &str) -> Option<&Path> {
+        self.tasks.get(name)?.stdout_path.as_deref()?.parent()
+    }
+
+    /// Top-level values of the task's `output.<task>.json`, as text
+    fn outputs(&self, name: &str) -> Option<BTreeMap<String, String>> {
+        let path = self.task_dir(name)?.join(format!("output.{name}.json"));
+        let content = std::fs::read_to_string(path).ok()?;
+        let Value::Object(values) = serde_json::from_str(&content).ok()? else {
+            return None;
+        };
+        Some(
+            values
+                .into_iter()
+                .map(|(key, value)| match value {
+                    Value::String(text) => (key, text),
+                    value => (key, value.to_string()),
+                })
+                .collect(),
+        )
+    }
+
+    fn task_snapshot(&self, name: &str) -> Option<&TaskSnapshot> {
+        self.snapshot.as_ref()?.tasks.get(name)
+    }
+
+    fn stdout_path(&self, name: &str) -> Option<PathBuf> {
+        self.tasks.get(name)?.stdout_path.clone()
+    }
+}
+
+/// A value that differs between the runs; `None` where a run doesn't have it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Change {
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct KeyChange {
+    pub key: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Trend {
+    Slower,
+    Faster,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DurationChange {
+    pub before: f64,
+    pub after: f64,
+    pub trend: Trend,
+}
+
+/// Everything that differs for one task
+#[derive(Debug, Clone, PartialEq, Default, Serialize)]
+pub struct TaskDiff {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<Change>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration: Option<DurationChange>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub script_hash: Option<Change>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub params: Vec<KeyChange>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub env: Vec<KeyChange>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub output: Vec<KeyChange>,
+    /// Unified diff of the stdout logs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stdout: Option<String>,
+}
+
+impl TaskDiff {
+    fn is_empty(&self) -> bool {
+        self.status.is_none()
+            && self.duration.is_none()
+            && self.script_hash.is_none()
+            && self.params.is_empty()
+            && self.env.is_empty()
+            && self.output.is_empty()
+            && self.stdout.is_none()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RunDiff {
+    pub run_a: i64,
+    pub run_b: i64,
+    /// Tasks that differ, by name
+    pub tasks: Vec<TaskDiff>,
+    /// Tasks that ran the same way in both runs
+    pub unchanged: usize,
+    /// Runs without a snapshot, whose params and env couldn't be compared
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub missing_snapshots: Vec<i64>,
+}
+
+pub fn diff_runs(a: &RunSide, b: &RunSide, options: &DiffOptions) -> RunDiff {
+    let names: BTreeSet<&String> = a.tasks.keys().chain(b.tasks.keys()).collect();
+    let mut tasks = Vec::new();
+    let mut unchanged = 0;
+    for name in names {
+        let diff = diff_task(name, a, b, options);
+        if diff.is_empty() {
+            unchanged += 1;
+        } else {
+            tasks.push(diff);
+        }
+    }
+
+    RunDiff {
+        run_a: a.run.id,
+        run_b: b.run.id,
+        tasks,
+        unchanged,
+        missing_snapshots: [a, b]
+            .iter()
+            .filter(|side| side.snapshot.is_none())
+            .map(|side| side.run.id)
+            .collect(),
+    }
+}
+
+fn diff_task(name: &str, a: &RunSide, b: &RunSide, options: &DiffOptions) -> TaskDiff {
+    let (before, after) = (a.tasks.get(name), b.tasks.get(name));
+    let mut diff = TaskDiff {
+        name: name.to_string(),
+        ..Default::default()
+    };
+
+    let status = |task: Option<&TaskRecord>| task.map(|task| task.status.as_str().to_string());
+    if status(before) != status(after) {
+        diff.status = Some(Change {
+            before: status(before),
+            after: status(after),
+        });
+    }
+    let (Some(before), Some(after)) = (before, after) else {
+        return diff;
+    };
+
+    if let (Some(old), Some(new)) = (before.duration_seconds, after.duration_seconds) {
+        diff.duration = duration_change(old, new, options);
+    }
+    if let (Some(old), Some(new)) = (&before.script_hash, &after.script_hash)
+        && old != new
+    {
+        diff.script_hash = Some(Change {
+            before: Some(old.clone()),
+            after: Some(new.clone()),
+        });
+    }
+
+    if let (Some(old), Some(new)) = (a.task_snapshot(name), b.task_snapshot(name)) {
+        diff.params = key_changes(&old.params, &new.params);
+        diff.env = key_changes(&old.env, &new.env);
+    }
+    let (old, new) = (a.outputs(name), b.outputs(name));
+    if old.is_some() || new.is_some() {
+        diff.output = key_changes(&old.unwrap_or_default(), &new.unwrap_or_default());
+    }
+
+    if options.logs
+        && let (Some(old), Some(new)) = (a.stdout_path(name), b.stdout_path(name))
+    {
+        diff.stdout = log_diff(&old, &new, a.run.id, b.run.id);
+    }
+    diff
+}
+
+/// Whether a task got slower or faster by more than both thresholds
+fn duration_change(before: f64, after: f64, options: &DiffOptions) -> Option<DurationChange> {
+    let delta = after - before;
+    if delta.abs() < options.min_seconds {
+        return None;
+    }
+    if before > 0.0 && delta.abs() / before * 100.0 < options.threshold_percent {
+        return None;
+    }
+    Some(DurationChange {
+        before,
+        after,
+        trend: if delta > 0.0 { Trend::Slower } else { Trend::Faster },
+    })
+}
+
+fn key_changes(before: &BTreeMap<String, String>, after: &BTreeMap<String, String>) -> Vec<KeyChange> {
+    let keys: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+    keys.into_iter()
+        .filter(|key| before.get(*key) != after.get(*key))
+        .map(|key| KeyChange {
+            key: key.clone(),
+            before: before.get(key).cloned(),
+            after: after.get(key).cloned(),
+        })
+        .collect()
+}
+
+fn log_diff(before: &Path, after: &Path, run_a: i64, run_b: i64) -> Option<String> {
+    let old = std::fs::read_to_string(before).ok()?;
+    let new = std::fs::read_to_string(after).ok()?;
+    if old == new {
+        return None;
+    }
+    let diff = TextDiff::from_lines(&old, &new)
+        .unified_diff()
+        .context_radius(LOG_CONTEXT_LINES)
+        .header(&format!("run {run_a}"), &format!("run {run_b}"))
+        .to_string();
+    Some(diff)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::state::{RunStatus, TaskStatus};
+
+    fn run(id: i64) -> RunRecord {
+        RunRecord {
+            id,
+            project_id: 1,
+            timestamp: 100 * id as u64,
+            status: RunStatus::Success,
+            duration_seconds: None,
+            size_bytes: None,
+            ottofile_path: None,
+            cwd: None,
+            user: None,
+            hostname: None,
+            args: None,
+            ended_at: None,
+            resume_count: 0,
+        }
+    }
+
+    fn task(id: i64, name: &str, status: TaskStatus, hash: &str, duration: f64) -> TaskRecord {
+        TaskRecord {
+            id,
+            run_id: 1,
+            name: name.to_string(),
+            status,
+            script_hash: Some(hash.to_string()),
+            exit_code: Some(0),
+            started_at: None,
+            ended_at: None,
+            duration_seconds: Some(duration),
+            stdout_path: None,
+            stderr_path: None,
+            script_path: None,
+            attempt: 1,
+            skip_reason: None,
+        }
+    }
+
+    /// Put a task's stdout and output file where a run directory would have them
+    fn write_task_files(run_dir: &Path, task: &mut TaskRecord, stdout: &str, output: &str) {
+        let task_dir = run_dir.join("tasks").join(&task.name);
+        std::fs::create_dir_all(&task_dir).unwrap();
+        std::fs::write(task_dir.join("stdout.log"), stdout).unwrap();
+        std::fs::write(task_dir.join(format!("output.{}.json", task.name)), output).unwrap();
+        task.stdout_path = Some(task_dir.join("stdout.log"));
+    }
+
+    #[test]
+    fn test_duration_thresholds() {
+        let options = DiffOptions::default();
+        assert_eq!(duration_change(10.0, 11.5, &options), None, "below the percentage");
+        assert_eq!(duration_change(2.0, 2.9, &options), None, "below the minimum seconds");
+        assert_eq!(
+            duration_change(10.0, 13.0, &options).map(|change| change.trend),
+            Some(Trend::Slower)
+        );
+        assert_eq!(
+            duration_change(10.0, 5.0, &options).map(|change| change.trend),
+            Some(Trend::Faster)
+        );
+        assert_eq!(
+            duration_change(0.0, 2.0, &options).map(|change| change.trend),
+            Some(Trend::Slower)
+        );
+    }
+
+    #[test]
+    fn test_diff_runs() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let (dir_a, dir_b) = (temp_dir.path().join("100"), temp_dir.path().join("200"));
+
+        let mut build_a = task(1, "build", TaskStatus::Completed, "aaa", 10.0);
+        write_task_files(
+            &dir_a,
+            &mut build_a,
+            "compiling\nok\n",
+            r#"{"version": "1.0", "size": 10}"#,
+        );
+        let mut build_b = task(2, "build", TaskStatus::Completed, "bbb", 30.0);
+        write_task_files(
+            &dir_b,
+            &mut build_b,
+            "compiling\nwarning\nok\n",
+            r#"{"version": "1.1", "size": 10}"#,
+        );
+
+        let snapshot = |profile: &str| RunSnapshot {
+            tasks: BTreeMap::from([(
+                "build".to_string(),
+                TaskSnapshot {
+                    params: BTreeMap::from([("profile".to_string(), profile.to_string())]),
+                    env: BTreeMap::from([("CC".to_string(), "clang".to_string())]),
+                },
+            )]),
+        };
+        for (dir, profile) in [(&dir_a, "debug"), (&dir_b, "release")] {
+            std::fs::write(
+                dir.join(crate::executor::snapshot::SNAPSHOT_FILE),
+                serde_yaml::to_string(&snapshot(profile)).unwrap(),
+            )
+            .unwrap();
+        }
+
+        let a = RunSide::new(
+            run(1),
+            vec![
+                build_a,
+                task(3, "lint", TaskStatus::Completed, "lll", 1.0),
+                task(4, "test", TaskStatus::Failed, "ttt", 5.0),
+                task(5, "test", TaskStatus::Completed, "ttt", 5.0),
+            ],
+        );
+        let b = RunSide::new(
+            run(2),
+            vec![
+                build_b,
+                task(6, "lint", TaskStatus::Completed, "lll", 1.2),
+                task(7, "test", TaskStatus::Failed, "ttt", 5.0),
+                task(8, "deploy", TaskStatus::DependencyFailed, "ddd", 0.0),
+            ],
+        );
+        assert!(a.snapshot.is_some());
+
+        let diff = diff_runs(
+            &a,
+            &b,
+            &DiffOptions {
+                logs: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(diff.unchanged, 1, "lint only changed by noise");
+        assert!(diff.missing_snapshots.is_empty());
+        let names: Vec<&str> = diff.tasks.iter().map(|task| task.name.as_str()).collect();
+        assert_eq!(names, vec!["build", "deploy", "test"]);
+
+        let build = &diff.tasks[0];
+        assert_eq!(build.status, None);
+        assert_eq!(build.duration.as_ref().map(|change| change.trend), Some(Trend::Slower));
+        assert_eq!(
+            build.script_hash,
+            Some(Change {
+                before: Some("aaa".to_string()),
+                after: Some("bbb".to_string())
+            })
+        );
+        assert_eq!(
+            build.params,
+            vec![KeyChange {
+                key: "profile".to_string(),
+                before: Some("debug".to_string()),
+                after: Some("release".to_string())
+            }]
+        );
+        assert!(build.env.is_empty());
+        assert_eq!(
+            build.output,
+            vec![KeyChange {
+                key: "version".to_string(),
+                before: Some("1.0".to_string()),
+                after: Some("1.1".to_string())
+            }]
+        );
+        let stdout = build.stdout.as_deref().unwrap();
+        assert!(stdout.contains("--- run 1\n+++ run 2\n"), "{stdout}");
+        assert!(stdout.contains("+warning\n"), "{stdout}");
+
+        let deploy = &diff.tasks[1];
+        assert_eq!(
+            deploy.status,
+            Some(Change {
+                before: None,
+                after: Some("dependency_failed".to_string())
+            })
+        );
+
+        // The last attempt counts: test passed on retry in run 1
+        let test = &diff.tasks[2];
+        assert_eq!(
+            test.status.as_ref().and_then(|change| change.before.as_deref()),
+            Some("completed")
+        );
+        assert_eq!(
+            test.status.as_ref().and_then(|change| change.after.as_deref()),
+            Some("failed")
+        );
+    }
+
+    #[test]
+    fn test_runs_without_snapshot() {
+        let a = RunSide::new(run(1), vec![task(1, "build", TaskStatus::Completed, "aaa", 1.0)]);
+        let b = RunSide::new(run(2), vec![task(2, "build", TaskStatus::Completed, "aaa", 1.0)]);
+        let diff = diff_runs(&a, &b, &DiffOptions::default());
+        assert!(diff.tasks.is_empty());
+        assert_eq!(diff.unchanged, 1);
+        assert_eq!(diff.missing_snapshots, vec![1, 2]);
+    }
+}
@@ -0,0 +1,146 @@
+//! Content fingerprints for `fingerprint: content` up-to-date checks
+//!
+//! A fingerprint covers everything that determines a task's outputs: the
+//! script, the contents of its resolved input files, its environment and its
+//! parameter values. Two runs with the same fingerprint are expected to
+//! produce the same outputs, so the second one can be skipped.
+
+use eyre::Result;
+use sha2::{Digest, Sha256};
+
+use crate::cfg::param::Value;
+use crate::executor::task::Task;
+
+/// Compute the content fingerprint of a task
+pub async fn compute_fingerprint(task: &Task) -> Result<String> {
+    let mut hasher = Sha256::new();
+
+    update_field(&mut hasher, "script", task.action.as_bytes());
+
+    let mut file_deps: Vec<&String> = task.file_deps.iter().collect();
+    file_deps.sort();
+    file_deps.dedup();
+    for path in file_deps {
+        let content_hash = match tokio::fs::read(path).await {
+            Ok(bytes) => hex::encode(Sha256::digest(&bytes)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => "missing".to_string(),
+            Err(e) => return Err(eyre::eyre!("Failed to read input file {path}: {e}")),
+        };
+        update_field(&mut hasher, "input", path.as_bytes());
+        update_field(&mut hasher, "content", content_hash.as_bytes());
+    }
+
+    let mut envs: Vec<(&String, &String)> = task.envs.iter().collect();
+    envs.sort();
+    for (key, value) in envs {
+        update_field(&mut hasher, "env", key.as_bytes());
+        update_field(&mut hasher, "value", value.as_bytes());
+    }
+
+    let mut values: Vec<(&String, &Value)> = task.values.iter().collect();
+    values.sort_by(|a, b| a.0.cmp(b.0));
+    for (key, value) in values {
+        update_field(&mut hasher, "param", key.as_bytes());
+        update_field(&mut hasher, "value", canonical_value(value).as_bytes());
+    }
+
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Feed a tagged, length-prefixed field into the hasher so adjacent fields can't collide
+fn update_field(hasher: &mut Sha256, tag: &str, bytes: &[u8]) {
+    hasher.update(tag.as_bytes());
+    hasher.update((bytes.len() as u64).to_le_bytes());
+    hasher.update(bytes);
+}
+
+/// Order-independent textual form of a parameter value
+fn canonical_value(value: &Value) -> String {
+    match value {
+        Value::Item(item) => format!("item:{item}"),
+        Value::List(items) => format!("list:{}", items.join("\u{1f}")),
+        Value::Dict(dict) => {
+            let mut pairs: Vec<String> = dict.iter().map(|(k, v)| format!("{k}={v}")).collect();
+            pairs.sort();
+            format!("dict:{}", pairs.join("\u{1f}"))
+        }
+        Value::Empty => "empty".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    fn task_with_input(input: &std::path::Path) -> Task {
+        Task::new(
+            "build".to_string(),
+            None,
+            vec![],
+            vec![input.to_string_lossy().to_string()],
+            vec![],
+            HashMap::from([("MODE".to_string(), "release".to_string())]),
+            HashMap::new(),
+            "echo build".to_string(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_fingerprint_ignores_mtime_but_tracks_content() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let input = temp_dir.path().join("input.txt");
+        std::fs::write(&input, "v1")?;
+        let task = task_with_input(&input);
+
+        let first = compute_fingerprint(&task).await?;
+
+        // Rewriting identical content bumps the mtime but not the fingerprint
+        std::fs::write(&input, "v1")?;
+        assert_eq!(compute_fingerprint(&task).await?, first);
+
+        std::fs::write(&input, "v2")?;
+        assert_ne!(compute_fingerprint(&task).await?, first);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fingerprint_tracks_script_env_and_params() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let input = temp_dir.path().join("input.txt");
+        std::fs::write(&input, "v1")?;
+        let task = task_with_input(&input);
+        let base = compute_fingerprint(&task).await?;
+
+        let mut changed_script = task.clone();
+        changed_script.action = "echo other".to_string();
+        assert_ne!(compute_fingerprint(&changed_script).await?, base);
+
+        let mut changed_env = task.clone();
+        changed_env.envs.insert("MODE".to_string(), "debug".to_string());
+        assert_ne!(compute_fingerprint(&changed_env).await?, base);
+
+        let mut changed_params = task.clone();
+        changed_params
+            .values
+            .insert("target".to_string(), Value::Item("x86".to_string()));
+        assert_ne!(compute_fingerprint(&changed_params).await?, base);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fingerprint_missing_input() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let input = temp_dir.path().join("absent.txt");
+        let task = task_with_input(&input);
+
+        let missing = compute_fingerprint(&task).await?;
+        std::fs::write(&input, "")?;
+        assert_ne!(compute_fingerprint(&task).await?, missing);
+
+        Ok(())
+    }
+}
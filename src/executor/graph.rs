@@ -140,24 +140,7 @@ impl DagVisualizer {
         let executor_tasks: Vec<Task> = tasks
             .into_iter()
             .filter(|task| task.name != "graph") // Exclude graph task itself
-            .map(|parser_task| {
-                // Derive parent for subtasks (names with colons like "install:td")
-                let parent = if parser_task.name.contains(':') {
-                    parser_task.name.split(':').next().map(|s| s.to_string())
-                } else {
-                    None
-                };
-                Task::new(
-                    parser_task.name,
-                    parent,
-                    parser_task.task_deps,
-                    parser_task.file_deps,
-                    parser_task.output_deps,
-                    parser_task.envs,
-                    parser_task.values,
-                    parser_task.action,
-                )
-            })
+            .map(Task::from)
             .collect();
 
         Self::create_dag_from_tasks(executor_tasks)
@@ -982,6 +965,7 @@ mod tests {
                     ..Default::default()
                 }),
                 virtual_parent: false,
+                ..Default::default()
             },
         );
 
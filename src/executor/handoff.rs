@@ -0,0 +1,156 @@
+//! Handing the terminal from the TUI to an interactive task and back
+//!
+//! The TUI keeps the terminal in raw mode on the alternate screen, which is of
+//! no use to a task waiting for a `terraform apply` confirmation. Before such a
+//! task starts, the scheduler asks the TUI to step aside and waits until it
+//! has; once the task exits, the TUI takes the terminal back and redraws.
+
+use tokio::sync::watch;
+
+/// Who has the terminal. Only the scheduler moves it away from the TUI and only
+/// the TUI takes it back, so each side waits for the other's transition.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TerminalState {
+    /// The TUI is drawing
+    #[default]
+    Tui,
+    /// An interactive task is waiting for the TUI to step aside
+    Requested,
+    /// The TUI has left raw mode and the alternate screen; the task has the terminal
+    Released,
+    /// The task is done and the TUI may take the terminal back
+    Returned,
+    /// The TUI has exited, so the terminal is no longer shared
+    Closed,
+}
+
+/// Shared between the scheduler and the TUI to coordinate terminal ownership
+#[derive(Clone, Debug)]
+pub struct TerminalHandoff {
+    tx: watch::Sender<TerminalState>,
+}
+
+/// The terminal on loan to an interactive task; returned to the TUI when dropped
+#[derive(Debug)]
+pub struct TerminalLease {
+    tx: watch::Sender<TerminalState>,
+}
+
+impl Default for TerminalHandoff {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TerminalHandoff {
+    pub fn new() -> Self {
+        Self {
+            tx: watch::Sender::new(TerminalState::Tui),
+        }
+    }
+
+    /// Ask the TUI for the terminal and wait until it has stepped aside
+    pub async fn acquire(&self) -> TerminalLease {
+        self.tx.send_modify(|state| {
+            *state = match *state {
+                TerminalState::Tui => TerminalState::Requested,
+                // The TUI hasn't taken it back since the last task, so it can pass straight on
+                TerminalState::Returned => TerminalState::Released,
+                other => other,
+            }
+        });
+        let mut rx = self.tx.subscribe();
+        // The sender lives in self, so waiting can't fail
+        let _ = rx
+            .wait_for(|state| matches!(state, TerminalState::Released | TerminalState::Closed))
+            .await;
+        TerminalLease { tx: self.tx.clone() }
+    }
+
+    pub fn state(&self) -> TerminalState {
+        *self.tx.borrow()
+    }
+
+    /// Record that the TUI has left the terminal to the task that requested it
+    pub fn released(&self) {
+        self.transition(TerminalState::Requested, TerminalState::Released);
+    }
+
+    /// Take the terminal back for the TUI; false while a task still has it
+    pub fn take_back(&self) -> bool {
+        self.transition(TerminalState::Returned, TerminalState::Tui)
+    }
+
+    /// The TUI has exited; tasks get the terminal without waiting from now on
+    pub fn close(&self) {
+        self.tx.send_replace(TerminalState::Closed);
+    }
+
+    fn transition(&self, from: TerminalState, to: TerminalState) -> bool {
+        self.tx.send_if_modified(|state| {
+            let matched = *state == from;
+            if matched {
+                *state = to;
+            }
+            matched
+        })
+    }
+}
+
+impl Drop for TerminalLease {
+    fn drop(&mut self) {
+        self.tx.send_if_modified(|state| {
+            let released = *state == TerminalState::Released;
+            if released {
+                *state = TerminalState::Returned;
+            }
+            released
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_task_waits_for_the_tui_to_step_aside() {
+        let handoff = TerminalHandoff::new();
+
+        let task = tokio::spawn({
+            let handoff = handoff.clone();
+            async move { handoff.acquire().await }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!task.is_finished());
+        assert_eq!(handoff.state(), TerminalState::Requested);
+        assert!(!handoff.take_back());
+
+        handoff.released();
+        let lease = task.await.unwrap();
+        assert_eq!(handoff.state(), TerminalState::Released);
+
+        drop(lease);
+        assert_eq!(handoff.state(), TerminalState::Returned);
+
+        // A second task before the TUI has taken the terminal back doesn't wait
+        let lease = handoff.acquire().await;
+        assert_eq!(handoff.state(), TerminalState::Released);
+        drop(lease);
+
+        assert!(handoff.take_back());
+        assert_eq!(handoff.state(), TerminalState::Tui);
+    }
+
+    #[tokio::test]
+    async fn test_closed_tui_hands_over_immediately() {
+        let handoff = TerminalHandoff::new();
+        handoff.close();
+
+        let lease = handoff.acquire().await;
+        drop(lease);
+        assert_eq!(handoff.state(), TerminalState::Closed);
+        assert!(!handoff.take_back());
+    }
+}
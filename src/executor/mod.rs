@@ -1,15 +1,28 @@
 pub mod action;
+pub mod cancel;
 pub mod colors;
+pub mod diff;
+pub mod fingerprint;
 pub mod graph;
+pub mod handoff;
 pub mod output;
+pub mod output_cache;
+pub mod progress;
 pub mod pruning;
+#[cfg(unix)]
+pub mod pty;
+pub mod report;
 pub mod scheduler;
+pub mod service;
+pub mod snapshot;
 pub mod state;
 pub mod task;
+pub mod trace;
 pub mod visualizer;
+pub mod workflow;
 pub mod workspace;
 
-pub use action::{ActionProcessor, BashProcessor, ProcessedAction, PythonProcessor, ScriptProcessor};
+pub use action::{ActionProcessor, BashProcessor, GenericProcessor, ProcessedAction, PythonProcessor, ScriptProcessor};
 pub use colors::{
     colorize_task_name, colorize_task_prefix, get_task_color, get_task_color_combination, set_global_task_order,
 };
@@ -1,10 +1,14 @@
 use std::{
+    borrow::Cow,
     io::{self, Write},
     path::{Path, PathBuf},
+    sync::Arc,
     time::SystemTime,
 };
 
 use eyre::Result;
+use once_cell::sync::Lazy;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use tokio::{
     fs::File,
@@ -12,7 +16,11 @@ use tokio::{
     sync::broadcast,
 };
 
+use crate::cfg::otto::OutputMode;
+
 use super::colors::colorize_task_prefix;
+use super::progress::{self, Progress, ProgressReporter};
+use super::workflow::{self, WorkflowCommand};
 
 /// Type of output stream
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -47,8 +55,18 @@ pub enum TuiTaskStatus {
     Completed,
     /// Task failed with an error
     Failed,
+    /// Task failed, but has `allow_failure: true`
+    FailedAllowed,
     /// Task was skipped
     Skipped,
+    /// Task outputs were restored from the local output cache
+    Cached,
+    /// Task was terminated after exceeding its timeout
+    TimedOut,
+    /// Task was not run because one of its dependencies failed
+    DependencyFailed,
+    /// Task was stopped or never started because the run was cancelled
+    Cancelled,
 }
 
 /// Message sent from scheduler to TUI with task updates
@@ -71,6 +89,8 @@ pub enum TaskMessage {
         timestamp: SystemTime,
         duration_ms: u64,
     },
+    /// Task reported how far along it is
+    Progress { task_name: String, progress: Progress },
 }
 
 /// A writer that writes to both a file and a terminal
@@ -83,8 +103,17 @@ pub struct TeeWriter {
     task_name: String,
     /// Whether to suppress terminal output (for TUI mode)
     suppress_terminal: bool,
+    /// Whether to remove ANSI escape sequences before writing to the file
+    strip_ansi: bool,
+    /// How lines are shown on the terminal
+    output_mode: OutputMode,
+    /// Where lines are held in grouped mode, shared by the task's stdout and stderr
+    grouped: GroupedOutput,
 }
 
+/// A task's terminal output in grouped mode, in arrival order, with whether each chunk went to stderr
+pub type GroupedOutput = Arc<std::sync::Mutex<Vec<(bool, String)>>>;
+
 impl TeeWriter {
     pub async fn new(file: File, is_stderr: bool, task_name: String, suppress_terminal: bool) -> Self {
         Self {
@@ -92,23 +121,59 @@ impl TeeWriter {
             is_stderr,
             task_name,
             suppress_terminal,
+            strip_ansi: false,
+            output_mode: OutputMode::default(),
+            grouped: GroupedOutput::default(),
         }
     }
 
     pub async fn write(&mut self, data: &[u8]) -> Result<()> {
-        // Always write to file (no colors)
-        self.file.write_all(data).await?;
+        self.write_file(data).await?;
+        self.write_terminal(&String::from_utf8_lossy(data))
+    }
+
+    /// Log a workflow command as written, but show the terminal its readable form (if any)
+    pub async fn write_command(&mut self, data: &[u8], command: &WorkflowCommand) -> Result<()> {
+        self.write_file(data).await?;
+        match command.terminal_line() {
+            Some(line) => self.write_terminal(&format!("{line}\n")),
+            None => Ok(()),
+        }
+    }
+
+    async fn write_file(&mut self, data: &[u8]) -> Result<()> {
+        // Always write to file (no colors of our own; the task's are kept unless stripped)
+        if self.strip_ansi {
+            self.file
+                .write_all(strip_ansi(&String::from_utf8_lossy(data)).as_bytes())
+                .await?;
+        } else {
+            self.file.write_all(data).await?;
+        }
+        Ok(())
+    }
 
+    fn write_terminal(&mut self, text: &str) -> Result<()> {
         // Conditionally write to terminal (suppressed in TUI mode)
         if !self.suppress_terminal {
             // Write to terminal with colored task name prefix
-            let colored_prefix = colorize_task_prefix(&self.task_name);
-            let terminal_output = format!("{} {}", colored_prefix, String::from_utf8_lossy(data));
-            if self.is_stderr {
-                eprint!("{terminal_output}");
-            } else {
-                print!("{terminal_output}");
+            let terminal_output = match self.output_mode {
+                OutputMode::Raw => text.to_string(),
+                OutputMode::Prefixed | OutputMode::Grouped => {
+                    format!("{} {text}", colorize_task_prefix(&self.task_name))
+                }
+            };
+            if self.output_mode == OutputMode::Grouped {
+                self.grouped.lock().unwrap().push((self.is_stderr, terminal_output));
+                return Ok(());
             }
+            progress::suspend(|| {
+                if self.is_stderr {
+                    eprint!("{terminal_output}");
+                } else {
+                    print!("{terminal_output}");
+                }
+            });
 
             // Ensure terminal output is flushed
             if self.is_stderr {
@@ -122,6 +187,60 @@ impl TeeWriter {
     }
 }
 
+/// Print the output held back in grouped mode as one block
+pub fn flush_grouped(grouped: &GroupedOutput) -> Result<()> {
+    let chunks = std::mem::take(&mut *grouped.lock().unwrap());
+    if chunks.is_empty() {
+        return Ok(());
+    }
+    progress::suspend(|| {
+        let mut stdout = io::stdout().lock();
+        let mut stderr = io::stderr().lock();
+        // Flush after every chunk so stdout and stderr lines keep their order
+        for (is_stderr, text) in &chunks {
+            let stream: &mut dyn Write = if *is_stderr { &mut stderr } else { &mut stdout };
+            stream.write_all(text.as_bytes())?;
+            stream.flush()?;
+        }
+        Ok::<_, io::Error>(())
+    })?;
+    Ok(())
+}
+
+/// CSI sequences (colors, cursor movement), OSC sequences (titles, hyperlinks) and two-byte escapes
+static ANSI_ESCAPE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\x1b(\[[0-?]*[ -/]*[@-~]|\][^\x07\x1b]*(\x07|\x1b\\)|[@-Z\\-_])").unwrap());
+
+/// Remove ANSI escape sequences from a line of output
+pub fn strip_ansi(text: &str) -> Cow<'_, str> {
+    ANSI_ESCAPE.replace_all(text, "")
+}
+
+/// Print a finished task's captured logs to the terminal with its prefix.
+///
+/// Used in quiet mode, where output is only written to the log files while the
+/// task runs and is shown afterwards if the task fails.
+pub async fn replay_task_logs(task_name: &str, stdout_file: &Path, stderr_file: &Path) -> Result<()> {
+    let colored_prefix = colorize_task_prefix(task_name);
+
+    for (path, is_stderr) in [(stdout_file, false), (stderr_file, true)] {
+        let Ok(content) = tokio::fs::read_to_string(path).await else {
+            continue;
+        };
+        for line in content.lines() {
+            if is_stderr {
+                eprintln!("{colored_prefix} {line}");
+            } else {
+                println!("{colored_prefix} {line}");
+            }
+        }
+    }
+
+    io::stdout().flush()?;
+    io::stderr().flush()?;
+    Ok(())
+}
+
 /// Manages output streams for a task
 #[derive(Debug, Clone)]
 pub struct TaskStreams {
@@ -131,6 +250,14 @@ pub struct TaskStreams {
     pub stderr_file: PathBuf,
     /// Broadcast channel for real-time output
     pub output_tx: broadcast::Sender<TaskOutput>,
+    /// Remove ANSI escape sequences from the log files (the terminal and TUI keep them)
+    pub strip_ansi: bool,
+    /// Where `::progress` lines are reported to
+    pub progress: Option<ProgressReporter>,
+    /// How output is shown on the terminal
+    pub output_mode: OutputMode,
+    /// Output held back in grouped mode until the task finishes
+    pub grouped: GroupedOutput,
 }
 
 impl TaskStreams {
@@ -153,16 +280,23 @@ impl TaskStreams {
             stdout_file,
             stderr_file,
             output_tx,
+            strip_ansi: false,
+            progress: None,
+            output_mode: OutputMode::default(),
+            grouped: GroupedOutput::default(),
         })
     }
 
+    /// Tee a task's output stream to its log file, the terminal and `output_tx`.
+    /// Returns the workflow commands (`::set-output`, `::error`, ...) found on stdout;
+    /// `::progress` lines go to the progress reporter as they arrive.
     pub async fn process_output(
         &self,
         task_name: String,
         output_type: OutputType,
         mut reader: impl AsyncBufReadExt + Unpin,
         suppress_terminal: bool,
-    ) -> Result<()> {
+    ) -> Result<Vec<WorkflowCommand>> {
         let output_file = match output_type {
             OutputType::Stdout => &self.stdout_file,
             OutputType::Stderr => &self.stderr_file,
@@ -176,8 +310,12 @@ impl TaskStreams {
             suppress_terminal,
         )
         .await;
+        writer.strip_ansi = self.strip_ansi;
+        writer.output_mode = self.output_mode;
+        writer.grouped = self.grouped.clone();
 
         let mut line = String::new();
+        let mut commands = Vec::new();
 
         while let Ok(n) = reader.read_line(&mut line).await {
             if n == 0 {
@@ -192,7 +330,28 @@ impl TaskStreams {
             };
 
             // Write to both file and terminal
-            writer.write(line.as_bytes()).await?;
+            let command = match output_type {
+                OutputType::Stdout => workflow::parse(&line),
+                OutputType::Stderr => None,
+            };
+            match command {
+                Some(command @ WorkflowCommand::Progress(_)) => {
+                    match &self.progress {
+                        Some(reporter) if reporter.shows_bar() => {
+                            writer.write_command(line.as_bytes(), &command).await?
+                        }
+                        _ => writer.write(line.as_bytes()).await?,
+                    }
+                    if let (Some(reporter), WorkflowCommand::Progress(update)) = (&self.progress, command) {
+                        reporter.report(update);
+                    }
+                }
+                Some(command) => {
+                    writer.write_command(line.as_bytes(), &command).await?;
+                    commands.push(command);
+                }
+                None => writer.write(line.as_bytes()).await?,
+            }
 
             // Broadcast for real-time monitoring
             let _ = self.output_tx.send(output);
@@ -200,7 +359,7 @@ impl TaskStreams {
             line.clear();
         }
 
-        Ok(())
+        Ok(commands)
     }
 
     pub async fn read_output(&self, output_type: OutputType) -> Result<Vec<String>> {
@@ -251,6 +410,124 @@ mod tests {
         assert_eq!(received.content, "line 1\n");
     }
 
+    #[tokio::test]
+    async fn test_workflow_commands_are_collected_and_logged() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let streams = TaskStreams::new("test_task", temp_dir.path()).await.unwrap();
+
+        let stdout = "::group::Build\ncompiling\n::endgroup::\n::set-output version=1.2\n";
+        let commands = streams
+            .process_output(
+                "test_task".to_string(),
+                OutputType::Stdout,
+                std::io::Cursor::new(stdout),
+                true,
+            )
+            .await
+            .unwrap();
+        assert_eq!(commands.len(), 3);
+        assert_eq!(
+            commands[2],
+            WorkflowCommand::SetOutput {
+                key: "version".to_string(),
+                value: "1.2".to_string()
+            }
+        );
+        // The log keeps the commands as written
+        assert_eq!(streams.read_output(OutputType::Stdout).await.unwrap().len(), 4);
+
+        // Only stdout carries commands
+        let commands = streams
+            .process_output(
+                "test_task".to_string(),
+                OutputType::Stderr,
+                std::io::Cursor::new("::error::nope\n"),
+                true,
+            )
+            .await
+            .unwrap();
+        assert!(commands.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_grouped_output_is_held_until_flushed() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut streams = TaskStreams::new("test_task", temp_dir.path()).await.unwrap();
+        streams.output_mode = OutputMode::Grouped;
+
+        streams
+            .process_output(
+                "test_task".to_string(),
+                OutputType::Stdout,
+                std::io::Cursor::new("out\n"),
+                false,
+            )
+            .await
+            .unwrap();
+        streams
+            .process_output(
+                "test_task".to_string(),
+                OutputType::Stderr,
+                std::io::Cursor::new("err\n"),
+                false,
+            )
+            .await
+            .unwrap();
+
+        let held: Vec<_> = streams
+            .grouped
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(is_stderr, text)| (*is_stderr, strip_ansi(text).into_owned()))
+            .collect();
+        assert_eq!(
+            held,
+            vec![
+                (false, "[test_task] out\n".to_string()),
+                (true, "[test_task] err\n".to_string())
+            ]
+        );
+
+        flush_grouped(&streams.grouped).unwrap();
+        assert!(streams.grouped.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_strip_ansi() {
+        assert_eq!(strip_ansi("\x1b[1;32mok\x1b[0m done"), "ok done");
+        assert_eq!(strip_ansi("\x1b]0;title\x07\x1b[2Kbar"), "bar");
+        assert_eq!(strip_ansi("plain [text]"), "plain [text]");
+    }
+
+    #[tokio::test]
+    async fn test_strip_ansi_only_affects_log_file() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut streams = TaskStreams::new("test_task", temp_dir.path()).await.unwrap();
+        streams.strip_ansi = true;
+        let mut rx = streams.output_tx.subscribe();
+
+        let mut cursor = std::io::Cursor::new("\x1b[31mred\x1b[0m\n");
+        streams
+            .process_output("test_task".to_string(), OutputType::Stdout, &mut cursor, true)
+            .await
+            .unwrap();
+
+        assert_eq!(streams.read_output(OutputType::Stdout).await.unwrap(), vec!["red"]);
+        assert_eq!(rx.try_recv().unwrap().content, "\x1b[31mred\x1b[0m\n");
+    }
+
+    #[tokio::test]
+    async fn test_replay_task_logs_tolerates_missing_files() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let stdout_file = temp_dir.path().join("stdout.log");
+        std::fs::write(&stdout_file, "line 1\n").unwrap();
+
+        replay_task_logs("test_task", &stdout_file, &temp_dir.path().join("missing.log"))
+            .await
+            .unwrap();
+    }
+
     #[tokio::test]
     async fn test_multiple_streams() {
         let temp_dir = tempfile::tempdir().unwrap();
@@ -0,0 +1,350 @@
+//! Local artifact cache for `fingerprint: content` tasks
+//!
+//! After a successful run the declared `output:` files of a task, together with
+//! its `output.<task>.json`, are copied into `$OTTO_HOME/output-cache/<key>/`.
+//! When a later run computes the same content fingerprint the outputs are
+//! restored from there instead of executing the task again.
+//!
+//! Entry layout:
+//!
+//! ```text
+//! output-cache/<key>/
+//!   manifest.json   # original output paths; mtime doubles as last-used time
+//!   files/<n>       # copy of the n-th output (file or directory)
+//!   output.json     # the task's structured output, if any
+//! ```
+
+use eyre::{Result, eyre};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::cfg::otto::RetentionSpec;
+use crate::executor::pruning::resolve_otto_home;
+
+const MANIFEST_FILE: &str = "manifest.json";
+const FILES_DIR: &str = "files";
+const OUTPUT_JSON_FILE: &str = "output.json";
+
+/// Where each cached output belongs when it is restored
+#[derive(Debug, Serialize, Deserialize)]
+struct Manifest {
+    task: String,
+    outputs: Vec<PathBuf>,
+}
+
+/// Content-addressed store of task outputs under `$OTTO_HOME`
+#[derive(Clone, Debug)]
+pub struct OutputCache {
+    root: PathBuf,
+}
+
+impl OutputCache {
+    /// Cache rooted at `<otto_home>/output-cache`
+    pub fn new(otto_home: &Path) -> Self {
+        Self {
+            root: otto_home.join("output-cache"),
+        }
+    }
+
+    /// Cache for the current `$OTTO_HOME`, or `None` when disabled (`max_cache_mb: 0`)
+    pub fn from_retention(retention: &RetentionSpec) -> Option<Self> {
+        if retention.max_cache_mb == 0 {
+            return None;
+        }
+        resolve_otto_home().ok().map(|otto_home| Self::new(&otto_home))
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Cache key for a task: its content fingerprint plus the name and output paths,
+    /// so tasks with identical inputs but different outputs never share an entry
+    pub fn key(task_name: &str, output_deps: &[String], fingerprint: &str) -> String {
+        let mut outputs: Vec<&String> = output_deps.iter().collect();
+        outputs.sort();
+
+        let mut hasher = Sha256::new();
+        hasher.update(fingerprint.as_bytes());
+        hasher.update([0]);
+        hasher.update(task_name.as_bytes());
+        for output in outputs {
+            hasher.update([0]);
+            hasher.update(output.as_bytes());
+        }
+        hex::encode(hasher.finalize())
+    }
+
+    /// Copy a task's outputs into the cache. Returns `false` (and stores nothing)
+    /// when a declared output is missing, since a partial entry could never be restored faithfully.
+    pub fn store(&self, key: &str, task_name: &str, output_deps: &[String], output_json: &Path) -> Result<bool> {
+        let mut outputs = Vec::new();
+        for pattern in output_deps {
+            let matches: Vec<PathBuf> = glob::glob(pattern)
+                .map(|paths| paths.flatten().collect())
+                .unwrap_or_default();
+            if matches.is_empty() {
+                log::debug!("Not caching task {task_name}: output {pattern} does not exist");
+                return Ok(false);
+            }
+            outputs.extend(matches);
+        }
+
+        // Build the entry next to its final location, then move it into place
+        let entry = self.root.join(key);
+        let staging = self.root.join(format!(".{key}.{}", std::process::id()));
+        if staging.exists() {
+            fs::remove_dir_all(&staging)?;
+        }
+        fs::create_dir_all(staging.join(FILES_DIR))?;
+
+        let result = (|| -> Result<()> {
+            for (index, output) in outputs.iter().enumerate() {
+                copy_path(output, &staging.join(FILES_DIR).join(index.to_string()))?;
+            }
+            if output_json.is_file() {
+                fs::copy(output_json, staging.join(OUTPUT_JSON_FILE))?;
+            }
+            let manifest = Manifest {
+                task: task_name.to_string(),
+                outputs,
+            };
+            fs::write(staging.join(MANIFEST_FILE), serde_json::to_string_pretty(&manifest)?)?;
+
+            if entry.exists() {
+                fs::remove_dir_all(&entry)?;
+            }
+            fs::rename(&staging, &entry)?;
+            Ok(())
+        })();
+
+        if result.is_err() {
+            let _ = fs::remove_dir_all(&staging);
+        }
+        result.map(|()| true)
+    }
+
+    /// Restore a cached entry's outputs and structured output. Returns `false` on a cache miss.
+    pub fn restore(&self, key: &str, output_json: &Path) -> Result<bool> {
+        let entry = self.root.join(key);
+        let manifest_path = entry.join(MANIFEST_FILE);
+        let Ok(manifest_content) = fs::read_to_string(&manifest_path) else {
+            return Ok(false);
+        };
+        let manifest: Manifest = serde_json::from_str(&manifest_content)
+            .map_err(|e| eyre!("Corrupt output cache manifest {}: {}", manifest_path.display(), e))?;
+
+        for (index, output) in manifest.outputs.iter().enumerate() {
+            remove_path(output)?;
+            if let Some(parent) = output.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            copy_path(&entry.join(FILES_DIR).join(index.to_string()), output)?;
+        }
+
+        let cached_json = entry.join(OUTPUT_JSON_FILE);
+        if cached_json.is_file() {
+            if let Some(parent) = output_json.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::copy(&cached_json, output_json)?;
+        }
+
+        // Mark the entry as recently used for eviction
+        fs::File::options()
+            .write(true)
+            .open(&manifest_path)?
+            .set_modified(SystemTime::now())?;
+
+        Ok(true)
+    }
+
+    /// Evict least recently used entries until the cache fits in `max_bytes`.
+    /// Returns the number of bytes freed.
+    pub fn evict_to_size(&self, max_bytes: u64) -> Result<u64> {
+        let entries = match fs::read_dir(&self.root) {
+            Ok(entries) => entries,
+            Err(_) => return Ok(0), // No cache yet
+        };
+
+        let mut cached: Vec<(SystemTime, u64, PathBuf)> = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            // `.<key>.<pid>` dirs are stores still being staged, possibly by another otto
+            if !path.is_dir() || entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            let Ok(last_used) = fs::metadata(path.join(MANIFEST_FILE)).and_then(|meta| meta.modified()) else {
+                continue;
+            };
+            cached.push((last_used, dir_size(&path), path));
+        }
+
+        let mut total: u64 = cached.iter().map(|(_, size, _)| size).sum();
+        cached.sort_by_key(|(last_used, _, _)| *last_used);
+
+        let mut freed = 0;
+        for (_, size, path) in cached {
+            if total <= max_bytes {
+                break;
+            }
+            log::debug!("Evicting output cache entry: {}", path.display());
+            fs::remove_dir_all(&path)?;
+            total -= size;
+            freed += size;
+        }
+
+        Ok(freed)
+    }
+}
+
+/// Copy a file or directory tree
+fn copy_path(src: &Path, dst: &Path) -> Result<()> {
+    if src.is_dir() {
+        fs::create_dir_all(dst)?;
+        for entry in fs::read_dir(src)? {
+            let entry = entry?;
+            copy_path(&entry.path(), &dst.join(entry.file_name()))?;
+        }
+    } else {
+        fs::copy(src, dst).map_err(|e| eyre!("Failed to copy {} to {}: {}", src.display(), dst.display(), e))?;
+    }
+    Ok(())
+}
+
+/// Remove a file or directory tree if it exists
+fn remove_path(path: &Path) -> Result<()> {
+    match fs::symlink_metadata(path) {
+        Ok(meta) if meta.is_dir() => fs::remove_dir_all(path)?,
+        Ok(_) => fs::remove_file(path)?,
+        Err(_) => {}
+    }
+    Ok(())
+}
+
+/// Total size of all files below a directory
+fn dir_size(path: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.metadata() {
+            Ok(meta) if meta.is_dir() => dir_size(&entry.path()),
+            Ok(meta) => meta.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_store_and_restore_roundtrip() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let cache = OutputCache::new(&temp_dir.path().join("home"));
+        let project = temp_dir.path().join("project");
+        fs::create_dir_all(project.join("dist/nested"))?;
+        fs::write(project.join("gen.rs"), "generated")?;
+        fs::write(project.join("dist/nested/app.js"), "bundle")?;
+        let output_json = project.join("output.gen.json");
+        fs::write(&output_json, r#"{"version":"1"}"#)?;
+
+        let outputs = vec![
+            project.join("gen.rs").to_string_lossy().to_string(),
+            project.join("dist").to_string_lossy().to_string(),
+        ];
+        let key = OutputCache::key("gen", &outputs, "fp");
+        assert!(cache.store(&key, "gen", &outputs, &output_json)?);
+
+        fs::remove_file(project.join("gen.rs"))?;
+        fs::remove_dir_all(project.join("dist"))?;
+        fs::remove_file(&output_json)?;
+
+        assert!(cache.restore(&key, &output_json)?);
+        assert_eq!(fs::read_to_string(project.join("gen.rs"))?, "generated");
+        assert_eq!(fs::read_to_string(project.join("dist/nested/app.js"))?, "bundle");
+        assert_eq!(fs::read_to_string(&output_json)?, r#"{"version":"1"}"#);
+
+        assert!(!cache.restore(&OutputCache::key("gen", &outputs, "other"), &output_json)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_store_skips_missing_outputs() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let cache = OutputCache::new(temp_dir.path());
+        let outputs = vec![temp_dir.path().join("absent").to_string_lossy().to_string()];
+
+        let key = OutputCache::key("gen", &outputs, "fp");
+        assert!(!cache.store(&key, "gen", &outputs, &temp_dir.path().join("output.json"))?);
+        assert!(!cache.root().join(&key).exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_key_depends_on_task_and_outputs() {
+        let outputs = vec!["/p/a".to_string(), "/p/b".to_string()];
+        let reordered = vec!["/p/b".to_string(), "/p/a".to_string()];
+        assert_eq!(
+            OutputCache::key("gen", &outputs, "fp"),
+            OutputCache::key("gen", &reordered, "fp")
+        );
+        assert_ne!(
+            OutputCache::key("gen", &outputs, "fp"),
+            OutputCache::key("other", &outputs, "fp")
+        );
+        assert_ne!(
+            OutputCache::key("gen", &outputs, "fp"),
+            OutputCache::key("gen", &outputs[..1], "fp")
+        );
+    }
+
+    #[test]
+    fn test_evict_removes_least_recently_used() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let cache = OutputCache::new(temp_dir.path());
+        let output = temp_dir.path().join("out.bin");
+        let outputs = vec![output.to_string_lossy().to_string()];
+        let output_json = temp_dir.path().join("output.json");
+
+        fs::write(&output, vec![0u8; 1000])?;
+        cache.store("old", "gen", &outputs, &output_json)?;
+        cache.store("new", "gen", &outputs, &output_json)?;
+
+        let an_hour_ago = SystemTime::now() - std::time::Duration::from_secs(3600);
+        fs::File::options()
+            .write(true)
+            .open(cache.root().join("old").join(MANIFEST_FILE))?
+            .set_modified(an_hour_ago)?;
+
+        let freed = cache.evict_to_size(1500)?;
+        assert!(freed >= 1000);
+        assert!(!cache.root().join("old").exists());
+        assert!(cache.root().join("new").exists());
+
+        assert_eq!(cache.evict_to_size(u64::MAX)?, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_evict_leaves_staging_dirs_alone() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let cache = OutputCache::new(temp_dir.path());
+
+        // A store another otto is still copying into
+        let staging = cache.root().join(".abc123.4242");
+        fs::create_dir_all(staging.join(FILES_DIR))?;
+        fs::write(staging.join(FILES_DIR).join("0"), vec![0u8; 1000])?;
+
+        assert_eq!(cache.evict_to_size(0)?, 0);
+        assert!(staging.join(FILES_DIR).join("0").exists());
+        Ok(())
+    }
+}
@@ -0,0 +1,244 @@
+//! Progress reported by long-running tasks
+//!
+//! A task reports progress with a `::progress 42/100 migrating users` line on
+//! stdout, or by writing the same `42/100 migrating users` text to the file
+//! named by `$OTTO_PROGRESS` when its stdout belongs to some other tool. `42%`
+//! is short for `42/100`, and a bare count works when the total isn't known.
+//!
+//! The TUI shows a gauge in the task's pane; on a terminal every task that
+//! reports progress gets a bar below the scrolling output until it finishes.
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tokio::sync::broadcast;
+
+use super::colors::colorize_task_prefix;
+use super::output::TaskMessage;
+
+/// How often the `$OTTO_PROGRESS` file is checked for updates
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Only the end of the progress file is read, in case a task keeps appending to it
+const TAIL_BYTES: u64 = 4096;
+
+/// Bars of every task currently reporting progress on the terminal
+static BARS: Lazy<MultiProgress> = Lazy::new(MultiProgress::new);
+static ACTIVE_BARS: AtomicUsize = AtomicUsize::new(0);
+
+/// One progress update from a task
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Progress {
+    pub current: u64,
+    pub total: Option<u64>,
+    pub message: String,
+}
+
+impl Progress {
+    /// Parse `42/100 message`, `42% message` or `42 message`
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let (amount, message) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+        let (current, total) = match amount.strip_suffix('%') {
+            Some(percent) => (percent.parse().ok()?, Some(100)),
+            None => match amount.split_once('/') {
+                Some((current, total)) => (current.parse().ok()?, Some(total.parse().ok()?)),
+                None => (amount.parse().ok()?, None),
+            },
+        };
+        Some(Self {
+            current,
+            total,
+            message: message.trim().to_string(),
+        })
+    }
+
+    /// Fraction done between 0 and 1, if the total is known
+    pub fn ratio(&self) -> Option<f64> {
+        self.total
+            .filter(|total| *total > 0)
+            .map(|total| (self.current as f64 / total as f64).min(1.0))
+    }
+}
+
+/// Run `f` (which prints task output) with the progress bars out of the way
+pub fn suspend<R>(f: impl FnOnce() -> R) -> R {
+    if ACTIVE_BARS.load(Ordering::Relaxed) == 0 { f() } else { BARS.suspend(f) }
+}
+
+/// Forwards a task's progress to the TUI and, on a terminal, to a progress bar
+#[derive(Debug, Clone)]
+pub struct ProgressReporter {
+    task_name: String,
+    message_tx: Option<broadcast::Sender<TaskMessage>>,
+    /// Created on the first update of each attempt, so tasks that never report progress get no bar
+    bar: Option<Arc<Mutex<Option<ProgressBar>>>>,
+}
+
+impl ProgressReporter {
+    pub fn new(task_name: String, message_tx: Option<broadcast::Sender<TaskMessage>>, show_bar: bool) -> Self {
+        Self {
+            task_name,
+            message_tx,
+            bar: show_bar.then(|| Arc::new(Mutex::new(None))),
+        }
+    }
+
+    pub fn report(&self, progress: Progress) {
+        if let Some(slot) = &self.bar {
+            let mut slot = slot.lock().unwrap_or_else(|e| e.into_inner());
+            let bar = slot.get_or_insert_with(|| {
+                ACTIVE_BARS.fetch_add(1, Ordering::Relaxed);
+                BARS.add(ProgressBar::no_length().with_prefix(colorize_task_prefix(&self.task_name).to_string()))
+            });
+            let template = match progress.total {
+                Some(_) => "{prefix} [{bar:30.cyan/blue}] {pos}/{len} {msg}",
+                None => "{prefix} {spinner} {pos} {msg}",
+            };
+            if let Ok(style) = ProgressStyle::default_bar().template(template) {
+                bar.set_style(style.progress_chars("█▓▒░ "));
+            }
+            match progress.total {
+                Some(total) => bar.set_length(total),
+                None => bar.unset_length(),
+            }
+            bar.set_message(progress.message.clone());
+            bar.set_position(progress.current);
+        }
+
+        if let Some(tx) = &self.message_tx {
+            let _ = tx.send(TaskMessage::Progress {
+                task_name: self.task_name.clone(),
+                progress,
+            });
+        }
+    }
+
+    /// Whether progress is drawn as a bar, which makes printing `::progress` lines redundant
+    pub fn shows_bar(&self) -> bool {
+        self.bar.is_some() && !BARS.is_hidden()
+    }
+
+    /// Remove the task's bar once an attempt has finished; a retry gets a new one
+    pub fn finish(&self) {
+        let bar = self
+            .bar
+            .as_ref()
+            .and_then(|slot| slot.lock().unwrap_or_else(|e| e.into_inner()).take());
+        if let Some(bar) = bar {
+            bar.finish_and_clear();
+            BARS.remove(&bar);
+            ACTIVE_BARS.fetch_sub(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Report the last complete line of the `$OTTO_PROGRESS` file whenever it changes.
+/// Runs until aborted; tasks may append to the file or overwrite it each time.
+pub async fn watch_file(path: PathBuf, reporter: ProgressReporter) {
+    let mut last = None;
+    loop {
+        tokio::time::sleep(POLL_INTERVAL).await;
+        let Some(line) = read_last_line(&path).await else {
+            continue;
+        };
+        if last.as_ref() != Some(&line) {
+            if let Some(progress) = Progress::parse(&line) {
+                reporter.report(progress);
+            }
+            last = Some(line);
+        }
+    }
+}
+
+async fn read_last_line(path: &PathBuf) -> Option<String> {
+    let mut file = tokio::fs::File::open(path).await.ok()?;
+    let len = file.metadata().await.ok()?.len();
+    file.seek(std::io::SeekFrom::Start(len.saturating_sub(TAIL_BYTES)))
+        .await
+        .ok()?;
+    let mut tail = Vec::new();
+    file.read_to_end(&mut tail).await.ok()?;
+
+    // Ignore a line that is still being written
+    let tail = String::from_utf8_lossy(&tail);
+    let complete = &tail[..tail.rfind('\n')?];
+    complete
+        .lines()
+        .rev()
+        .find(|line| !line.trim().is_empty())
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_progress() {
+        assert_eq!(
+            Progress::parse("42/100 migrating users"),
+            Some(Progress {
+                current: 42,
+                total: Some(100),
+                message: "migrating users".to_string()
+            })
+        );
+        assert_eq!(Progress::parse("75%").and_then(|p| p.ratio()), Some(0.75));
+        assert_eq!(
+            Progress::parse("7 files").map(|p| (p.total, p.message)),
+            Some((None, "files".to_string()))
+        );
+        assert_eq!(Progress::parse("half done"), None);
+        assert_eq!(Progress::parse("1/x"), None);
+    }
+
+    // Serial with the scheduler tests, whose tasks may draw bars too
+    #[test]
+    #[serial_test::serial]
+    fn test_bar_is_recreated_for_each_attempt() {
+        let reporter = ProgressReporter::new("migrate".to_string(), None, true);
+        let active = || ACTIVE_BARS.load(Ordering::Relaxed);
+        let before = active();
+
+        // Two attempts of a retried task, each reporting progress and then finishing
+        for _ in 0..2 {
+            reporter.report(Progress::parse("1/2 users").unwrap());
+            assert_eq!(active(), before + 1);
+            reporter.finish();
+            assert_eq!(active(), before);
+        }
+        // Finishing again without an update doesn't touch the count
+        reporter.finish();
+        assert_eq!(active(), before);
+    }
+
+    #[tokio::test]
+    async fn test_watch_file_reports_updates() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("progress");
+        let (tx, mut rx) = broadcast::channel(16);
+        let reporter = ProgressReporter::new("migrate".to_string(), Some(tx), false);
+        let watcher = tokio::spawn(watch_file(path.clone(), reporter));
+
+        std::fs::write(&path, "1/10 first\n3/10 third\n4/1").unwrap();
+        let message = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        watcher.abort();
+
+        let TaskMessage::Progress { task_name, progress } = message else {
+            panic!("expected a progress message");
+        };
+        assert_eq!(task_name, "migrate");
+        assert_eq!(progress.current, 3);
+        assert_eq!(progress.message, "third");
+    }
+}
@@ -1,5 +1,6 @@
 use crate::cfg::otto::RetentionSpec;
 use crate::cli::CleanCommand;
+use crate::executor::output_cache::OutputCache;
 use eyre::Result;
 use log::warn;
 use std::fs;
@@ -59,12 +60,28 @@ pub async fn auto_prune(otto_home: &Path, retention: &RetentionSpec) {
         warn!("Cache prune failed: {}", e);
     }
 
+    // Keep the output cache within its size budget
+    if let Err(e) = prune_output_cache(otto_home, retention.max_cache_mb) {
+        warn!("Output cache eviction failed: {}", e);
+    }
+
     // Touch marker file
     if let Err(e) = fs::File::create(&marker) {
         warn!("Failed to update .last_prune marker: {}", e);
     }
 }
 
+/// Evict least recently used output cache entries until the cache fits in `max_cache_mb`.
+///
+/// A budget of 0 means the cache is disabled, so every entry is removed.
+fn prune_output_cache(otto_home: &Path, max_cache_mb: u64) -> Result<()> {
+    let freed = OutputCache::new(otto_home).evict_to_size(max_cache_mb.saturating_mul(1024 * 1024))?;
+    if freed > 0 {
+        log::info!("Evicted {} bytes from the output cache", freed);
+    }
+    Ok(())
+}
+
 /// Remove orphaned cache entries that are no longer referenced by any run.
 ///
 /// For each project dir under otto_home, scans the `.cache/` directory
@@ -318,4 +335,23 @@ mod tests {
         assert!(!cache_dir.join("aabb1122.sh").exists());
         assert!(!cache_dir.join("ccdd3344.sh").exists());
     }
+
+    #[test]
+    fn test_prune_output_cache_respects_budget() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = OutputCache::new(temp_dir.path());
+        let output = temp_dir.path().join("out.bin");
+        fs::write(&output, vec![0u8; 4096]).unwrap();
+        let outputs = vec![output.to_string_lossy().to_string()];
+        cache
+            .store("entry", "gen", &outputs, &temp_dir.path().join("output.json"))
+            .unwrap();
+
+        prune_output_cache(temp_dir.path(), 1).unwrap();
+        assert!(cache.root().join("entry").exists());
+
+        // A zero budget disables the cache entirely
+        prune_output_cache(temp_dir.path(), 0).unwrap();
+        assert!(!cache.root().join("entry").exists());
+    }
 }
@@ -0,0 +1,95 @@
+//! Pseudo-terminals for `pty: true` tasks
+//!
+//! Tools like cargo, pytest and npm turn off colors and progress bars when
+//! their output is a pipe. A pty task gets one pseudo-terminal for stdout and
+//! another for stderr, so both look like a terminal to the child while staying
+//! separate in the logs. The scheduler reads the master sides like pipes: once
+//! every process holding the slave side has exited, reads fail with EIO, which
+//! ends the output stream just like EOF on a pipe.
+
+use std::fs::File;
+use std::io;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use std::process::Stdio;
+
+/// Size used when otto itself isn't attached to a terminal
+const DEFAULT_SIZE: (u16, u16) = (80, 24);
+
+/// An open pseudo-terminal pair
+pub struct Pty {
+    master: OwnedFd,
+    slave: OwnedFd,
+}
+
+impl Pty {
+    /// Open a pseudo-terminal sized like otto's own terminal. Output
+    /// post-processing is turned off so `\n` isn't rewritten to `\r\n`.
+    pub fn open() -> io::Result<Self> {
+        let (cols, rows) = crossterm::terminal::size().unwrap_or(DEFAULT_SIZE);
+        let size = libc::winsize {
+            ws_row: rows,
+            ws_col: cols,
+            ws_xpixel: 0,
+            ws_ypixel: 0,
+        };
+
+        let mut master = -1;
+        let mut slave = -1;
+        // SAFETY: openpty writes two fds into the provided pointers; name and termios may be null
+        let rc = unsafe { libc::openpty(&mut master, &mut slave, std::ptr::null_mut(), std::ptr::null(), &size) };
+        if rc != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: openpty succeeded, so both fds are open and owned by nobody else
+        let pty = unsafe {
+            Self {
+                master: OwnedFd::from_raw_fd(master),
+                slave: OwnedFd::from_raw_fd(slave),
+            }
+        };
+
+        // SAFETY: termios is plain data, filled in by tcgetattr before it is read
+        unsafe {
+            let mut termios: libc::termios = std::mem::zeroed();
+            if libc::tcgetattr(pty.slave.as_raw_fd(), &mut termios) == 0 {
+                termios.c_oflag &= !libc::OPOST;
+                libc::tcsetattr(pty.slave.as_raw_fd(), libc::TCSANOW, &termios);
+            }
+        }
+
+        Ok(pty)
+    }
+
+    /// The slave side, to be passed to the child as one of its stdio streams
+    pub fn slave(&self) -> io::Result<Stdio> {
+        Ok(Stdio::from(self.slave.try_clone()?))
+    }
+
+    /// Close the slave side and return a reader for the child's output
+    pub fn into_reader(self) -> tokio::fs::File {
+        tokio::fs::File::from_std(File::from(self.master))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncReadExt;
+
+    #[tokio::test]
+    async fn test_child_sees_a_terminal() {
+        let pty = Pty::open().unwrap();
+        let mut cmd = tokio::process::Command::new("sh");
+        cmd.arg("-c")
+            .arg("[ -t 1 ] && echo tty || echo pipe")
+            .stdout(pty.slave().unwrap());
+        let mut child = cmd.spawn().unwrap();
+        // The command keeps its own copy of the slave open until it is dropped
+        drop(cmd);
+
+        let mut output = Vec::new();
+        let _ = pty.into_reader().read_to_end(&mut output).await;
+        child.wait().await.unwrap();
+        assert_eq!(String::from_utf8_lossy(&output), "tty\n");
+    }
+}
@@ -0,0 +1,298 @@
+//! Machine-readable run reports for CI (`--report junit:path.xml`, `--report json:path.json`)
+//!
+//! Reports are built from the task rows the state database records for the
+//! run, so they agree with `otto History`. Every task becomes one test case;
+//! failed tasks carry the tail of their stderr log.
+
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use eyre::{Context, Result};
+use serde::Serialize;
+
+use super::state::{TaskRecord, TaskStatus};
+
+/// Lines of stderr included for a failed task
+const STDERR_TAIL_LINES: usize = 50;
+
+/// Format of a report file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Junit,
+    Json,
+}
+
+/// A report requested with `--report <format>:<path>`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReportSpec {
+    pub format: ReportFormat,
+    pub path: PathBuf,
+}
+
+impl ReportSpec {
+    /// Parse `junit:path.xml` or `json:path.json`
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let (format, path) = value
+            .split_once(':')
+            .ok_or_else(|| format!("expected <format>:<path>, got '{value}'"))?;
+        let format = match format {
+            "junit" => ReportFormat::Junit,
+            "json" => ReportFormat::Json,
+            other => return Err(format!("unknown report format '{other}', expected junit or json")),
+        };
+        if path.is_empty() {
+            return Err(format!("missing path in '{value}'"));
+        }
+        Ok(Self {
+            format,
+            path: PathBuf::from(path),
+        })
+    }
+
+    pub fn write(&self, report: &RunReport) -> Result<()> {
+        let content = match self.format {
+            ReportFormat::Junit => report.to_junit(),
+            ReportFormat::Json => serde_json::to_string_pretty(report)?,
+        };
+        if let Some(parent) = self.path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, content).with_context(|| format!("Failed to write report {}", self.path.display()))
+    }
+}
+
+/// How a task counts in a report
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    Passed,
+    Failed,
+    Skipped,
+}
+
+/// The final attempt of one task
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskReport {
+    pub name: String,
+    pub status: String,
+    pub outcome: Outcome,
+    pub exit_code: Option<i32>,
+    pub duration_seconds: f64,
+    pub attempts: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub skip_reason: Option<String>,
+    /// Last lines of stderr, for failed tasks
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stderr_tail: Option<String>,
+}
+
+impl TaskReport {
+    fn from_record(record: &TaskRecord) -> Self {
+        let outcome = match record.status {
+            TaskStatus::Completed | TaskStatus::Cached | TaskStatus::FailedAllowed => Outcome::Passed,
+            TaskStatus::Failed | TaskStatus::TimedOut => Outcome::Failed,
+            TaskStatus::Skipped
+            | TaskStatus::DependencyFailed
+            | TaskStatus::Cancelled
+            | TaskStatus::Pending
+            | TaskStatus::Running => Outcome::Skipped,
+        };
+        let stderr_tail = match outcome {
+            Outcome::Failed => record.stderr_path.as_deref().and_then(read_tail),
+            _ => None,
+        };
+        Self {
+            name: record.name.clone(),
+            status: record.status.as_str().to_string(),
+            outcome,
+            exit_code: record.exit_code,
+            duration_seconds: record.duration_seconds.unwrap_or_default(),
+            attempts: record.attempt,
+            skip_reason: record.skip_reason.clone(),
+            stderr_tail,
+        }
+    }
+
+    /// Why the task failed or was skipped, for JUnit's `message` attribute
+    fn message(&self) -> String {
+        match (self.outcome, self.exit_code) {
+            (Outcome::Failed, _) if self.status == TaskStatus::TimedOut.as_str() => "timed out".to_string(),
+            (Outcome::Failed, Some(code)) => format!("exit code {code}"),
+            _ => self
+                .skip_reason
+                .clone()
+                .unwrap_or_else(|| self.status.replace('_', " ")),
+        }
+    }
+}
+
+/// Everything a report contains
+#[derive(Debug, Clone, Serialize)]
+pub struct RunReport {
+    pub run_id: i64,
+    pub tests: usize,
+    pub failures: usize,
+    pub skipped: usize,
+    pub duration_seconds: f64,
+    pub tasks: Vec<TaskReport>,
+}
+
+impl RunReport {
+    /// Build a report from a run's task rows; retried tasks are reported by their last attempt
+    pub fn from_records(run_id: i64, records: &[TaskRecord]) -> Self {
+        let mut tasks: Vec<TaskReport> = Vec::new();
+        for record in records {
+            let report = TaskReport::from_record(record);
+            match tasks.iter_mut().find(|task| task.name == record.name) {
+                Some(task) => *task = report,
+                None => tasks.push(report),
+            }
+        }
+        let count = |outcome| tasks.iter().filter(|task| task.outcome == outcome).count();
+        Self {
+            run_id,
+            tests: tasks.len(),
+            failures: count(Outcome::Failed),
+            skipped: count(Outcome::Skipped),
+            duration_seconds: tasks.iter().map(|task| task.duration_seconds).sum(),
+            tasks,
+        }
+    }
+
+    pub fn to_junit(&self) -> String {
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let counts = format!(
+            "tests=\"{}\" failures=\"{}\" errors=\"0\" skipped=\"{}\" time=\"{:.3}\"",
+            self.tests, self.failures, self.skipped, self.duration_seconds
+        );
+        let _ = writeln!(xml, "<testsuites name=\"otto\" {counts}>");
+        let _ = writeln!(xml, "  <testsuite name=\"otto run {}\" {counts}>", self.run_id);
+        for task in &self.tasks {
+            let _ = write!(
+                xml,
+                "    <testcase name=\"{}\" classname=\"otto\" time=\"{:.3}\"",
+                escape(&task.name),
+                task.duration_seconds
+            );
+            match task.outcome {
+                Outcome::Passed => xml.push_str("/>\n"),
+                Outcome::Failed => {
+                    let _ = writeln!(
+                        xml,
+                        ">\n      <failure message=\"{}\" type=\"{}\">{}</failure>\n    </testcase>",
+                        escape(&task.message()),
+                        task.status,
+                        escape(task.stderr_tail.as_deref().unwrap_or_default())
+                    );
+                }
+                Outcome::Skipped => {
+                    let _ = writeln!(
+                        xml,
+                        ">\n      <skipped message=\"{}\"/>\n    </testcase>",
+                        escape(&task.message())
+                    );
+                }
+            }
+        }
+        xml.push_str("  </testsuite>\n</testsuites>\n");
+        xml
+    }
+}
+
+/// The last lines of a log file, if it has any
+fn read_tail(path: &Path) -> Option<String> {
+    let content = std::fs::read_to_string(path).ok()?;
+    let lines: Vec<&str> = content.lines().collect();
+    let tail = lines[lines.len().saturating_sub(STDERR_TAIL_LINES)..].join("\n");
+    (!tail.is_empty()).then_some(tail)
+}
+
+/// Escape text for XML attributes and content, dropping characters XML 1.0 can't represent
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if (c as u32) < 0x20 => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(name: &str, status: TaskStatus, exit_code: Option<i32>, attempt: u32) -> TaskRecord {
+        TaskRecord {
+            id: 0,
+            run_id: 1,
+            name: name.to_string(),
+            status,
+            script_hash: None,
+            exit_code,
+            started_at: None,
+            ended_at: None,
+            duration_seconds: Some(1.5),
+            stdout_path: None,
+            stderr_path: None,
+            script_path: None,
+            attempt,
+            skip_reason: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_report_spec() {
+        assert_eq!(
+            ReportSpec::parse("junit:out/report.xml"),
+            Ok(ReportSpec {
+                format: ReportFormat::Junit,
+                path: PathBuf::from("out/report.xml")
+            })
+        );
+        assert_eq!(
+            ReportSpec::parse("json:r.json").map(|spec| spec.format),
+            Ok(ReportFormat::Json)
+        );
+        assert!(ReportSpec::parse("html:r.html").is_err());
+        assert!(ReportSpec::parse("junit").is_err());
+        assert!(ReportSpec::parse("junit:").is_err());
+    }
+
+    #[test]
+    fn test_report_uses_last_attempt() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let stderr = temp_dir.path().join("stderr.log");
+        std::fs::write(&stderr, "compiling\nerror: <bad> & worse\n").unwrap();
+        let mut failed = record("test", TaskStatus::Failed, Some(1), 2);
+        failed.stderr_path = Some(stderr);
+
+        let report = RunReport::from_records(
+            1,
+            &[
+                record("build", TaskStatus::Completed, Some(0), 1),
+                record("test", TaskStatus::Failed, Some(1), 1),
+                failed,
+                record("deploy", TaskStatus::DependencyFailed, None, 1),
+            ],
+        );
+        assert_eq!((report.tests, report.failures, report.skipped), (3, 1, 1));
+        assert_eq!(report.tasks[1].attempts, 2);
+
+        let xml = report.to_junit();
+        assert!(xml.contains("<testcase name=\"build\" classname=\"otto\" time=\"1.500\"/>"));
+        assert!(xml.contains(
+            "<failure message=\"exit code 1\" type=\"failed\">compiling\nerror: &lt;bad&gt; &amp; worse</failure>"
+        ));
+        assert!(xml.contains("<skipped message=\"dependency failed\"/>"));
+    }
+}
@@ -1,38 +1,329 @@
 use std::{
     collections::HashMap,
     io::{self, Write},
-    path::Path,
+    path::{Path, PathBuf},
+    process::Stdio,
     sync::Arc,
     time::Duration,
 };
 
 use serde_json;
 
+use colored::Colorize;
 use eyre::{Result, eyre};
 use log::{debug, error, info};
 use tokio::{
     io::BufReader,
     process::Command,
-    sync::{Mutex, Semaphore, mpsc},
-    task::JoinHandle,
+    sync::{Mutex, Notify, RwLock, Semaphore, broadcast, mpsc, watch},
+    task::{JoinHandle, JoinSet},
     time::timeout,
 };
 
+use crate::cfg::duration::format_duration;
+use crate::cfg::otto::{OutputMode, Verbosity};
+use crate::cfg::param::Value;
+use crate::cfg::task::Fingerprint;
 use crate::ports::FileSystem;
 
+#[cfg(unix)]
+use super::pty::Pty;
 use super::task::Task;
 use super::{
     action::{ActionProcessor, ProcessedAction},
+    cancel::{CancelHandle, CancelLevel},
     colors::{colorize_task_prefix, set_global_task_order},
-    output::{OutputType, TaskMessage, TaskStreams, TuiTaskStatus},
+    fingerprint::compute_fingerprint,
+    handoff::TerminalHandoff,
+    output::{
+        GroupedOutput, OutputType, TaskMessage, TaskOutput, TaskStreams, TuiTaskStatus, flush_grouped, replay_task_logs,
+    },
+    output_cache::OutputCache,
+    progress::{ProgressReporter, watch_file as watch_progress_file},
+    report::{ReportSpec, RunReport},
+    service::wait_until_ready,
+    snapshot::RunSnapshot,
+    workflow::{Annotation, WorkflowCommand},
     workspace::{ExecutionContext, Workspace},
 };
 
+/// A task's stdout or stderr as read by otto: a pipe, or a pseudo-terminal for `pty` tasks
+type OutputReader = Box<dyn tokio::io::AsyncRead + Send + Unpin>;
+
 /// Timeout for output processing after task completion
 const OUTPUT_PROCESSING_TIMEOUT_SECS: u64 = 5;
 
-/// Convert JSON object to shell-sourceable .env format
-/// Handles proper escaping for bash safety
+/// Default time between SIGTERM and SIGKILL when a task exceeds its timeout
+const DEFAULT_TIMEOUT_GRACE_SECS: u64 = 10;
+
+/// Exit code recorded for tasks terminated by their timeout (matches coreutils `timeout`)
+const TIMED_OUT_EXIT_CODE: i32 = 124;
+
+/// Error returned by a task that exceeded its configured timeout
+#[derive(Debug)]
+struct TaskTimedOut(String);
+
+impl std::fmt::Display for TaskTimedOut {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for TaskTimedOut {}
+
+/// Error returned by a task that was stopped because the run was cancelled
+#[derive(Debug)]
+struct TaskCancelled(String);
+
+impl std::fmt::Display for TaskCancelled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for TaskCancelled {}
+
+/// Send a signal to every process in the child's process group
+#[cfg(unix)]
+fn signal_process_group(pid: u32, signal: libc::c_int) {
+    // SAFETY: kill(2) has no memory-safety preconditions; a negative pid targets the process group
+    let rc = unsafe { libc::kill(-(pid as libc::pid_t), signal) };
+    if rc != 0 {
+        debug!(
+            "Failed to send signal {} to process group {}: {}",
+            signal,
+            pid,
+            io::Error::last_os_error()
+        );
+    }
+}
+
+/// Terminate a timed-out task: SIGTERM the process group, wait for the grace period, then SIGKILL
+async fn terminate_child(child: &mut tokio::process::Child, grace: Duration) -> Result<std::process::ExitStatus> {
+    #[cfg(unix)]
+    {
+        if let Some(pid) = child.id() {
+            signal_process_group(pid, libc::SIGTERM);
+            if let Ok(status) = timeout(grace, child.wait()).await {
+                return Ok(status?);
+            }
+            signal_process_group(pid, libc::SIGKILL);
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = grace;
+        child.start_kill()?;
+    }
+    Ok(child.wait().await?)
+}
+
+/// How a task's process ended
+enum ChildExit {
+    /// Exited on its own
+    Exited(std::process::ExitStatus),
+    /// Terminated after exceeding its timeout
+    TimedOut(std::process::ExitStatus),
+    /// Exited unsuccessfully after the run was cancelled
+    Cancelled(std::process::ExitStatus),
+    /// A service terminated because its dependents are done
+    Stopped(std::process::ExitStatus),
+}
+
+/// Forward a cancellation request to a task's process group
+fn signal_cancel(child: &mut tokio::process::Child, level: CancelLevel) {
+    #[cfg(unix)]
+    if let (Some(pid), Some(signal)) = (child.id(), level.signal()) {
+        signal_process_group(pid, signal);
+    }
+    #[cfg(not(unix))]
+    if level != CancelLevel::None {
+        let _ = child.start_kill();
+    }
+}
+
+/// Wait for a task's process, terminating it if it exceeds its timeout and
+/// forwarding cancellation requests to its process group. Cleanup tasks started
+/// after a cancellation pass that level as `baseline` and only react to escalations.
+async fn wait_for_child(
+    child: &mut tokio::process::Child,
+    task_name: &str,
+    task_timeout: Option<Duration>,
+    grace: Duration,
+    cancel: &mut watch::Receiver<CancelLevel>,
+    baseline: CancelLevel,
+) -> Result<ChildExit> {
+    let deadline = task_timeout.map(|limit| tokio::time::Instant::now() + limit);
+
+    // A cancellation requested while the process was starting still applies to it
+    let level = *cancel.borrow_and_update();
+    let mut cancelled = level > baseline;
+    if cancelled {
+        signal_cancel(child, level);
+    }
+
+    loop {
+        tokio::select! {
+            status = child.wait() => {
+                let status = status?;
+                return Ok(if cancelled && !status.success() {
+                    ChildExit::Cancelled(status)
+                } else {
+                    ChildExit::Exited(status)
+                });
+            }
+            _ = async {
+                match deadline {
+                    Some(deadline) => tokio::time::sleep_until(deadline).await,
+                    None => std::future::pending().await,
+                }
+            } => {
+                log::warn!(
+                    "Task {task_name} exceeded its timeout of {}, terminating",
+                    format_duration(task_timeout.unwrap_or_default())
+                );
+                return Ok(ChildExit::TimedOut(terminate_child(child, grace).await?));
+            }
+            Ok(()) = cancel.changed() => {
+                let level = *cancel.borrow_and_update();
+                if level > baseline {
+                    cancelled = true;
+                    info!("Forwarding cancellation ({level:?}) to task {task_name}");
+                    signal_cancel(child, level);
+                }
+            }
+        }
+    }
+}
+
+/// Apply a task's environment to its command: otto's own environment (or only
+/// the `env_passthrough` variables with `env_clear`), the task's envs, then
+/// `path_prepend` in front of PATH
+fn configure_env(cmd: &mut Command, task: &Task) {
+    if task.env_clear {
+        cmd.env_clear();
+        for name in &task.env_passthrough {
+            if let Some(value) = std::env::var_os(name) {
+                cmd.env(name, value);
+            }
+        }
+    }
+    cmd.envs(&task.envs);
+
+    if task.path_prepend.is_empty() {
+        return;
+    }
+    let inherited = if task.env_clear && !task.env_passthrough.iter().any(|name| name == "PATH") {
+        None
+    } else {
+        std::env::var_os("PATH")
+    };
+    let base = task.envs.get("PATH").map(std::ffi::OsString::from).or(inherited);
+    let mut entries = task.path_prepend.clone();
+    entries.extend(base.iter().flat_map(std::env::split_paths));
+    match std::env::join_paths(entries) {
+        Ok(path) => {
+            cmd.env("PATH", path);
+        }
+        Err(e) => log::warn!("Ignoring path_prepend of task {}: {e}", task.name),
+    }
+}
+
+/// Locate a program on otto's own PATH, falling back to the bare name
+fn find_in_path(program: &str) -> PathBuf {
+    std::env::var_os("PATH")
+        .iter()
+        .flat_map(std::env::split_paths)
+        .map(|dir| dir.join(program))
+        .find(|candidate| candidate.is_file())
+        .unwrap_or_else(|| PathBuf::from(program))
+}
+
+/// Wait for a service's readiness probe to pass. Returns how the process ended
+/// if it exited, timed out or was cancelled first.
+async fn wait_for_service_ready(
+    child: &mut tokio::process::Child,
+    task: &Task,
+    output: broadcast::Receiver<TaskOutput>,
+    cwd: &Path,
+    grace: Duration,
+    cancel: &mut watch::Receiver<CancelLevel>,
+    baseline: CancelLevel,
+) -> Result<Option<ChildExit>> {
+    let Some(ready) = &task.ready else {
+        return Ok(None);
+    };
+
+    tokio::select! {
+        exit = wait_for_child(child, &task.name, None, grace, cancel, baseline) => match exit? {
+            ChildExit::Exited(status) => Err(eyre!(
+                "Task {} exited with code {:?} before becoming ready",
+                task.name,
+                status.code()
+            )),
+            exit => Ok(Some(exit)),
+        },
+        ready = wait_until_ready(&task.name, ready, output, cwd, &task.envs) => {
+            if let Err(e) = ready {
+                terminate_child(child, grace).await?;
+                return Err(e);
+            }
+            Ok(None)
+        }
+    }
+}
+
+/// Keep a ready service running until `stop` fires, it exits on its own or the run is cancelled
+async fn serve_until_stopped(
+    child: &mut tokio::process::Child,
+    task_name: &str,
+    task_timeout: Option<Duration>,
+    grace: Duration,
+    cancel: &mut watch::Receiver<CancelLevel>,
+    baseline: CancelLevel,
+    stop: &Notify,
+) -> Result<ChildExit> {
+    tokio::select! {
+        exit = wait_for_child(child, task_name, task_timeout, grace, cancel, baseline) => exit,
+        () = stop.notified() => {
+            info!("Stopping service {task_name}");
+            Ok(ChildExit::Stopped(terminate_child(child, grace).await?))
+        }
+    }
+}
+
+/// Render a param value for display in the dry-run plan
+fn format_value(value: &Value) -> String {
+    match value {
+        Value::Item(item) => item.clone(),
+        Value::List(items) => format!("[{}]", items.join(", ")),
+        Value::Dict(dict) => {
+            let mut pairs: Vec<String> = dict.iter().map(|(k, v)| format!("{k}={v}")).collect();
+            pairs.sort();
+            format!("{{{}}}", pairs.join(", "))
+        }
+        Value::Empty => String::new(),
+    }
+}
+
+/// Copy the final attempt's logs to `stdout.log`/`stderr.log` so the usual
+/// log locations always reflect the outcome of a retried task
+async fn publish_attempt_logs(stdout_log: &Path, stderr_log: &Path, max_attempts: u32) {
+    if max_attempts <= 1 {
+        return;
+    }
+    for (attempt_log, name) in [(stdout_log, "stdout.log"), (stderr_log, "stderr.log")] {
+        if let Some(dir) = attempt_log.parent()
+            && let Err(e) = tokio::fs::copy(attempt_log, dir.join(name)).await
+        {
+            log::warn!("Failed to copy {} to {}: {}", attempt_log.display(), name, e);
+        }
+    }
+}
+
+/// Convert a dependency's JSON output to the `.env` file bash tasks source.
+/// Lists become bash arrays; `OTTO_JSON_<TASK>_<KEY>` keeps every value as JSON
+/// for `otto_get_input_json`, so nothing is lost on the way through bash.
 fn json_to_env(json: &serde_json::Value, task_name: &str) -> String {
     let mut lines = Vec::new();
     lines.push(format!("# Auto-generated by Otto from {task_name} output"));
@@ -44,27 +335,40 @@ fn json_to_env(json: &serde_json::Value, task_name: &str) -> String {
             // Create variable name: OTTO_INPUT_<TASK>_<KEY> (uppercase, safe chars only)
             let safe_task = task_name.to_uppercase().replace(['-', '.'], "_");
             let safe_key = key.to_uppercase().replace(['-', '.'], "_");
-            let var_name = format!("OTTO_INPUT_{safe_task}_{safe_key}");
+            let var_suffix = format!("{safe_task}_{safe_key}");
 
-            // Convert value to string and escape for bash
-            let str_value = match value {
-                serde_json::Value::String(s) => s.clone(),
-                serde_json::Value::Null => String::new(),
-                other => other.to_string(),
-            };
-
-            // Escape single quotes for bash single-quoted string
-            let escaped = str_value.replace('\'', "'\\''");
-            lines.push(format!("{var_name}='{escaped}'"));
+            match value {
+                serde_json::Value::Array(items) => {
+                    let items: Vec<String> = items.iter().map(|item| bash_quote(&env_string(item))).collect();
+                    lines.push(format!("OTTO_INPUT_{var_suffix}=({})", items.join(" ")));
+                }
+                other => lines.push(format!("OTTO_INPUT_{var_suffix}={}", bash_quote(&env_string(other)))),
+            }
+            lines.push(format!("OTTO_JSON_{var_suffix}={}", bash_quote(&value.to_string())));
         }
     }
 
     lines.join("\n") + "\n"
 }
 
+/// Plain string form of a JSON value for bash: strings unquoted, null empty, the rest as JSON
+fn env_string(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// Single-quote a value for bash
+fn bash_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
 /// Convert shell .env format back to JSON
-/// Reads key=value pairs and reconstructs JSON object
-fn env_to_json(env_content: &str) -> serde_json::Value {
+/// Reads key=value pairs and reconstructs JSON object. Values set with
+/// `otto_set_output_json` are written as `key:json=...` and keep their JSON type.
+fn env_to_json(env_content: &str) -> Result<serde_json::Value> {
     let mut map = serde_json::Map::new();
 
     for line in env_content.lines() {
@@ -87,11 +391,74 @@ fn env_to_json(env_content: &str) -> serde_json::Value {
             // Unescape single quotes
             let unescaped = value.replace("'\\''", "'");
 
-            map.insert(key.to_string(), serde_json::Value::String(unescaped));
+            if let Some(key) = key.strip_suffix(":json") {
+                let json =
+                    serde_json::from_str(&unescaped).map_err(|e| eyre!("output {key} is not valid JSON: {e}"))?;
+                map.insert(key.to_string(), json);
+            } else {
+                map.insert(key.to_string(), serde_json::Value::String(unescaped));
+            }
+        }
+    }
+
+    Ok(serde_json::Value::Object(map))
+}
+
+/// Write a finished task's `output.<task>.json`, converting the `.env` file bash
+/// tasks write and adding values from `::set-output` lines, and check it against
+/// the types declared under `outputs:`
+async fn publish_output<F: FileSystem>(
+    workspace: &Workspace<F>,
+    task: &Task,
+    set_outputs: &[(String, String)],
+) -> Result<()> {
+    let env_output_file = workspace.task_output_env_file(&task.name);
+    let json_output_file = workspace.task_output_file(&task.name);
+
+    if env_output_file.exists() {
+        // Read .env file and convert to JSON
+        if let Ok(env_content) = tokio::fs::read_to_string(&env_output_file).await {
+            let json = env_to_json(&env_content).map_err(|e| eyre!("Task {} {e}", task.name))?;
+            if let Ok(json_str) = serde_json::to_string_pretty(&json)
+                && let Err(e) = tokio::fs::write(&json_output_file, json_str).await
+            {
+                log::warn!("Failed to write JSON output for task {}: {e}", task.name);
+            }
         }
+    } else if !json_output_file.exists() {
+        // If no output was written, create empty JSON
+        if let Err(e) = tokio::fs::write(&json_output_file, "{}").await {
+            log::warn!("Failed to write empty JSON output for task {}: {e}", task.name);
+        }
+    }
+
+    if task.outputs.is_empty() && set_outputs.is_empty() {
+        return Ok(());
     }
+    let content = tokio::fs::read_to_string(&json_output_file).await.map_err(|e| {
+        eyre!(
+            "Task {} output {} could not be read: {e}",
+            task.name,
+            json_output_file.display()
+        )
+    })?;
+    let mut output: serde_json::Value = serde_json::from_str(&content).map_err(|e| {
+        eyre!(
+            "Task {} output {} is not valid JSON: {e}",
+            task.name,
+            json_output_file.display()
+        )
+    })?;
 
-    serde_json::Value::Object(map)
+    if let Some(map) = output.as_object_mut()
+        && !set_outputs.is_empty()
+    {
+        for (key, value) in set_outputs {
+            map.insert(key.clone(), serde_json::Value::String(value.clone()));
+        }
+        tokio::fs::write(&json_output_file, serde_json::to_string_pretty(&output)?).await?;
+    }
+    task.validate_outputs(&output)
 }
 
 /// Status of a task during execution
@@ -105,8 +472,28 @@ pub enum TaskStatus {
     Completed,
     /// Task was skipped due to up-to-date outputs
     Skipped,
+    /// Task outputs were restored from the local output cache
+    Cached,
     /// Task failed during execution
     Failed(String),
+    /// Task failed, but has `allow_failure: true`; dependents still run
+    FailedAllowed,
+    /// Task exceeded its timeout and was terminated
+    TimedOut,
+    /// Task was not run because a task it depends on failed
+    DependencyFailed,
+    /// Task was stopped, or never started, because the run was cancelled
+    Cancelled,
+}
+
+/// A `service: true` task that keeps running in the background
+struct RunningService {
+    /// Wakes the service to terminate its process
+    stop: Arc<Notify>,
+    /// The service's task once it is ready; awaited when the run ends
+    handle: Option<JoinHandle<Result<()>>>,
+    /// Whether `stop` has been sent
+    stopping: bool,
 }
 
 /// Task scheduler that manages concurrent execution
@@ -129,6 +516,36 @@ pub struct TaskScheduler<F: FileSystem = crate::ports::RealFs> {
     message_tx: Option<tokio::sync::broadcast::Sender<TaskMessage>>,
     /// Pre-created TaskStreams for TUI mode (task_name -> TaskStreams)
     task_streams: Option<Arc<std::collections::HashMap<String, TaskStreams>>>,
+    /// Keep running independent tasks after a failure (--keep-going)
+    keep_going: bool,
+    /// Run tasks even if their outputs are up to date (--force)
+    force: bool,
+    /// How much terminal output to produce (--quiet/--verbose)
+    verbosity: Verbosity,
+    /// Local artifact cache for `fingerprint: content` tasks
+    output_cache: Option<Arc<OutputCache>>,
+    /// Cancellation requests (Ctrl-C) for this run
+    cancel: CancelHandle,
+    /// Service tasks started in this run
+    services: Arc<Mutex<HashMap<String, RunningService>>>,
+    /// Held shared by running tasks and exclusively by an interactive task
+    terminal: Arc<RwLock<()>>,
+    /// Gets the terminal back from the TUI for interactive tasks
+    terminal_handoff: Option<TerminalHandoff>,
+    /// Run every task under a pseudo-terminal (--pty)
+    pty: bool,
+    /// Remove ANSI escape sequences from task log files
+    strip_ansi: bool,
+    /// How task output is shown on the terminal
+    output_mode: OutputMode,
+    /// Reports written when the run finishes
+    reports: Vec<ReportSpec>,
+    /// Errors and warnings tasks reported with `::error` etc., for the summary
+    annotations: Arc<std::sync::Mutex<Vec<(String, Annotation)>>>,
+    /// Tasks that passed in the run being replayed by `otto Rerun --failed`, with their output file
+    reused_outputs: HashMap<String, Option<PathBuf>>,
+    /// Tasks that completed before the run was interrupted (`otto --resume`)
+    completed_before_resume: std::collections::HashSet<String>,
 }
 
 impl<F: FileSystem + 'static> TaskScheduler<F> {
@@ -156,6 +573,21 @@ impl<F: FileSystem + 'static> TaskScheduler<F> {
             tui_mode,
             message_tx: None,
             task_streams: None,
+            keep_going: false,
+            force: false,
+            verbosity: Verbosity::default(),
+            output_cache: None,
+            cancel: CancelHandle::new(),
+            services: Arc::new(Mutex::new(HashMap::new())),
+            terminal: Arc::new(RwLock::new(())),
+            terminal_handoff: None,
+            pty: false,
+            strip_ansi: false,
+            output_mode: OutputMode::default(),
+            reports: Vec::new(),
+            annotations: Arc::new(std::sync::Mutex::new(Vec::new())),
+            reused_outputs: HashMap::new(),
+            completed_before_resume: std::collections::HashSet::new(),
         })
     }
 
@@ -163,11 +595,114 @@ impl<F: FileSystem + 'static> TaskScheduler<F> {
         self.message_tx = Some(tx);
     }
 
+    /// Suspend the TUI while an interactive task has the terminal
+    pub fn set_terminal_handoff(&mut self, handoff: TerminalHandoff) {
+        self.terminal_handoff = Some(handoff);
+    }
+
+    /// Keep running tasks that don't depend on a failed task instead of stopping at the first failure
+    pub fn set_keep_going(&mut self, keep_going: bool) {
+        self.keep_going = keep_going;
+    }
+
+    /// Bypass the up-to-date check so every task runs
+    pub fn set_force(&mut self, force: bool) {
+        self.force = force;
+    }
+
+    /// Run every task under a pseudo-terminal, not just those with `pty: true`
+    pub fn set_pty(&mut self, pty: bool) {
+        self.pty = pty;
+    }
+
+    /// Keep ANSI escape sequences out of the task log files
+    pub fn set_strip_ansi(&mut self, strip_ansi: bool) {
+        self.strip_ansi = strip_ansi;
+    }
+
+    /// Show task output prefixed line by line, grouped per task, or raw
+    pub fn set_output_mode(&mut self, output_mode: OutputMode) {
+        self.output_mode = output_mode;
+    }
+
+    /// Write JUnit/JSON reports of the run when it finishes
+    pub fn set_reports(&mut self, reports: Vec<ReportSpec>) {
+        self.reports = reports;
+    }
+
+    /// Skip tasks that passed in an earlier run, carrying their `output.<task>.json` over
+    pub fn set_reused_outputs(&mut self, reused_outputs: HashMap<String, Option<PathBuf>>) {
+        self.reused_outputs = reused_outputs;
+    }
+
+    /// Treat tasks that completed before the run was interrupted as done; their outputs
+    /// are already in the run directory
+    pub fn set_completed_before_resume(&mut self, tasks: std::collections::HashSet<String>) {
+        self.completed_before_resume = tasks;
+    }
+
+    /// Set the terminal verbosity (quiet hides successful task output)
+    pub fn set_verbosity(&mut self, verbosity: Verbosity) {
+        self.verbosity = verbosity;
+    }
+
+    /// Restore outputs of `fingerprint: content` tasks from this cache instead of re-running them
+    pub fn set_output_cache(&mut self, cache: OutputCache) {
+        self.output_cache = Some(Arc::new(cache));
+    }
+
+    /// Handle for cancelling this run; running tasks are signalled and no new tasks start
+    pub fn cancel_handle(&self) -> CancelHandle {
+        self.cancel.clone()
+    }
+
+    /// Print a scheduler decision for a task (verbose terminal mode only)
+    fn print_verbose(&self, task_name: &str, message: &str) {
+        if !self.tui_mode && self.verbosity >= Verbosity::Verbose {
+            println!("{} {}", colorize_task_prefix(task_name), message.dimmed());
+            io::stdout().flush().unwrap_or(());
+        }
+    }
+
+    /// Print the env a task will run with (verbose terminal mode only)
+    fn print_verbose_envs(&self, task: &Task) {
+        let mut envs: Vec<(&String, &String)> = task.envs.iter().collect();
+        envs.sort_by_key(|(name, _)| name.as_str());
+        for (name, value) in envs {
+            self.print_verbose(&task.name, &format!("env {name}={value}"));
+        }
+    }
+
     /// Set pre-created TaskStreams for TUI mode
     pub fn set_task_streams(&mut self, streams: std::collections::HashMap<String, TaskStreams>) {
         self.task_streams = Some(Arc::new(streams));
     }
 
+    /// Copy the output a task wrote in an earlier run into this run, so dependents get it as input
+    async fn reuse_output(&self, task_name: &str, output: Option<&Path>) -> Result<()> {
+        if let Some(output) = output {
+            tokio::fs::create_dir_all(self.workspace.task(task_name)).await?;
+            tokio::fs::copy(output, self.workspace.task_output_file(task_name))
+                .await
+                .map_err(|e| eyre!("Failed to reuse output {}: {}", output.display(), e))?;
+        }
+        Ok(())
+    }
+
+    /// Skip a task that passed in the run being rerun, unless its output can't be carried over
+    async fn reuse_decision(&self, task_name: &str, output: Option<&Path>) -> (bool, String) {
+        match self.reuse_output(task_name, output).await {
+            Ok(()) => (false, "passed in the run being rerun".to_string()),
+            Err(e) => {
+                log::warn!("Task {task_name}: {e}");
+                (
+                    true,
+                    "could not reuse output from the earlier run, running anyway".to_string(),
+                )
+            }
+        }
+    }
+
     /// Helper to broadcast a TaskMessage to TUI
     fn broadcast_message(&self, message: TaskMessage) {
         if let Some(tx) = &self.message_tx {
@@ -183,7 +718,12 @@ impl<F: FileSystem + 'static> TaskScheduler<F> {
             TaskStatus::Running => TuiTaskStatus::Running,
             TaskStatus::Completed => TuiTaskStatus::Completed,
             TaskStatus::Skipped => TuiTaskStatus::Skipped,
+            TaskStatus::Cached => TuiTaskStatus::Cached,
             TaskStatus::Failed(_) => TuiTaskStatus::Failed,
+            TaskStatus::FailedAllowed => TuiTaskStatus::FailedAllowed,
+            TaskStatus::TimedOut => TuiTaskStatus::TimedOut,
+            TaskStatus::DependencyFailed => TuiTaskStatus::DependencyFailed,
+            TaskStatus::Cancelled => TuiTaskStatus::Cancelled,
         }
     }
 
@@ -195,15 +735,57 @@ impl<F: FileSystem + 'static> TaskScheduler<F> {
         tx: mpsc::Sender<Result<String>>,
         active_tasks: &mut std::collections::HashMap<String, JoinHandle<Result<()>>>,
         completed_set: &mut std::collections::HashSet<String>,
+        settled: &mut std::collections::HashSet<String>,
         blocked_tasks: &mut Vec<Task>,
         ready_queue: &mut std::collections::VecDeque<Task>,
         completed_tasks: &mut usize,
         total_tasks: usize,
     ) -> Result<()> {
-        match self.needs_rebuild(&task).await {
-            Ok(true) => {
+        if self.completed_before_resume.contains(&task.name) {
+            info!("Task {} completed before the run was resumed", task.name);
+            if !self.tui_mode && self.verbosity > Verbosity::Quiet {
+                println!("{} completed before resume", colorize_task_prefix(&task.name));
+                io::stdout().flush().unwrap_or(());
+            }
+            self.broadcast_message(TaskMessage::StatusChange {
+                task_name: task.name.clone(),
+                status: TuiTaskStatus::Completed,
+                timestamp: std::time::SystemTime::now(),
+            });
+
+            self.task_statuses
+                .lock()
+                .await
+                .insert(task.name.clone(), TaskStatus::Completed);
+            completed_set.insert(task.name.clone());
+            settled.insert(task.name.clone());
+            *completed_tasks += 1;
+            self.unblock_tasks(&task.name, blocked_tasks, ready_queue, completed_set, settled);
+            return Ok(());
+        }
+
+        let decision = if let Some(output) = self.reused_outputs.get(&task.name) {
+            Ok(self.reuse_decision(&task.name, output.as_deref()).await)
+        } else if !task.handles_failure_of.is_empty() && !self.handled_task_failed(&task).await {
+            Ok((
+                false,
+                format!("not needed, none of {} failed", task.handles_failure_of.join(", ")),
+            ))
+        } else if self.force {
+            Ok((true, "forced to run (--force)".to_string()))
+        } else {
+            self.rebuild_decision(&task).await
+        };
+        if let Ok((_, reason)) = &decision {
+            debug!("Task {}: {}", task.name, reason);
+            self.print_verbose(&task.name, reason);
+        }
+
+        match decision {
+            Ok((true, _)) => {
                 // Task needs to run
                 info!("Starting task {} ({}/{})", task.name, *completed_tasks + 1, total_tasks);
+                self.print_verbose_envs(&task);
 
                 // Broadcast task started to TUI
                 self.broadcast_message(TaskMessage::Started {
@@ -215,7 +797,7 @@ impl<F: FileSystem + 'static> TaskScheduler<F> {
                 let task_name = task.name.clone();
                 active_tasks.insert(task_name.clone(), handle);
             }
-            Ok(false) => {
+            Ok((false, reason)) => {
                 // Task can be skipped - outputs are up to date
                 info!(
                     "Skipping task {} - outputs are up to date ({}/{})",
@@ -224,9 +806,30 @@ impl<F: FileSystem + 'static> TaskScheduler<F> {
                     total_tasks
                 );
 
+                // Record the skip and why in database (graceful degradation)
+                if let Some(run_id) = self.workspace.db_run_id()
+                    && let Some(store) = self.workspace.state_store()
+                {
+                    let script_hash = ActionProcessor::new(self.workspace.clone(), &task.name)
+                        .and_then(|processor| processor.script_hash(&task.action, &task))
+                        .map_err(|e| log::warn!("Failed to compute script hash for task {}: {}", task.name, e))
+                        .ok();
+                    let reason = reason.strip_suffix(", can be skipped").unwrap_or(&reason);
+                    if let Err(e) = store.record_task_skipped(run_id, &task.name, script_hash.as_deref(), reason) {
+                        log::warn!("Failed to record skipped task in database: {}", e);
+                    }
+                }
+
                 // Print user-visible skipped message (only in terminal mode)
-                if !self.tui_mode {
-                    let skipped_msg = format!("{} skipped (up to date)\n", colorize_task_prefix(&task.name));
+                if !self.tui_mode && self.verbosity > Verbosity::Quiet {
+                    let why = if self.reused_outputs.contains_key(&task.name) {
+                        "passed before"
+                    } else if task.handles_failure_of.is_empty() {
+                        "up to date"
+                    } else {
+                        "no failure to handle"
+                    };
+                    let skipped_msg = format!("{} skipped ({why})\n", colorize_task_prefix(&task.name));
                     print!("{skipped_msg}");
                     io::stdout().flush().unwrap_or(());
                 }
@@ -241,24 +844,15 @@ impl<F: FileSystem + 'static> TaskScheduler<F> {
                 let mut statuses = self.task_statuses.lock().await;
                 statuses.insert(task.name.clone(), TaskStatus::Skipped);
                 completed_set.insert(task.name.clone());
+                settled.insert(task.name.clone());
                 *completed_tasks += 1;
 
-                blocked_tasks.retain(|blocked_task| {
-                    let task_deps_completed = blocked_task
-                        .task_deps
-                        .iter()
-                        .all(|task_dep| completed_set.contains(task_dep));
-                    if !task_deps_completed {
-                        return true; // Keep the task in blocked list
-                    }
-
-                    // All dependencies are completed, move to ready queue
-                    ready_queue.push_back(blocked_task.clone());
-                    false // Remove from blocked list
-                });
+                self.unblock_tasks(&task.name, blocked_tasks, ready_queue, completed_set, settled);
             }
             Err(e) => {
                 error!("Error checking file dependencies for task {}: {}", task.name, e);
+                self.print_verbose(&task.name, &format!("file check failed ({e}), running anyway"));
+                self.print_verbose_envs(&task);
                 // On error, default to running the task
                 info!(
                     "Starting task {} (file check failed, defaulting to run) ({}/{})",
@@ -282,6 +876,49 @@ impl<F: FileSystem + 'static> TaskScheduler<F> {
     }
 
     pub async fn execute_all(&self) -> Result<()> {
+        if let Err(e) = self
+            .workspace
+            .save_snapshot(&RunSnapshot::from_tasks(&self.tasks))
+            .await
+        {
+            log::warn!("{e}");
+        }
+        let result = self.execute_graph().await;
+        let reported = self.write_reports();
+        // A failed run is reported by its tasks; a report that can't be written fails a passing run
+        match (result, reported) {
+            (Err(e), Err(report_error)) => {
+                log::warn!("{report_error:#}");
+                Err(e)
+            }
+            (result, reported) => result.and(reported),
+        }
+    }
+
+    /// Write the `--report` files from the task rows the state database holds for this run
+    fn write_reports(&self) -> Result<()> {
+        if self.reports.is_empty() {
+            return Ok(());
+        }
+        let (Some(run_id), Some(store)) = (self.workspace.db_run_id(), self.workspace.state_store()) else {
+            return Err(eyre!(
+                "Reports need the state database, which is unavailable for this run"
+            ));
+        };
+        let report = RunReport::from_records(run_id, &store.get_run_tasks(run_id)?);
+        for spec in &self.reports {
+            spec.write(&report)?;
+        }
+        Ok(())
+    }
+
+    async fn execute_graph(&self) -> Result<()> {
+        let result = self.run_tasks().await;
+        self.shutdown_services(result.is_err()).await;
+        result
+    }
+
+    async fn run_tasks(&self) -> Result<()> {
         let (tx, mut rx) = mpsc::channel(32);
 
         // Initialize task statuses and start times
@@ -318,16 +955,40 @@ impl<F: FileSystem + 'static> TaskScheduler<F> {
         let mut active_tasks = std::collections::HashMap::new();
         let max_concurrent = self.semaphore.available_permits();
 
-        // Track completed tasks for dependency checking
+        // Track completed tasks for dependency checking, and every task that
+        // reached a final state (whatever it was) for cleanup tasks
         let mut completed_set = std::collections::HashSet::new();
+        let mut settled = std::collections::HashSet::new();
+
+        // Failures collected in keep-going mode (or while cleanup runs after a
+        // failure), and per-task durations for the summary
+        let mut failures: Vec<eyre::Report> = Vec::new();
+        let mut durations: HashMap<String, u64> = HashMap::new();
 
         while completed_tasks < total_tasks {
+            self.stop_idle_services(&settled).await;
+
+            // After a failure or cancellation, start nothing new except cleanup tasks
+            // and wait for running tasks to wind down
+            if self.cancel.is_cancelled() || (!self.keep_going && !failures.is_empty()) {
+                completed_tasks += self.abandon_pending(&mut ready_queue, &mut blocked_tasks, &mut settled);
+                self.unblock_tasks(
+                    "stopping run",
+                    &mut blocked_tasks,
+                    &mut ready_queue,
+                    &completed_set,
+                    &settled,
+                );
+                if active_tasks.is_empty() && ready_queue.is_empty() {
+                    break;
+                }
+            }
+
             // Start as many tasks as we can
             while active_tasks.len() < max_concurrent && !ready_queue.is_empty() {
                 let task = ready_queue.pop_front().unwrap();
 
-                let deps_completed = task.task_deps.iter().all(|dep| completed_set.contains(dep));
-                if !deps_completed {
+                if !Self::dependencies_met(&task, &completed_set, &settled) {
                     // Put it back at the end of the queue
                     ready_queue.push_back(task);
 
@@ -344,6 +1005,7 @@ impl<F: FileSystem + 'static> TaskScheduler<F> {
                     tx.clone(),
                     &mut active_tasks,
                     &mut completed_set,
+                    &mut settled,
                     &mut blocked_tasks,
                     &mut ready_queue,
                     &mut completed_tasks,
@@ -371,38 +1033,77 @@ impl<F: FileSystem + 'static> TaskScheduler<F> {
                             .unwrap_or(0)
                     };
 
+                    durations.insert(completed_task.clone(), duration_ms);
+
+                    let (cached, failed_allowed) = match self.task_statuses.lock().await.get(&completed_task) {
+                        Some(TaskStatus::Cached) => (true, false),
+                        Some(TaskStatus::FailedAllowed) => (false, true),
+                        _ => (false, false),
+                    };
+
+                    // A ready service keeps running; hold on to it until its dependents are done
+                    let service = self
+                        .tasks
+                        .iter()
+                        .any(|task| task.name == completed_task && task.service);
+                    if service && let Some(running) = self.services.lock().await.get_mut(&completed_task) {
+                        running.handle = active_tasks.remove(&completed_task);
+                    }
+
                     // Print user-visible success message (only in terminal mode)
-                    if !self.tui_mode {
-                        let success_msg = format!("{} finished successfully\n", colorize_task_prefix(&completed_task));
+                    if !self.tui_mode && self.verbosity > Verbosity::Quiet {
+                        let outcome = if service {
+                            "ready"
+                        } else if failed_allowed {
+                            "failed (allowed)"
+                        } else if cached {
+                            "restored from cache"
+                        } else {
+                            "finished successfully"
+                        };
+                        let success_msg = format!("{} {outcome}\n", colorize_task_prefix(&completed_task));
                         print!("{success_msg}");
                         io::stdout().flush().unwrap_or(());
                     }
 
-                    // Broadcast task completion to TUI
-                    self.broadcast_message(TaskMessage::Finished {
-                        task_name: completed_task.clone(),
-                        status: TuiTaskStatus::Completed,
-                        timestamp: std::time::SystemTime::now(),
-                        duration_ms,
-                    });
+                    // Broadcast task completion to TUI (services finish when they are stopped)
+                    if !service {
+                        self.broadcast_message(TaskMessage::Finished {
+                            task_name: completed_task.clone(),
+                            status: if failed_allowed {
+                                TuiTaskStatus::FailedAllowed
+                            } else if cached {
+                                TuiTaskStatus::Cached
+                            } else {
+                                TuiTaskStatus::Completed
+                            },
+                            timestamp: std::time::SystemTime::now(),
+                            duration_ms,
+                        });
+                    }
 
                     let mut statuses = self.task_statuses.lock().await;
-                    statuses.insert(completed_task.clone(), TaskStatus::Completed);
+                    let status = if failed_allowed {
+                        TaskStatus::FailedAllowed
+                    } else if cached {
+                        TaskStatus::Cached
+                    } else {
+                        TaskStatus::Completed
+                    };
+                    statuses.insert(completed_task.clone(), status);
+                    drop(statuses);
                     completed_set.insert(completed_task.clone());
+                    settled.insert(completed_task.clone());
                     completed_tasks += 1;
                     active_tasks.remove(&completed_task);
 
-                    blocked_tasks.retain(|task| {
-                        let task_deps_completed =
-                            task.task_deps.iter().all(|task_dep| completed_set.contains(task_dep));
-                        if !task_deps_completed {
-                            return true; // Keep the task in blocked list
-                        }
-
-                        // All dependencies are completed, move to ready queue
-                        ready_queue.push_back(task.clone());
-                        false // Remove from blocked list
-                    });
+                    self.unblock_tasks(
+                        &completed_task,
+                        &mut blocked_tasks,
+                        &mut ready_queue,
+                        &completed_set,
+                        &settled,
+                    );
 
                     for remaining_task in &blocked_tasks {
                         if remaining_task.task_deps.contains(&completed_task)
@@ -417,7 +1118,8 @@ impl<F: FileSystem + 'static> TaskScheduler<F> {
 
                     // Extract task name from error message for user-visible failure message
                     let error_str = e.to_string();
-                    if let Some(task_name) = error_str.split_whitespace().nth(1) {
+                    let failed_task = error_str.split_whitespace().nth(1);
+                    if let Some(task_name) = failed_task {
                         // Calculate task duration
                         let duration_ms = {
                             let mut start_times = self.task_start_times.lock().await;
@@ -427,9 +1129,15 @@ impl<F: FileSystem + 'static> TaskScheduler<F> {
                                 .unwrap_or(0)
                         };
 
+                        let (outcome, tui_status) = match self.task_statuses.lock().await.get(task_name) {
+                            Some(TaskStatus::TimedOut) => ("timed out", TuiTaskStatus::TimedOut),
+                            Some(TaskStatus::Cancelled) => ("cancelled", TuiTaskStatus::Cancelled),
+                            _ => ("failed", TuiTaskStatus::Failed),
+                        };
+
                         // Print user-visible failure message (only in terminal mode)
                         if !self.tui_mode {
-                            let failure_msg = format!("{} failed\n", colorize_task_prefix(task_name));
+                            let failure_msg = format!("{} {outcome}\n", colorize_task_prefix(task_name));
                             eprint!("{failure_msg}");
                             io::stderr().flush().unwrap_or(());
                         }
@@ -437,13 +1145,43 @@ impl<F: FileSystem + 'static> TaskScheduler<F> {
                         // Broadcast task failure to TUI
                         self.broadcast_message(TaskMessage::Finished {
                             task_name: task_name.to_string(),
-                            status: TuiTaskStatus::Failed,
+                            status: tui_status,
                             timestamp: std::time::SystemTime::now(),
                             duration_ms,
                         });
+                        durations.insert(task_name.to_string(), duration_ms);
                     }
 
-                    return Err(e);
+                    // After a cancellation every remaining task is drained the way keep-going does,
+                    // and so is a failure that leaves cleanup tasks to run
+                    let cancelled = e.downcast_ref::<TaskCancelled>().is_some();
+                    let draining = self.keep_going
+                        || cancelled
+                        || self.cancel.is_cancelled()
+                        || !failures.is_empty()
+                        || ready_queue.iter().chain(blocked_tasks.iter()).any(Self::is_cleanup);
+                    let Some(task_name) = failed_task.filter(|_| draining) else {
+                        return Err(e);
+                    };
+
+                    // Drop the failed task and everything downstream of it, letting
+                    // unrelated branches of the graph (or cleanup tasks) continue
+                    active_tasks.remove(task_name);
+                    completed_tasks += 1;
+                    settled.insert(task_name.to_string());
+                    if !cancelled {
+                        let dependents = self.fail_dependents(task_name, &mut blocked_tasks).await;
+                        completed_tasks += dependents.len();
+                        settled.extend(dependents);
+                        failures.push(e);
+                    }
+                    self.unblock_tasks(
+                        task_name,
+                        &mut blocked_tasks,
+                        &mut ready_queue,
+                        &completed_set,
+                        &settled,
+                    );
                 }
                 None => {
                     error!("Task completion channel closed unexpectedly");
@@ -452,59 +1190,546 @@ impl<F: FileSystem + 'static> TaskScheduler<F> {
             }
         }
 
-        Ok(())
-    }
+        if !self.tui_mode {
+            self.print_annotations();
+        }
 
-    async fn execute_task(&self, task: Task, tx: mpsc::Sender<Result<String>>) -> Result<JoinHandle<Result<()>>> {
-        let semaphore = self.semaphore.clone();
+        if self.cancel.is_cancelled() {
+            return Err(self.finish_cancelled_run(&durations).await);
+        }
 
-        let task_name = task.name.clone();
-        let task_dir = self.workspace.task(&task_name);
-        let task_statuses = self.task_statuses.clone();
-        let task_deps = task.task_deps.clone();
-        let workspace = self.workspace.clone();
-        let envs = task.envs.clone();
-        let tasks_dir = self.workspace.run().join("tasks");
-        let execution_context = self.execution_context.clone();
-        let suppress_terminal = self.tui_mode;
-        let task_streams = self.task_streams.clone();
+        if self.keep_going {
+            let statuses = self.task_statuses.lock().await;
+            if !self.tui_mode {
+                self.print_summary(&statuses, &durations);
+            }
 
-        Ok(tokio::spawn(async move {
-            // Acquire semaphore permit
-            let _permit = semaphore.acquire().await?;
+            if !failures.is_empty() {
+                let blocked = statuses
+                    .values()
+                    .filter(|status| matches!(status, TaskStatus::DependencyFailed))
+                    .count();
+                let details = failures.iter().map(|e| e.to_string()).collect::<Vec<_>>().join("\n\n");
+                return Err(eyre!(
+                    "{} of {} tasks failed, {} not run due to failed dependencies\n\n{}",
+                    failures.len(),
+                    total_tasks,
+                    blocked,
+                    details
+                ));
+            }
+        } else if !failures.is_empty() {
+            // Cleanup has run; report the failure that stopped the run
+            return Err(failures.remove(0));
+        }
 
-            {
-                let statuses = task_statuses.lock().await;
-                for dep in &task_deps {
-                    match statuses.get(dep) {
-                        Some(TaskStatus::Completed) | Some(TaskStatus::Skipped) => {
-                            // Dependency is satisfied
-                        }
-                        _ => {
-                            return Err(eyre!("Dependency {} not completed for task {}", dep, task_name));
-                        }
-                    }
-                }
+        Ok(())
+    }
+
+    /// Stop every ready service whose dependents in this run have all finished
+    async fn stop_idle_services(&self, settled: &std::collections::HashSet<String>) {
+        let mut services = self.services.lock().await;
+        for (name, service) in services.iter_mut() {
+            if service.stopping || service.handle.is_none() {
+                continue;
             }
+            let mut dependents = self
+                .tasks
+                .iter()
+                .filter(|task| task.task_deps.contains(name))
+                .peekable();
+            if dependents.peek().is_some() && dependents.all(|task| settled.contains(&task.name)) {
+                debug!("Service {name} is no longer needed, stopping it");
+                service.stop.notify_one();
+                service.stopping = true;
+            }
+        }
+    }
 
-            // Update task status to Running ONLY after dependency check
-            {
-                let mut statuses = task_statuses.lock().await;
-                statuses.insert(task_name.clone(), TaskStatus::Running);
+    /// Stop services once the run is over and wait for them to exit. Services
+    /// nothing depends on keep serving until they exit or the run is cancelled,
+    /// unless the run failed.
+    async fn shutdown_services(&self, stop_all: bool) {
+        let services: Vec<(String, RunningService)> = self.services.lock().await.drain().collect();
+        for (name, service) in services {
+            let has_dependents = self.tasks.iter().any(|task| task.task_deps.contains(&name));
+            if stop_all || has_dependents {
+                service.stop.notify_one();
+            } else if service.handle.is_some() && !self.tui_mode && self.verbosity > Verbosity::Quiet {
+                println!(
+                    "{} serving until it exits (Ctrl-C to stop)",
+                    colorize_task_prefix(&name)
+                );
+                io::stdout().flush().unwrap_or(());
             }
 
-            info!("Starting task {task_name}");
+            let Some(handle) = service.handle else {
+                continue;
+            };
+            match handle.await {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => log::warn!("Service {name} failed while shutting down: {e}"),
+                Err(e) => log::warn!("Service {name} panicked: {e}"),
+            }
+            self.broadcast_message(TaskMessage::StatusChange {
+                task_name: name,
+                status: TuiTaskStatus::Completed,
+                timestamp: std::time::SystemTime::now(),
+            });
+        }
+    }
 
-            tokio::fs::create_dir_all(&task_dir).await?;
+    /// Cleanup tasks (`finally` tasks and `on_failure` handlers) still run after
+    /// their dependencies failed or the run stopped
+    fn is_cleanup(task: &Task) -> bool {
+        task.finally || !task.handles_failure_of.is_empty()
+    }
 
-            // Setup dependency input files (symlink outputs from dependencies)
-            for dep_name in &task_deps {
-                let dep_output_file = workspace.task_output_file(dep_name);
-                let current_input_file = workspace.task_input_file(&task_name, dep_name);
-                let current_input_env_file = workspace.task_input_env_file(&task_name, dep_name);
+    /// Whether a task can start: regular tasks need every dependency to have
+    /// succeeded, cleanup tasks only need them to have finished
+    fn dependencies_met(
+        task: &Task,
+        completed_set: &std::collections::HashSet<String>,
+        settled: &std::collections::HashSet<String>,
+    ) -> bool {
+        let finished = if Self::is_cleanup(task) { settled } else { completed_set };
+        task.task_deps.iter().all(|dep| finished.contains(dep))
+    }
 
-                // Only create symlink if dependency output exists
-                if dep_output_file.exists() {
+    /// Move blocked tasks whose dependencies are now met onto the ready queue
+    fn unblock_tasks(
+        &self,
+        cause: &str,
+        blocked_tasks: &mut Vec<Task>,
+        ready_queue: &mut std::collections::VecDeque<Task>,
+        completed_set: &std::collections::HashSet<String>,
+        settled: &std::collections::HashSet<String>,
+    ) {
+        let mut unblocked = Vec::new();
+        blocked_tasks.retain(|task| {
+            if !Self::dependencies_met(task, completed_set, settled) {
+                return true; // Keep the task in blocked list
+            }
+
+            // All dependencies are done, move to ready queue
+            ready_queue.push_back(task.clone());
+            unblocked.push(task.name.clone());
+            false // Remove from blocked list
+        });
+        for name in unblocked {
+            self.print_verbose(&name, &format!("ready (unblocked by {cause})"));
+        }
+    }
+
+    /// Drop every queued task that won't run now that the run is stopping; only cleanup
+    /// tasks stay queued, unless the cancellation was escalated to a kill. Dropped tasks
+    /// stay pending. Returns how many tasks were dropped.
+    fn abandon_pending(
+        &self,
+        ready_queue: &mut std::collections::VecDeque<Task>,
+        blocked_tasks: &mut Vec<Task>,
+        settled: &mut std::collections::HashSet<String>,
+    ) -> usize {
+        let killing = self.cancel.level() == CancelLevel::Kill;
+        let mut abandoned = Vec::new();
+        let mut keep = |task: &Task| {
+            if Self::is_cleanup(task) && !killing {
+                return true;
+            }
+            abandoned.push(task.name.clone());
+            false
+        };
+        ready_queue.retain(|task| keep(task));
+        blocked_tasks.retain(|task| keep(task));
+
+        for task_name in &abandoned {
+            debug!("Task {task_name} not run: the run is stopping");
+            settled.insert(task_name.clone());
+        }
+        abandoned.len()
+    }
+
+    /// Whether any task handled by an `on_failure` handler has failed
+    async fn handled_task_failed(&self, handler: &Task) -> bool {
+        let statuses = self.task_statuses.lock().await;
+        handler
+            .handles_failure_of
+            .iter()
+            .any(|name| matches!(statuses.get(name), Some(TaskStatus::Failed(_) | TaskStatus::TimedOut)))
+    }
+
+    /// Mark every task that never got to run as cancelled, summarize, and build the run's error
+    async fn finish_cancelled_run(&self, durations: &HashMap<String, u64>) -> eyre::Report {
+        let mut statuses = self.task_statuses.lock().await;
+        for task in &self.tasks {
+            if let Some(status) = statuses.get_mut(&task.name)
+                && matches!(status, TaskStatus::Pending | TaskStatus::Running)
+            {
+                *status = TaskStatus::Cancelled;
+                self.broadcast_message(TaskMessage::StatusChange {
+                    task_name: task.name.clone(),
+                    status: TuiTaskStatus::Cancelled,
+                    timestamp: std::time::SystemTime::now(),
+                });
+            }
+        }
+
+        if !self.tui_mode {
+            self.print_summary(&statuses, durations);
+        }
+
+        let unfinished = statuses
+            .values()
+            .filter(|status| matches!(status, TaskStatus::Cancelled))
+            .count();
+        eyre!(
+            "Run cancelled: {} of {} tasks did not finish",
+            unfinished,
+            self.tasks.len()
+        )
+    }
+
+    /// Group tasks into stages that could run in parallel: every task's
+    /// dependencies are in an earlier stage. Dependencies outside the
+    /// scheduled set are ignored.
+    pub fn execution_stages(&self) -> Vec<Vec<&Task>> {
+        let scheduled: std::collections::HashSet<&str> = self.tasks.iter().map(|t| t.name.as_str()).collect();
+        let mut placed: std::collections::HashSet<&str> = std::collections::HashSet::new();
+        let mut remaining: Vec<&Task> = self.tasks.iter().collect();
+        let mut stages = Vec::new();
+
+        while !remaining.is_empty() {
+            let (ready, blocked): (Vec<&Task>, Vec<&Task>) = remaining.into_iter().partition(|task| {
+                task.task_deps
+                    .iter()
+                    .all(|dep| placed.contains(dep.as_str()) || !scheduled.contains(dep.as_str()))
+            });
+
+            if ready.is_empty() {
+                // Cycles are rejected when the DAG is built; don't loop forever if one slips through
+                stages.push(blocked);
+                break;
+            }
+
+            placed.extend(ready.iter().map(|t| t.name.as_str()));
+            stages.push(ready);
+            remaining = blocked;
+        }
+
+        for stage in &mut stages {
+            stage.sort_by(|a, b| a.name.cmp(&b.name));
+        }
+        stages
+    }
+
+    /// Print what a run would do without spawning anything (--dry-run)
+    pub async fn print_plan(&self) -> Result<()> {
+        let stages = self.execution_stages();
+
+        println!("Dry run mode - no tasks will be executed\n");
+        println!("Tasks to execute (in order):");
+
+        let mut would_run = 0;
+        let mut would_skip = 0;
+        for (i, stage) in stages.iter().enumerate() {
+            let names: Vec<&str> = stage.iter().map(|t| t.name.as_str()).collect();
+            if stage.len() > 1 {
+                println!("  {}. [parallel] {}", i + 1, names.join(", "));
+            } else {
+                println!("  {}. {}", i + 1, names.join(", "));
+            }
+
+            for task in stage {
+                let decision = if self.force {
+                    "run (forced)".to_string()
+                } else {
+                    match self.needs_rebuild(task).await {
+                        Ok(true) => "run".to_string(),
+                        Ok(false) => "skip (up to date)".to_string(),
+                        Err(e) => format!("run (file check failed: {e})"),
+                    }
+                };
+                if decision.starts_with("skip") {
+                    would_skip += 1;
+                } else {
+                    would_run += 1;
+                }
+
+                println!("     {} {}", colorize_task_prefix(&task.name), decision);
+                if !task.task_deps.is_empty() {
+                    println!("         depends on: {}", task.task_deps.join(", "));
+                }
+                if task.finally {
+                    println!("         finally: runs even if its dependencies fail");
+                }
+                if !task.handles_failure_of.is_empty() {
+                    println!("         on failure of: {}", task.handles_failure_of.join(", "));
+                }
+                if task.service {
+                    println!("         service: keeps running until its dependents finish");
+                }
+                if let Some(dir) = &task.dir {
+                    println!("         dir: {}", dir.display());
+                }
+                if task.interactive {
+                    println!("         interactive: gets the terminal and runs alone");
+                } else if task.pty || self.pty {
+                    println!("         pty: output goes through a pseudo-terminal");
+                }
+
+                let mut params: Vec<(&String, &Value)> = task.values.iter().collect();
+                params.sort_by_key(|(name, _)| name.as_str());
+                for (name, value) in params {
+                    println!("         param {name} = {}", format_value(value));
+                }
+
+                let mut envs: Vec<(&String, &String)> = task.envs.iter().collect();
+                envs.sort_by_key(|(name, _)| name.as_str());
+                for (name, value) in envs {
+                    println!("         env {name}={value}");
+                }
+            }
+        }
+
+        println!(
+            "\nWould execute {} tasks ({} skipped as up to date) across {} stages",
+            would_run,
+            would_skip,
+            stages.len()
+        );
+
+        Ok(())
+    }
+
+    /// Mark every blocked task that transitively depends on `failed_task` as
+    /// `DependencyFailed`, returning the tasks taken off the blocked list.
+    /// Cleanup tasks are left blocked, since they run regardless.
+    async fn fail_dependents(&self, failed_task: &str, blocked_tasks: &mut Vec<Task>) -> Vec<String> {
+        let mut failed = std::collections::HashSet::from([failed_task.to_string()]);
+        let mut dependents = Vec::new();
+
+        loop {
+            let (newly_failed, still_blocked): (Vec<Task>, Vec<Task>) = std::mem::take(blocked_tasks)
+                .into_iter()
+                .partition(|task| !Self::is_cleanup(task) && task.task_deps.iter().any(|dep| failed.contains(dep)));
+            *blocked_tasks = still_blocked;
+
+            if newly_failed.is_empty() {
+                break;
+            }
+            for task in newly_failed {
+                failed.insert(task.name.clone());
+                dependents.push(task.name);
+            }
+        }
+
+        let mut statuses = self.task_statuses.lock().await;
+        let mut start_times = self.task_start_times.lock().await;
+        for task_name in &dependents {
+            info!("Task {task_name} not run: depends on failed task {failed_task}");
+            statuses.insert(task_name.clone(), TaskStatus::DependencyFailed);
+            start_times.remove(task_name);
+
+            if !self.tui_mode {
+                let blocked_msg = format!("{} not run (dependency failed)\n", colorize_task_prefix(task_name));
+                eprint!("{blocked_msg}");
+                io::stderr().flush().unwrap_or(());
+            }
+
+            self.broadcast_message(TaskMessage::StatusChange {
+                task_name: task_name.clone(),
+                status: TuiTaskStatus::DependencyFailed,
+                timestamp: std::time::SystemTime::now(),
+            });
+
+            // Record in database (graceful degradation)
+            if let Some(run_id) = self.workspace.db_run_id()
+                && let Some(store) = self.workspace.state_store()
+                && let Err(e) = store.record_task_dependency_failed(run_id, task_name)
+            {
+                log::warn!("Failed to record dependency failure in database: {}", e);
+            }
+        }
+
+        dependents
+    }
+
+    /// Print a table of every task's final status, failures first
+    fn print_summary(&self, statuses: &HashMap<String, TaskStatus>, durations: &HashMap<String, u64>) {
+        use comfy_table::{Cell, CellAlignment, Color, Table, modifiers::UTF8_ROUND_CORNERS, presets::UTF8_FULL};
+
+        let rank = |status: &TaskStatus| match status {
+            TaskStatus::Failed(_) | TaskStatus::TimedOut => 0,
+            TaskStatus::DependencyFailed | TaskStatus::Cancelled | TaskStatus::FailedAllowed => 1,
+            TaskStatus::Completed | TaskStatus::Cached => 2,
+            TaskStatus::Skipped => 3,
+            TaskStatus::Pending | TaskStatus::Running => 4,
+        };
+
+        let mut rows: Vec<(&Task, &TaskStatus)> = self
+            .tasks
+            .iter()
+            .filter_map(|task| statuses.get(&task.name).map(|status| (task, status)))
+            .collect();
+        rows.sort_by_key(|(_, status)| rank(status));
+
+        let mut table = Table::new();
+        table
+            .load_preset(UTF8_FULL)
+            .apply_modifier(UTF8_ROUND_CORNERS)
+            .set_header(vec![
+                Cell::new("Task").set_alignment(CellAlignment::Left),
+                Cell::new("Status").set_alignment(CellAlignment::Left),
+                Cell::new("Duration").set_alignment(CellAlignment::Right),
+            ]);
+
+        for (task, status) in rows {
+            let (label, color) = match status {
+                TaskStatus::Failed(_) => ("failed", Color::Red),
+                TaskStatus::FailedAllowed => ("failed (allowed)", Color::Yellow),
+                TaskStatus::TimedOut => ("timed out", Color::Magenta),
+                TaskStatus::DependencyFailed => ("dependency failed", Color::DarkGrey),
+                TaskStatus::Cancelled => ("cancelled", Color::DarkYellow),
+                TaskStatus::Completed => ("succeeded", Color::Green),
+                TaskStatus::Cached => ("restored from cache", Color::Cyan),
+                TaskStatus::Skipped => ("skipped", Color::Yellow),
+                TaskStatus::Pending | TaskStatus::Running => ("not run", Color::Reset),
+            };
+            let duration = durations
+                .get(&task.name)
+                .map(|ms| format_duration(Duration::from_millis(*ms)))
+                .unwrap_or_else(|| "-".to_string());
+
+            table.add_row(vec![
+                Cell::new(&task.name).set_alignment(CellAlignment::Left),
+                Cell::new(label).fg(color).set_alignment(CellAlignment::Left),
+                Cell::new(duration).set_alignment(CellAlignment::Right),
+            ]);
+        }
+
+        println!("\n{table}");
+    }
+
+    /// Repeat the errors and warnings tasks reported, so they aren't lost in the output
+    fn print_annotations(&self) {
+        let annotations = self.annotations.lock().unwrap();
+        if annotations.is_empty() {
+            return;
+        }
+        println!("\nAnnotations:");
+        for (task_name, annotation) in annotations.iter() {
+            println!("  {} {annotation}", colorize_task_prefix(task_name));
+        }
+    }
+
+    async fn execute_task(&self, task: Task, tx: mpsc::Sender<Result<String>>) -> Result<JoinHandle<Result<()>>> {
+        let semaphore = self.semaphore.clone();
+        let terminal = self.terminal.clone();
+        let terminal_handoff = self.terminal_handoff.clone();
+        let annotations = self.annotations.clone();
+
+        let task_name = task.name.clone();
+        let task_dir = self.workspace.task(&task_name);
+        let task_statuses = self.task_statuses.clone();
+        let task_deps = task.task_deps.clone();
+        let workspace = self.workspace.clone();
+        let tasks_dir = self.workspace.run().join("tasks");
+        let execution_context = self.execution_context.clone();
+        let suppress_terminal = self.tui_mode;
+        // Quiet mode only writes output to the log files, replaying them if the task fails
+        let quiet = self.verbosity == Verbosity::Quiet;
+        let live_output_hidden = suppress_terminal || quiet;
+        let use_pty = (task.pty || self.pty) && !task.interactive;
+        let strip_ansi = self.strip_ansi;
+        let output_mode = self.output_mode;
+        let progress = ProgressReporter::new(
+            task_name.clone(),
+            self.message_tx.clone(),
+            !live_output_hidden && !task.interactive,
+        );
+        let task_streams = self.task_streams.clone();
+        let output_cache = self.output_cache.clone();
+        let cancel = self.cancel.clone();
+        let cleanup = Self::is_cleanup(&task);
+        let service_stop = task.service.then(|| Arc::new(Notify::new()));
+        if let Some(stop) = &service_stop {
+            self.services.lock().await.insert(
+                task_name.clone(),
+                RunningService {
+                    stop: stop.clone(),
+                    handle: None,
+                    stopping: false,
+                },
+            );
+        }
+        let task_timeout = task.timeout;
+        let timeout_grace = task
+            .timeout_grace
+            .unwrap_or(Duration::from_secs(DEFAULT_TIMEOUT_GRACE_SECS));
+
+        Ok(tokio::spawn(async move {
+            // Acquire semaphore permit (a service gives it back once it is ready)
+            let mut permit = Some(semaphore.acquire().await?);
+            // An interactive task waits for running tasks to finish and holds off new ones
+            let _exclusive = if task.interactive { Some(terminal.clone().write_owned().await) } else { None };
+            let mut shared = if task.interactive { None } else { Some(terminal.clone().read_owned().await) };
+
+            // Don't start anything once the run has been cancelled, except cleanup
+            // tasks, which still run unless the cancellation was escalated to a kill
+            let cancel_baseline = if cleanup { cancel.level() } else { CancelLevel::None };
+            if cancel.is_cancelled() && (!cleanup || cancel_baseline == CancelLevel::Kill) {
+                task_statuses
+                    .lock()
+                    .await
+                    .insert(task_name.clone(), TaskStatus::Cancelled);
+                let e = eyre::Report::new(TaskCancelled(format!(
+                    "Task {task_name} was cancelled before it started"
+                )));
+                if let Err(send_err) = tx.send(Err(e)).await {
+                    error!("Failed to send cancellation notification for task {task_name}: {send_err}");
+                }
+                return Ok(());
+            }
+            let mut cancel_rx = cancel.subscribe();
+
+            // Cleanup tasks run whatever state their dependencies ended in
+            if !cleanup {
+                let statuses = task_statuses.lock().await;
+                for dep in &task_deps {
+                    match statuses.get(dep) {
+                        Some(
+                            TaskStatus::Completed
+                            | TaskStatus::Skipped
+                            | TaskStatus::Cached
+                            | TaskStatus::FailedAllowed,
+                        ) => {
+                            // Dependency is satisfied
+                        }
+                        _ => {
+                            return Err(eyre!("Dependency {} not completed for task {}", dep, task_name));
+                        }
+                    }
+                }
+            }
+
+            // Update task status to Running ONLY after dependency check
+            {
+                let mut statuses = task_statuses.lock().await;
+                statuses.insert(task_name.clone(), TaskStatus::Running);
+            }
+
+            info!("Starting task {task_name}");
+
+            tokio::fs::create_dir_all(&task_dir).await?;
+
+            // Setup dependency input files (symlink outputs from dependencies)
+            for dep_name in &task_deps {
+                let dep_output_file = workspace.task_output_file(dep_name);
+                let current_input_file = workspace.task_input_file(&task_name, dep_name);
+                let current_input_env_file = workspace.task_input_env_file(&task_name, dep_name);
+
+                // Only create symlink if dependency output exists
+                if dep_output_file.exists() {
                     if current_input_file.exists() {
                         tokio::fs::remove_file(&current_input_file).await.ok();
                     }
@@ -534,266 +1759,601 @@ impl<F: FileSystem + 'static> TaskScheduler<F> {
                 }
             }
 
-            // Process the user's action script with Otto enhancements
+            // Fingerprint the inputs before running so changes made while the task runs
+            // are picked up next time
+            let fingerprint = if task.fingerprint == Fingerprint::Content {
+                match compute_fingerprint(&task).await {
+                    Ok(fingerprint) => Some(fingerprint),
+                    Err(e) => {
+                        log::warn!("Failed to compute content fingerprint for task {task_name}: {e}");
+                        None
+                    }
+                }
+            } else {
+                None
+            };
+
             let action_processor = ActionProcessor::new(workspace.clone(), &task_name)?;
-            let processed_action = action_processor.process(&task.action, &task)?;
 
-            // Extract script path and determine interpreter
-            let (script_path, interpreter) = match processed_action {
-                ProcessedAction::Bash { path, .. } => (path, "bash"),
-                ProcessedAction::Python3 { path, .. } => (path, "python3"),
-            };
+            // Restore declared outputs from the local cache instead of running when possible
+            let cache_key = fingerprint
+                .as_ref()
+                .filter(|_| output_cache.is_some() && !task.output_deps.is_empty())
+                .map(|fingerprint| OutputCache::key(&task_name, &task.output_deps, fingerprint));
+            if let (Some(cache), Some(key)) = (&output_cache, &cache_key) {
+                match cache.restore(key, &workspace.task_output_file(&task_name)) {
+                    Ok(true) => {
+                        info!("Task {task_name} restored from output cache");
+
+                        // Record the cache hit in database (graceful degradation)
+                        if let Some(run_id) = workspace.db_run_id()
+                            && let Some(store) = workspace.state_store()
+                        {
+                            let script_hash = action_processor
+                                .script_hash(&task.action, &task)
+                                .map_err(|e| log::warn!("Failed to compute script hash for task {task_name}: {e}"))
+                                .ok();
+                            if let Err(e) = store.record_task_cached(run_id, &task_name, script_hash.as_deref()) {
+                                log::warn!("Failed to record cached task in database: {}", e);
+                            }
+                            if let Some(fingerprint) = &fingerprint
+                                && let Err(e) = store.record_task_fingerprint(run_id, &task_name, fingerprint)
+                            {
+                                log::warn!("Failed to record content fingerprint in database: {}", e);
+                            }
+                        }
 
-            // Record task start in database with paths (graceful degradation)
-            let db_task_id = if let Some(run_id) = workspace.db_run_id() {
-                if let Some(store) = workspace.state_store() {
-                    let stdout_path = tasks_dir.join(&task_name).join("stdout.log");
-                    let stderr_path = tasks_dir.join(&task_name).join("stderr.log");
-
-                    match store.record_task_start(
-                        run_id,
-                        &task_name,
-                        None, // TODO: Compute script hash in future phase
-                        Some(&stdout_path),
-                        Some(&stderr_path),
-                        Some(&script_path),
-                    ) {
-                        Ok(task_id) => Some(task_id),
-                        Err(e) => {
-                            log::warn!("Failed to record task start in database: {}", e);
-                            None
+                        task_statuses.lock().await.insert(task_name.clone(), TaskStatus::Cached);
+                        if let Err(e) = tx.send(Ok(task_name.clone())).await {
+                            error!("Failed to send completion notification for task {task_name}: {e}");
                         }
+                        return Ok(());
                     }
-                } else {
-                    None
+                    Ok(false) => debug!("No output cache entry for task {task_name}"),
+                    Err(e) => log::warn!("Failed to restore task {task_name} from output cache: {e}"),
                 }
-            } else {
-                None
+            }
+
+            // Process the user's action script with Otto enhancements
+            let processed_action = action_processor.process(&task.action, &task)?;
+
+            // Extract script path and hash and determine interpreter
+            let (script_path, script_hash, command, script_envs) = match processed_action {
+                ProcessedAction::Bash { path, hash, .. } => (path, hash, vec!["bash".to_string()], Vec::new()),
+                ProcessedAction::Python3 { path, hash, .. } => (path, hash, vec!["python3".to_string()], Vec::new()),
+                ProcessedAction::Generic {
+                    path,
+                    hash,
+                    command,
+                    envs,
+                    ..
+                } => (path, hash, command, envs),
             };
+            let (interpreter, interpreter_args) = command
+                .split_first()
+                .ok_or_else(|| eyre!("Task {task_name} has an empty interpreter command"))?;
+
+            let retry = task.retry.clone();
+            let max_attempts = retry.as_ref().map_or(1, |r| r.attempts.max(1));
 
             // Setup command environment
-            let mut cmd = Command::new(interpreter);
-            cmd.arg(&script_path)
-                .current_dir(workspace.root())
-                // Inherit current environment by default (no env_clear())
-                .envs(&envs) // Override with user-specified env vars
-                .env("OTTO_TASK", &task_name)
+            let working_dir = task.dir.clone().unwrap_or_else(|| workspace.root().clone());
+            // A cleared environment may not have a PATH to find the interpreter with
+            let mut cmd = if task.env_clear {
+                Command::new(find_in_path(interpreter))
+            } else {
+                Command::new(interpreter)
+            };
+            cmd.args(interpreter_args).arg(&script_path).current_dir(&working_dir);
+            configure_env(&mut cmd, &task);
+            cmd.envs(script_envs);
+            cmd.env("OTTO_TASK", &task_name)
                 .env("OTTO_TASK_DIR", task_dir.to_string_lossy().to_string())
                 .env("OTTO_WORKSPACE", workspace.root().to_string_lossy().to_string())
                 .env("OTTO_TASKS_DIR", tasks_dir.to_string_lossy().to_string())
                 .env("OTTO_USER", &execution_context.user);
+            let progress_file = tasks_dir.join(&task_name).join("progress");
+            cmd.env("OTTO_PROGRESS", &progress_file);
+
+            // Run the task in its own process group so a timeout can terminate
+            // the whole tree, not just the interpreter. Interactive tasks stay in
+            // the foreground group, which is what lets them read from the terminal.
+            #[cfg(unix)]
+            if !task.interactive {
+                cmd.process_group(0);
+            }
 
-            // Execute without timeout - runs until completion or failure
-            let result = async {
-                let mut child = cmd
-                    .stdout(std::process::Stdio::piped())
-                    .stderr(std::process::Stdio::piped())
-                    .spawn()?;
-
-                // Setup output streams
-                let stdout = child.stdout.take().ok_or_else(|| eyre!("Failed to capture stdout"))?;
-                let stderr = child.stderr.take().ok_or_else(|| eyre!("Failed to capture stderr"))?;
-
-                let streams = if let Some(streams_map) = &task_streams {
-                    streams_map
-                        .get(&task_name)
-                        .ok_or_else(|| eyre!("TaskStreams not found for task {}", task_name))?
-                        .clone()
+            let mut attempt = 1;
+            loop {
+                // Each attempt keeps its own logs when retries are enabled
+                let log_dir = tasks_dir.join(&task_name);
+                tokio::fs::remove_file(&progress_file).await.ok();
+                let (stdout_log, stderr_log) = if max_attempts > 1 {
+                    (
+                        log_dir.join(format!("stdout.attempt-{attempt}.log")),
+                        log_dir.join(format!("stderr.attempt-{attempt}.log")),
+                    )
                 } else {
-                    TaskStreams::new(&task_name, &tasks_dir).await?
+                    (log_dir.join("stdout.log"), log_dir.join("stderr.log"))
                 };
 
-                // Start output handling
-                let stdout_handle = {
-                    let streams = streams.clone();
-                    let task_name = task_name.clone();
-                    tokio::spawn(async move {
-                        let reader = BufReader::new(stdout);
-                        streams
-                            .process_output(task_name, OutputType::Stdout, reader, suppress_terminal)
-                            .await
-                    })
-                };
-
-                let stderr_handle = {
-                    let streams = streams.clone();
-                    let task_name = task_name.clone();
-                    tokio::spawn(async move {
-                        let reader = BufReader::new(stderr);
-                        streams
-                            .process_output(task_name, OutputType::Stderr, reader, suppress_terminal)
-                            .await
-                    })
+                // Record task start in database with paths (graceful degradation)
+                let db_task_id = if let Some(run_id) = workspace.db_run_id() {
+                    if let Some(store) = workspace.state_store() {
+                        match store.record_task_start(
+                            run_id,
+                            &task_name,
+                            Some(&script_hash),
+                            Some(&stdout_log),
+                            Some(&stderr_log),
+                            Some(&script_path),
+                        ) {
+                            Ok(task_id) => {
+                                // Each retry is its own row; tag it with its attempt number
+                                if attempt > 1
+                                    && let Err(e) = store.record_task_attempt(task_id, attempt)
+                                {
+                                    log::warn!("Failed to record task attempt in database: {}", e);
+                                }
+                                Some(task_id)
+                            }
+                            Err(e) => {
+                                log::warn!("Failed to record task start in database: {}", e);
+                                None
+                            }
+                        }
+                    } else {
+                        None
+                    }
+                } else {
+                    None
                 };
 
-                // Wait for process to complete
-                let status = child.wait().await?;
+                let mut exit_code = None;
+                let mut service_ready = false;
+                let mut workflow_commands = Vec::new();
+                let result = async {
+                    // pty tasks see terminals on stdout and stderr; otto reads the master sides
+                    #[allow(unused_mut)]
+                    let mut pty_readers: Option<(OutputReader, OutputReader)> = None;
+                    #[cfg(unix)]
+                    if use_pty {
+                        let open =
+                            || Pty::open().map_err(|e| eyre!("Task {task_name} failed to open a pseudo-terminal: {e}"));
+                        let (stdout_pty, stderr_pty) = (open()?, open()?);
+                        cmd.stdout(stdout_pty.slave()?).stderr(stderr_pty.slave()?);
+                        pty_readers = Some((Box::new(stdout_pty.into_reader()), Box::new(stderr_pty.into_reader())));
+                    }
 
-                // Wait for output handling to complete with timeout (only for output processing)
-                let output_timeout = Duration::from_secs(OUTPUT_PROCESSING_TIMEOUT_SECS);
+                    // The TUI steps aside until the interactive task is done with the terminal
+                    let _terminal_lease = match &terminal_handoff {
+                        Some(handoff) if task.interactive => Some(handoff.acquire().await),
+                        _ => None,
+                    };
 
-                match timeout(output_timeout, stdout_handle).await {
-                    Ok(Ok(Ok(()))) => {
-                        // Stdout processing completed successfully
-                    }
-                    Ok(Ok(Err(e))) => {
-                        error!("Stdout processing failed for task {task_name}: {e}");
-                    }
-                    Ok(Err(e)) => {
-                        error!("Stdout processing join failed for task {task_name}: {e}");
-                    }
-                    Err(_) => {
-                        error!("Stdout processing timed out for task {task_name}");
+                    let child = if task.interactive {
+                        cmd.stdin(Stdio::inherit())
+                            .stdout(Stdio::inherit())
+                            .stderr(Stdio::inherit())
+                    } else if pty_readers.is_some() {
+                        &mut cmd
+                    } else {
+                        cmd.stdout(Stdio::piped()).stderr(Stdio::piped())
+                    };
+                    let spawned = child.spawn();
+                    if pty_readers.is_some() {
+                        // The command holds on to the slave sides; close them so reads end when the task exits
+                        cmd.stdout(Stdio::null()).stderr(Stdio::null());
                     }
-                }
+                    let mut child = spawned
+                        .map_err(|e| eyre!("Task {task_name} failed to start in {}: {e}", working_dir.display()))?;
+                    // A JoinSet so the watcher also stops if this attempt bails out early
+                    let mut progress_watcher = JoinSet::new();
+                    progress_watcher.spawn(watch_progress_file(progress_file.clone(), progress.clone()));
+
+                    let grouped = GroupedOutput::default();
+                    // Interactive tasks write straight to the terminal, so there is nothing to capture
+                    let (stdout_handle, stderr_handle, service_output) = if task.interactive {
+                        let done = || tokio::spawn(async { Ok::<_, eyre::Report>(Vec::new()) });
+                        (done(), done(), None)
+                    } else {
+                        // Setup output streams
+                        let (stdout, stderr): (OutputReader, OutputReader) = match pty_readers {
+                            Some(readers) => readers,
+                            None => (
+                                Box::new(child.stdout.take().ok_or_else(|| eyre!("Failed to capture stdout"))?),
+                                Box::new(child.stderr.take().ok_or_else(|| eyre!("Failed to capture stderr"))?),
+                            ),
+                        };
 
-                match timeout(output_timeout, stderr_handle).await {
-                    Ok(Ok(Ok(()))) => {
-                        // Stderr processing completed successfully
-                    }
-                    Ok(Ok(Err(e))) => {
-                        error!("Stderr processing failed for task {task_name}: {e}");
-                    }
-                    Ok(Err(e)) => {
-                        error!("Stderr processing join failed for task {task_name}: {e}");
-                    }
-                    Err(_) => {
-                        error!("Stderr processing timed out for task {task_name}");
-                    }
-                }
+                        let mut streams = if let Some(streams_map) = &task_streams {
+                            streams_map
+                                .get(&task_name)
+                                .ok_or_else(|| eyre!("TaskStreams not found for task {}", task_name))?
+                                .clone()
+                        } else {
+                            TaskStreams::new(&task_name, &tasks_dir).await?
+                        };
+                        streams.stdout_file = stdout_log.clone();
+                        streams.stderr_file = stderr_log.clone();
+                        streams.strip_ansi = strip_ansi;
+                        streams.progress = Some(progress.clone());
+                        streams.output_mode = output_mode;
+                        streams.grouped = grouped.clone();
+                        // Subscribe before output handling starts so the log probe sees every line
+                        let service_output = service_stop.as_ref().map(|_| streams.output_tx.subscribe());
+
+                        // Start output handling
+                        let stdout_handle = {
+                            let streams = streams.clone();
+                            let task_name = task_name.clone();
+                            tokio::spawn(async move {
+                                let reader = BufReader::new(stdout);
+                                streams
+                                    .process_output(task_name, OutputType::Stdout, reader, live_output_hidden)
+                                    .await
+                            })
+                        };
 
-                if status.success() {
-                    Ok(())
-                } else {
-                    // Get fully qualified log file paths
-                    let stdout_log = tasks_dir.join(&task_name).join("stdout.log");
-                    let stderr_log = tasks_dir.join(&task_name).join("stderr.log");
-
-                    // Read stderr content to include in error message
-                    let stderr_content = tokio::fs::read_to_string(&stderr_log).await.unwrap_or_default();
-                    let stderr_preview = if !stderr_content.trim().is_empty() {
-                        let lines: Vec<&str> = stderr_content.lines().collect();
-                        let preview_lines = if lines.len() > 20 { &lines[lines.len() - 20..] } else { &lines[..] };
-                        format!(
-                            "\n\nError output (last {} lines):\n{}",
-                            preview_lines.len(),
-                            preview_lines.join("\n")
-                        )
-                    } else {
-                        String::new()
+                        let stderr_handle = {
+                            let streams = streams.clone();
+                            let task_name = task_name.clone();
+                            tokio::spawn(async move {
+                                let reader = BufReader::new(stderr);
+                                streams
+                                    .process_output(task_name, OutputType::Stderr, reader, live_output_hidden)
+                                    .await
+                            })
+                        };
+
+                        (stdout_handle, stderr_handle, service_output)
                     };
 
-                    Err(eyre!(
-                        "Task {} failed with exit code {:?}{}\n\nLogs:\n  stdout: {}\n  stderr: {}",
-                        task_name,
-                        status.code(),
-                        stderr_preview,
-                        stdout_log.canonicalize().unwrap_or(stdout_log).display(),
-                        stderr_log.canonicalize().unwrap_or(stderr_log).display()
-                    ))
-                }
-            }
-            .await;
-
-            match result {
-                Ok(()) => {
-                    info!("Task {task_name} completed successfully");
-
-                    // Convert .env output to JSON for downstream tasks
-                    // This allows bash to write simple key=value format while maintaining JSON compatibility
-                    let env_output_file = workspace.task_output_env_file(&task_name);
-                    let json_output_file = workspace.task_output_file(&task_name);
-
-                    if env_output_file.exists() {
-                        // Read .env file and convert to JSON
-                        if let Ok(env_content) = tokio::fs::read_to_string(&env_output_file).await
-                            && let Ok(json_str) = serde_json::to_string_pretty(&env_to_json(&env_content))
-                            && let Err(e) = tokio::fs::write(&json_output_file, json_str).await
-                        {
-                            log::warn!("Failed to write JSON output for task {task_name}: {e}");
+                    // Wait for process to complete, terminating it on timeout or cancellation.
+                    // A service is done for its dependents once ready and then serves until stopped.
+                    let exit = match (&service_stop, service_output) {
+                        (Some(stop), Some(output)) => {
+                            match wait_for_service_ready(
+                                &mut child,
+                                &task,
+                                output,
+                                &working_dir,
+                                timeout_grace,
+                                &mut cancel_rx,
+                                cancel_baseline,
+                            )
+                            .await?
+                            {
+                                Some(exit) => exit,
+                                None => {
+                                    info!("Service {task_name} is ready");
+                                    service_ready = true;
+                                    permit.take();
+                                    shared.take();
+                                    if let Err(e) = tx.send(Ok(task_name.clone())).await {
+                                        error!("Failed to send readiness notification for task {task_name}: {e}");
+                                    }
+                                    serve_until_stopped(
+                                        &mut child,
+                                        &task_name,
+                                        task_timeout,
+                                        timeout_grace,
+                                        &mut cancel_rx,
+                                        cancel_baseline,
+                                        stop,
+                                    )
+                                    .await?
+                                }
+                            }
+                        }
+                        _ => {
+                            wait_for_child(
+                                &mut child,
+                                &task_name,
+                                task_timeout,
+                                timeout_grace,
+                                &mut cancel_rx,
+                                cancel_baseline,
+                            )
+                            .await?
                         }
-                    } else if !json_output_file.exists() {
-                        // If no output was written, create empty JSON
-                        if let Err(e) = tokio::fs::write(&json_output_file, "{}").await {
-                            log::warn!("Failed to write empty JSON output for task {task_name}: {e}");
+                    };
+                    let (status, timed_out, cancelled, stopped) = match exit {
+                        ChildExit::Exited(status) => (status, false, false, false),
+                        ChildExit::TimedOut(status) => (status, true, false, false),
+                        ChildExit::Cancelled(status) => (status, false, true, false),
+                        ChildExit::Stopped(status) => (status, false, false, true),
+                    };
+                    exit_code = if timed_out { Some(TIMED_OUT_EXIT_CODE) } else { status.code() };
+
+                    // Wait for output handling to complete with timeout (only for output processing)
+                    let output_timeout = Duration::from_secs(OUTPUT_PROCESSING_TIMEOUT_SECS);
+
+                    match timeout(output_timeout, stdout_handle).await {
+                        Ok(Ok(Ok(commands))) => workflow_commands.extend(commands),
+                        Ok(Ok(Err(e))) => {
+                            error!("Stdout processing failed for task {task_name}: {e}");
+                        }
+                        Ok(Err(e)) => {
+                            error!("Stdout processing join failed for task {task_name}: {e}");
+                        }
+                        Err(_) => {
+                            error!("Stdout processing timed out for task {task_name}");
                         }
                     }
 
-                    // Record task completion in database (graceful degradation)
-                    if let Some(task_id) = db_task_id
-                        && let Some(store) = workspace.state_store()
-                        && let Err(e) = store.record_task_complete(task_id, 0, super::state::TaskStatus::Completed)
-                    {
-                        log::warn!("Failed to record task completion in database: {}", e);
+                    match timeout(output_timeout, stderr_handle).await {
+                        Ok(Ok(Ok(commands))) => workflow_commands.extend(commands),
+                        Ok(Ok(Err(e))) => {
+                            error!("Stderr processing failed for task {task_name}: {e}");
+                        }
+                        Ok(Err(e)) => {
+                            error!("Stderr processing join failed for task {task_name}: {e}");
+                        }
+                        Err(_) => {
+                            error!("Stderr processing timed out for task {task_name}");
+                        }
                     }
+                    progress_watcher.abort_all();
+                    progress.finish();
+                    if let Err(e) = flush_grouped(&grouped) {
+                        error!("Failed to print output of task {task_name}: {e}");
+                    }
+
+                    if cancelled {
+                        Err(eyre::Report::new(TaskCancelled(format!(
+                            "Task {} was cancelled\n\nLogs:\n  stdout: {}\n  stderr: {}",
+                            task_name,
+                            stdout_log.canonicalize().unwrap_or(stdout_log.clone()).display(),
+                            stderr_log.canonicalize().unwrap_or(stderr_log.clone()).display()
+                        ))))
+                    } else if timed_out {
+                        Err(eyre::Report::new(TaskTimedOut(format!(
+                            "Task {} timed out after {}\n\nLogs:\n  stdout: {}\n  stderr: {}",
+                            task_name,
+                            format_duration(task_timeout.unwrap_or_default()),
+                            stdout_log.canonicalize().unwrap_or(stdout_log.clone()).display(),
+                            stderr_log.canonicalize().unwrap_or(stderr_log.clone()).display()
+                        ))))
+                    } else if stopped || status.code().is_some_and(|code| task.is_success_code(code)) {
+                        Ok(())
+                    } else {
+                        // Read stderr content to include in error message
+                        let stderr_content = tokio::fs::read_to_string(&stderr_log).await.unwrap_or_default();
+                        let stderr_preview = if !stderr_content.trim().is_empty() {
+                            let lines: Vec<&str> = stderr_content.lines().collect();
+                            let preview_lines = if lines.len() > 20 { &lines[lines.len() - 20..] } else { &lines[..] };
+                            format!(
+                                "\n\nError output (last {} lines):\n{}",
+                                preview_lines.len(),
+                                preview_lines.join("\n")
+                            )
+                        } else {
+                            String::new()
+                        };
 
-                    // Ensure we send the completion message
-                    if let Err(e) = tx.send(Ok(task_name.clone())).await {
-                        error!("Failed to send completion notification for task {task_name}: {e}");
+                        Err(eyre!(
+                            "Task {} failed with exit code {:?}{}\n\nLogs:\n  stdout: {}\n  stderr: {}",
+                            task_name,
+                            status.code(),
+                            stderr_preview,
+                            stdout_log.canonicalize().unwrap_or(stdout_log.clone()).display(),
+                            stderr_log.canonicalize().unwrap_or(stderr_log.clone()).display()
+                        ))
                     }
                 }
-                Err(e) => {
-                    error!("Task {task_name} failed: {e}");
-
-                    // Record task failure in database (graceful degradation)
+                .await;
+
+                let mut set_outputs = Vec::new();
+                let mut task_annotations = Vec::new();
+                for command in workflow_commands {
+                    match command {
+                        WorkflowCommand::SetOutput { key, value } => set_outputs.push((key, value)),
+                        WorkflowCommand::Annotate(annotation) => task_annotations.push(annotation),
+                        WorkflowCommand::Group(_) | WorkflowCommand::EndGroup | WorkflowCommand::Progress(_) => {}
+                    }
+                }
+                if !task_annotations.is_empty() {
+                    // Record annotations in database (graceful degradation)
                     if let Some(task_id) = db_task_id
                         && let Some(store) = workspace.state_store()
+                        && let Err(e) = store.record_task_annotations(task_id, &task_annotations)
                     {
-                        // Extract exit code from error message if possible
-                        let exit_code = e
-                            .to_string()
-                            .split("exit code")
-                            .nth(1)
-                            .and_then(|s| s.trim().parse::<i32>().ok())
-                            .unwrap_or(1);
-
-                        // Ignore errors in graceful degradation
-                        let _ = store.record_task_complete(task_id, exit_code, super::state::TaskStatus::Failed);
-                    }
-
-                    let mut statuses = task_statuses.lock().await;
-                    statuses.insert(task_name.clone(), TaskStatus::Failed(e.to_string()));
-                    if let Err(send_err) = tx.send(Err(e)).await {
-                        error!("Failed to send error notification for task {task_name}: {send_err}");
+                        log::warn!("Failed to record task annotations in database: {}", e);
                     }
+                    annotations.lock().unwrap().extend(
+                        task_annotations
+                            .into_iter()
+                            .map(|annotation| (task_name.clone(), annotation)),
+                    );
                 }
-            }
-
-            Ok(())
-        }))
-    }
 
-    pub async fn get_task_statuses(&self) -> HashMap<String, TaskStatus> {
-        self.task_statuses.lock().await.clone()
-    }
+                // The task only succeeds once its output is published and matches `outputs:`
+                let result = match result {
+                    Ok(()) => publish_output(&workspace, &task, &set_outputs).await,
+                    Err(e) => Err(e),
+                };
 
-    pub async fn get_task_status(&self, task_name: &str) -> TaskStatus {
-        let statuses = self.task_statuses.lock().await;
-        statuses.get(task_name).cloned().unwrap_or(TaskStatus::Pending)
-    }
+                match result {
+                    Ok(()) => {
+                        info!("Task {task_name} completed successfully");
+                        publish_attempt_logs(&stdout_log, &stderr_log, max_attempts).await;
+
+                        let json_output_file = workspace.task_output_file(&task_name);
+
+                        // Record task completion in database (graceful degradation)
+                        if let Some(task_id) = db_task_id
+                            && let Some(store) = workspace.state_store()
+                            && let Err(e) = store.record_task_complete(
+                                task_id,
+                                exit_code.unwrap_or(0),
+                                super::state::TaskStatus::Completed,
+                            )
+                        {
+                            log::warn!("Failed to record task completion in database: {}", e);
+                        }
 
-    pub async fn needs_rebuild(&self, task: &Task) -> Result<bool> {
-        // If no file dependencies, always run (traditional task-only mode)
-        if task.file_deps.is_empty() {
-            debug!("Task {} has no file dependencies, will run", task.name);
-            return Ok(true);
-        }
+                        // Save declared outputs to the local cache for future runs (best effort)
+                        if let (Some(cache), Some(key)) = (&output_cache, &cache_key)
+                            && let Err(e) = cache.store(key, &task_name, &task.output_deps, &json_output_file)
+                        {
+                            log::warn!("Failed to store outputs of task {task_name} in output cache: {e}");
+                        }
 
-        let output_files = &task.output_deps;
+                        // Remember the fingerprint of this successful run (graceful degradation)
+                        if let Some(fingerprint) = &fingerprint
+                            && let Some(run_id) = workspace.db_run_id()
+                            && let Some(store) = workspace.state_store()
+                            && let Err(e) = store.record_task_fingerprint(run_id, &task_name, fingerprint)
+                        {
+                            log::warn!("Failed to record content fingerprint in database: {}", e);
+                        }
 
-        // If no output files exist, need to run
-        if output_files.is_empty() {
-            debug!("Task {} has no output files defined, will run", task.name);
-            return Ok(true);
-        }
+                        // Ensure we send the completion message (a service already reported readiness)
+                        if !service_ready && let Err(e) = tx.send(Ok(task_name.clone())).await {
+                            error!("Failed to send completion notification for task {task_name}: {e}");
+                        }
+                    }
+                    Err(e) => {
+                        let timed_out = e.downcast_ref::<TaskTimedOut>().is_some();
+                        let cancelled = e.downcast_ref::<TaskCancelled>().is_some();
+                        let retrying = retry.as_ref().is_some_and(|retry| {
+                            attempt < max_attempts && !cancel.is_cancelled() && retry.should_retry(exit_code)
+                        });
+                        // Only the final attempt's exit code is tolerated; timeouts and cancellation never are
+                        let allowed = task.allow_failure
+                            && exit_code.is_some()
+                            && !timed_out
+                            && !cancelled
+                            && !retrying
+                            && !service_ready;
+
+                        // Record this attempt's failure in database (graceful degradation)
+                        if let Some(task_id) = db_task_id
+                            && let Some(store) = workspace.state_store()
+                        {
+                            let db_status = if cancelled {
+                                super::state::TaskStatus::Cancelled
+                            } else if timed_out {
+                                super::state::TaskStatus::TimedOut
+                            } else if allowed {
+                                super::state::TaskStatus::FailedAllowed
+                            } else {
+                                super::state::TaskStatus::Failed
+                            };
+
+                            // Ignore errors in graceful degradation
+                            let _ = store.record_task_complete(task_id, exit_code.unwrap_or(1), db_status);
+                        }
+
+                        // Dependents already treated a ready service as done; just report that it went away
+                        if service_ready {
+                            log::warn!("Service {task_name} exited before it was stopped: {e}");
+                            if !live_output_hidden && !cancelled {
+                                eprintln!("{} exited before it was stopped", colorize_task_prefix(&task_name));
+                            }
+                            break;
+                        }
+
+                        if let Some(retry) = &retry
+                            && retrying
+                        {
+                            let delay = retry.delay_before(attempt + 1);
+                            log::warn!(
+                                "Task {task_name} failed on attempt {attempt}/{max_attempts}, retrying in {}",
+                                format_duration(delay)
+                            );
+                            if !live_output_hidden {
+                                eprintln!(
+                                    "{} attempt {attempt}/{max_attempts} failed, retrying in {}",
+                                    colorize_task_prefix(&task_name),
+                                    format_duration(delay)
+                                );
+                            }
+                            tokio::time::sleep(delay).await;
+                            attempt += 1;
+                            continue;
+                        }
+
+                        publish_attempt_logs(&stdout_log, &stderr_log, max_attempts).await;
+
+                        if quiet
+                            && !suppress_terminal
+                            && let Err(replay_err) = replay_task_logs(&task_name, &stdout_log, &stderr_log).await
+                        {
+                            log::warn!("Failed to replay output for task {task_name}: {replay_err}");
+                        }
+
+                        // An allowed failure completes the task as far as its dependents are concerned
+                        if allowed {
+                            log::warn!("Task {task_name} failed, continuing since failure is allowed: {e}");
+                            task_statuses
+                                .lock()
+                                .await
+                                .insert(task_name.clone(), TaskStatus::FailedAllowed);
+                            if let Err(send_err) = tx.send(Ok(task_name.clone())).await {
+                                error!("Failed to send completion notification for task {task_name}: {send_err}");
+                            }
+                            break;
+                        }
+
+                        error!("Task {task_name} failed: {e}");
+
+                        let status = if cancelled {
+                            TaskStatus::Cancelled
+                        } else if timed_out {
+                            TaskStatus::TimedOut
+                        } else {
+                            TaskStatus::Failed(e.to_string())
+                        };
+                        task_statuses.lock().await.insert(task_name.clone(), status);
+                        if let Err(send_err) = tx.send(Err(e)).await {
+                            error!("Failed to send error notification for task {task_name}: {send_err}");
+                        }
+                    }
+                }
+                break;
+            }
+            Ok(())
+        }))
+    }
+
+    pub async fn get_task_statuses(&self) -> HashMap<String, TaskStatus> {
+        self.task_statuses.lock().await.clone()
+    }
+
+    pub async fn get_task_status(&self, task_name: &str) -> TaskStatus {
+        let statuses = self.task_statuses.lock().await;
+        statuses.get(task_name).cloned().unwrap_or(TaskStatus::Pending)
+    }
+
+    pub async fn needs_rebuild(&self, task: &Task) -> Result<bool> {
+        let (needs_rebuild, reason) = self.rebuild_decision(task).await?;
+        debug!("Task {}: {}", task.name, reason);
+        Ok(needs_rebuild)
+    }
+
+    /// Decide whether a task must run, along with a human-readable reason
+    async fn rebuild_decision(&self, task: &Task) -> Result<(bool, String)> {
+        if task.fingerprint == Fingerprint::Content {
+            return self.content_rebuild_decision(task).await;
+        }
+
+        // If no file dependencies, always run (traditional task-only mode)
+        if task.file_deps.is_empty() {
+            return Ok((true, "no file dependencies, will run".to_string()));
+        }
+
+        let output_files = &task.output_deps;
+
+        // If no output files exist, need to run
+        if output_files.is_empty() {
+            return Ok((true, "no output files defined, will run".to_string()));
+        }
 
         for output_path in output_files {
             if !Path::new(output_path).exists() {
-                debug!(
-                    "Output file {} does not exist, task {} needs to run",
-                    output_path, task.name
-                );
-                return Ok(true);
+                return Ok((true, format!("output file {output_path} does not exist, needs to run")));
             }
         }
 
@@ -806,21 +2366,43 @@ impl<F: FileSystem + 'static> TaskScheduler<F> {
 
         match (newest_input, oldest_output) {
             (Some(input_time), Some(output_time)) => {
-                let needs_rebuild = input_time > output_time;
-                if needs_rebuild {
-                    debug!("Input files newer than outputs, task {} needs to run", task.name);
+                if input_time > output_time {
+                    Ok((true, "input files newer than outputs, needs to run".to_string()))
                 } else {
-                    debug!("Outputs up to date, task {} can be skipped", task.name);
+                    Ok((false, "outputs up to date, can be skipped".to_string()))
                 }
-                Ok(needs_rebuild)
             }
-            (None, _) => {
-                debug!("No input files found, task {} will run", task.name);
-                Ok(true) // No inputs found, run the task
+            // No inputs found, run the task
+            (None, _) => Ok((true, "no input files found, will run".to_string())),
+            // No outputs found, need to run
+            (_, None) => Ok((true, "no output files found, needs to run".to_string())),
+        }
+    }
+
+    /// Decide whether a `fingerprint: content` task must run by comparing its
+    /// current fingerprint with the one stored for its last successful run
+    async fn content_rebuild_decision(&self, task: &Task) -> Result<(bool, String)> {
+        for output_path in &task.output_deps {
+            if !Path::new(output_path).exists() {
+                return Ok((true, format!("output file {output_path} does not exist, needs to run")));
             }
-            (_, None) => {
-                debug!("No output files found, task {} needs to run", task.name);
-                Ok(true) // No outputs found, need to run
+        }
+
+        let (Some(run_id), Some(store)) = (self.workspace.db_run_id(), self.workspace.state_store()) else {
+            return Ok((true, "no state database for content fingerprints, will run".to_string()));
+        };
+
+        let current = compute_fingerprint(task).await?;
+        match store.get_task_fingerprint(run_id, &task.name) {
+            Ok(Some(previous)) if previous == current => Ok((
+                false,
+                "content fingerprint unchanged since last successful run, can be skipped".to_string(),
+            )),
+            Ok(Some(_)) => Ok((true, "content fingerprint changed, needs to run".to_string())),
+            Ok(None) => Ok((true, "no previous content fingerprint, will run".to_string())),
+            Err(e) => {
+                log::warn!("Failed to read content fingerprint from database: {}", e);
+                Ok((true, "previous content fingerprint unavailable, will run".to_string()))
             }
         }
     }
@@ -858,6 +2440,7 @@ impl<F: FileSystem + 'static> TaskScheduler<F> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::cfg::task::RetrySpec;
     use serial_test::serial;
     use std::path::PathBuf;
     use tempfile::TempDir;
@@ -874,6 +2457,20 @@ mod tests {
         }
     }
 
+    /// Helper to build a task that only has dependencies and an action
+    fn test_task(name: &str, deps: &[&str], action: &str) -> Task {
+        Task::new(
+            name.to_string(),
+            None,
+            deps.iter().map(|d| d.to_string()).collect(),
+            vec![],
+            vec![],
+            HashMap::new(),
+            HashMap::new(),
+            action.to_string(),
+        )
+    }
+
     #[tokio::test]
     #[serial]
     #[serial]
@@ -988,6 +2585,1117 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    #[serial]
+    async fn test_task_timeout() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let work_dir = PathBuf::from(temp_dir.path());
+        setup_test_db(&work_dir);
+
+        let mut task = Task::new(
+            "slow".to_string(),
+            None,
+            vec![],
+            vec![],
+            vec![],
+            HashMap::new(),
+            HashMap::new(),
+            "sleep 30".to_string(),
+        );
+        task.timeout = Some(Duration::from_millis(300));
+        task.timeout_grace = Some(Duration::from_secs(2));
+
+        let workspace = Workspace::new(work_dir).await?;
+        workspace.init().await?;
+        let scheduler = TaskScheduler::new(vec![task], Arc::new(workspace), ExecutionContext::new(), 1, false).await?;
+
+        let start = std::time::Instant::now();
+        let result = scheduler.execute_all().await;
+
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("timed out after 300ms"));
+        assert!(start.elapsed() < Duration::from_secs(10));
+        assert_eq!(scheduler.get_task_status("slow").await, TaskStatus::TimedOut);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_execution_stages() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let work_dir = PathBuf::from(temp_dir.path());
+        setup_test_db(&work_dir);
+
+        let tasks = vec![
+            test_task("build", &["test", "clippy"], "true"),
+            test_task("test", &["check"], "true"),
+            test_task("clippy", &["fmt-check"], "true"),
+            test_task("check", &[], "true"),
+            test_task("fmt-check", &[], "true"),
+        ];
+
+        let workspace = Workspace::new(work_dir).await?;
+        let scheduler = TaskScheduler::new(tasks, Arc::new(workspace), ExecutionContext::new(), 1, false).await?;
+
+        let stages: Vec<Vec<&str>> = scheduler
+            .execution_stages()
+            .iter()
+            .map(|stage| stage.iter().map(|t| t.name.as_str()).collect())
+            .collect();
+        assert_eq!(
+            stages,
+            vec![vec!["check", "fmt-check"], vec!["clippy", "test"], vec!["build"]]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_rebuild_decision_reasons() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let work_dir = PathBuf::from(temp_dir.path());
+        setup_test_db(&work_dir);
+
+        let input = work_dir.join("input.txt");
+        let output = work_dir.join("output.txt");
+        std::fs::write(&input, "in")?;
+
+        let task = Task::new(
+            "gen".to_string(),
+            None,
+            vec![],
+            vec![input.to_string_lossy().to_string()],
+            vec![output.to_string_lossy().to_string()],
+            HashMap::new(),
+            HashMap::new(),
+            "true".to_string(),
+        );
+
+        let workspace = Workspace::new(work_dir).await?;
+        let scheduler = TaskScheduler::new(
+            vec![task.clone()],
+            Arc::new(workspace),
+            ExecutionContext::new(),
+            1,
+            false,
+        )
+        .await?;
+
+        let (run, reason) = scheduler.rebuild_decision(&task).await?;
+        assert!(run);
+        assert!(reason.contains("does not exist"));
+
+        std::thread::sleep(Duration::from_millis(20));
+        std::fs::write(&output, "out")?;
+        let (run, reason) = scheduler.rebuild_decision(&task).await?;
+        assert!(!run);
+        assert_eq!(reason, "outputs up to date, can be skipped");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_force_bypasses_up_to_date_check() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let work_dir = PathBuf::from(temp_dir.path());
+        setup_test_db(&work_dir);
+
+        let input = work_dir.join("input.txt");
+        let output = work_dir.join("output.txt");
+        std::fs::write(&input, "in")?;
+        std::thread::sleep(Duration::from_millis(20));
+        std::fs::write(&output, "out")?;
+
+        let make_task = || {
+            Task::new(
+                "gen".to_string(),
+                None,
+                vec![],
+                vec![input.to_string_lossy().to_string()],
+                vec![output.to_string_lossy().to_string()],
+                HashMap::new(),
+                HashMap::new(),
+                "true".to_string(),
+            )
+        };
+
+        let workspace = Arc::new(Workspace::new(work_dir).await?);
+        workspace.init().await?;
+
+        let scheduler =
+            TaskScheduler::new(vec![make_task()], workspace.clone(), ExecutionContext::new(), 1, false).await?;
+        scheduler.execute_all().await?;
+        assert_eq!(scheduler.get_task_status("gen").await, TaskStatus::Skipped);
+
+        let mut scheduler = TaskScheduler::new(vec![make_task()], workspace, ExecutionContext::new(), 1, false).await?;
+        scheduler.set_force(true);
+        scheduler.execute_all().await?;
+        assert_eq!(scheduler.get_task_status("gen").await, TaskStatus::Completed);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_content_fingerprint_skips_unchanged_inputs() -> Result<()> {
+        use crate::ports::{MemoryStateStore, StateStore};
+
+        let temp_dir = TempDir::new()?;
+        let work_dir = PathBuf::from(temp_dir.path());
+        setup_test_db(&work_dir);
+
+        let input = work_dir.join("input.txt");
+        let counter = work_dir.join("runs.txt");
+        std::fs::write(&input, "v1")?;
+
+        let mut task = Task::new(
+            "gen".to_string(),
+            None,
+            vec![],
+            vec![input.to_string_lossy().to_string()],
+            vec![],
+            HashMap::new(),
+            HashMap::new(),
+            format!("echo run >> {}", counter.display()),
+        );
+        task.fingerprint = Fingerprint::Content;
+
+        let store = Arc::new(MemoryStateStore::new());
+        let workspace = Arc::new(Workspace::new(work_dir).await?.with_state_store(store.clone()));
+        workspace.init().await?;
+        workspace.save_execution_context(ExecutionContext::new()).await?;
+
+        let run = |task: Task| {
+            let workspace = workspace.clone();
+            async move {
+                let scheduler = TaskScheduler::new(vec![task], workspace, ExecutionContext::new(), 1, false).await?;
+                scheduler.execute_all().await?;
+                Ok::<_, eyre::Report>(scheduler.get_task_status("gen").await)
+            }
+        };
+
+        assert_eq!(run(task.clone()).await?, TaskStatus::Completed);
+
+        // Rewriting identical content changes the mtime but not the fingerprint
+        std::fs::write(&input, "v1")?;
+        assert_eq!(run(task.clone()).await?, TaskStatus::Skipped);
+
+        std::fs::write(&input, "v2")?;
+        assert_eq!(run(task.clone()).await?, TaskStatus::Completed);
+        assert_eq!(std::fs::read_to_string(&counter)?.lines().count(), 2);
+
+        // Skips are persisted with their reason, and every row carries the script hash
+        let history = store.get_task_history("gen", 10)?;
+        assert_eq!(history.len(), 3);
+        let skipped = history
+            .iter()
+            .find(|t| t.status == crate::executor::DbTaskStatus::Skipped)
+            .expect("skipped task should be recorded");
+        assert_eq!(
+            skipped.skip_reason.as_deref(),
+            Some("content fingerprint unchanged since last successful run")
+        );
+        assert!(history.iter().all(|t| t.script_hash == history[0].script_hash));
+        assert!(history[0].script_hash.is_some());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_output_cache_restores_deleted_outputs() -> Result<()> {
+        use crate::ports::{MemoryStateStore, StateStore};
+
+        let temp_dir = TempDir::new()?;
+        let work_dir = PathBuf::from(temp_dir.path());
+        setup_test_db(&work_dir);
+
+        let input = work_dir.join("schema.txt");
+        let output = work_dir.join("generated.txt");
+        let counter = work_dir.join("runs.txt");
+        std::fs::write(&input, "v1")?;
+
+        let mut task = Task::new(
+            "codegen".to_string(),
+            None,
+            vec![],
+            vec![input.to_string_lossy().to_string()],
+            vec![output.to_string_lossy().to_string()],
+            HashMap::new(),
+            HashMap::new(),
+            format!(
+                "echo run >> {}\ncat {} > {}",
+                counter.display(),
+                input.display(),
+                output.display()
+            ),
+        );
+        task.fingerprint = Fingerprint::Content;
+
+        let store = Arc::new(MemoryStateStore::new());
+        let workspace = Arc::new(Workspace::new(work_dir.clone()).await?.with_state_store(store.clone()));
+        workspace.init().await?;
+        workspace.save_execution_context(ExecutionContext::new()).await?;
+
+        let run = |task: Task| {
+            let workspace = workspace.clone();
+            let cache = OutputCache::new(&work_dir.join("otto-home"));
+            async move {
+                let mut scheduler =
+                    TaskScheduler::new(vec![task], workspace, ExecutionContext::new(), 1, false).await?;
+                scheduler.set_output_cache(cache);
+                scheduler.execute_all().await?;
+                Ok::<_, eyre::Report>(scheduler.get_task_status("codegen").await)
+            }
+        };
+
+        assert_eq!(run(task.clone()).await?, TaskStatus::Completed);
+
+        // Outputs deleted (e.g. after switching branches): restored without running
+        std::fs::remove_file(&output)?;
+        assert_eq!(run(task.clone()).await?, TaskStatus::Cached);
+        assert_eq!(std::fs::read_to_string(&output)?, "v1");
+        assert_eq!(std::fs::read_to_string(&counter)?.lines().count(), 1);
+
+        let stats = store.get_task_stats("codegen")?;
+        assert_eq!(stats[0].cached_executions, 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_keep_going_runs_independent_branches() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let work_dir = PathBuf::from(temp_dir.path());
+        setup_test_db(&work_dir);
+
+        let tasks = vec![
+            test_task("broken", &[], "exit 1"),
+            test_task("package", &["broken"], "echo package"),
+            test_task("deploy", &["package"], "echo deploy"),
+            test_task("lint", &[], "echo lint"),
+            test_task("docs", &["lint"], "echo docs"),
+        ];
+
+        let workspace = Workspace::new(work_dir).await?;
+        workspace.init().await?;
+        let mut scheduler = TaskScheduler::new(tasks, Arc::new(workspace), ExecutionContext::new(), 1, false).await?;
+        scheduler.set_keep_going(true);
+
+        let result = scheduler.execute_all().await;
+
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains("1 of 5 tasks failed, 2 not run due to failed dependencies"));
+        assert!(matches!(
+            scheduler.get_task_status("broken").await,
+            TaskStatus::Failed(_)
+        ));
+        assert_eq!(scheduler.get_task_status("package").await, TaskStatus::DependencyFailed);
+        assert_eq!(scheduler.get_task_status("deploy").await, TaskStatus::DependencyFailed);
+        assert_eq!(scheduler.get_task_status("lint").await, TaskStatus::Completed);
+        assert_eq!(scheduler.get_task_status("docs").await, TaskStatus::Completed);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_allowed_failure_and_success_codes_unblock_dependents() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let work_dir = PathBuf::from(temp_dir.path());
+        setup_test_db(&work_dir);
+
+        let mut lint = test_task("lint", &[], "exit 1");
+        lint.allow_failure = true;
+        let mut scan = test_task("scan", &[], "exit 3");
+        scan.success_codes = vec![0, 3];
+        let tasks = vec![lint, scan, test_task("build", &["lint", "scan"], "echo build")];
+
+        let workspace = Arc::new(Workspace::new(work_dir).await?);
+        workspace.init().await?;
+        workspace.save_execution_context(ExecutionContext::new()).await?;
+        let scheduler = TaskScheduler::new(tasks, workspace.clone(), ExecutionContext::new(), 1, false).await?;
+
+        scheduler.execute_all().await?;
+        assert_eq!(scheduler.get_task_status("lint").await, TaskStatus::FailedAllowed);
+        assert_eq!(scheduler.get_task_status("scan").await, TaskStatus::Completed);
+        assert_eq!(scheduler.get_task_status("build").await, TaskStatus::Completed);
+
+        let store = workspace.state_store().expect("state store");
+        let tasks = store.get_run_tasks(workspace.db_run_id().expect("run id"))?;
+        let recorded = |name: &str| {
+            tasks
+                .iter()
+                .find(|task| task.name == name)
+                .map(|task| (task.status.clone(), task.exit_code))
+        };
+        assert_eq!(
+            recorded("lint"),
+            Some((crate::executor::DbTaskStatus::FailedAllowed, Some(1)))
+        );
+        assert_eq!(
+            recorded("scan"),
+            Some((crate::executor::DbTaskStatus::Completed, Some(3)))
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_finally_task_runs_after_failure() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let work_dir = PathBuf::from(temp_dir.path());
+        setup_test_db(&work_dir);
+        let marker = temp_dir.path().join("torn-down");
+
+        let mut teardown = test_task("db-down", &["integration"], &format!("touch {}", marker.display()));
+        teardown.finally = true;
+        let tasks = vec![
+            test_task("db-up", &[], "echo up"),
+            test_task("integration", &["db-up"], "exit 1"),
+            test_task("publish", &["integration"], "echo publish"),
+            teardown,
+        ];
+
+        let workspace = Workspace::new(work_dir).await?;
+        workspace.init().await?;
+        let scheduler = TaskScheduler::new(tasks, Arc::new(workspace), ExecutionContext::new(), 1, false).await?;
+
+        let err = scheduler.execute_all().await.unwrap_err().to_string();
+        assert!(err.starts_with("Task integration failed"), "{err}");
+        assert!(marker.exists());
+        assert_eq!(scheduler.get_task_status("db-down").await, TaskStatus::Completed);
+        assert_eq!(scheduler.get_task_status("publish").await, TaskStatus::DependencyFailed);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_configure_env_clears_and_prepends_path() {
+        let mut task = Task::new(
+            "hermetic".to_string(),
+            None,
+            vec![],
+            vec![],
+            vec![],
+            HashMap::from([("MODE".to_string(), "ci".to_string())]),
+            HashMap::new(),
+            "env".to_string(),
+        );
+        task.env_clear = true;
+        task.path_prepend = vec![PathBuf::from("/repo/node_modules/.bin")];
+
+        let mut cmd = Command::new("bash");
+        configure_env(&mut cmd, &task);
+        let envs: HashMap<String, String> = cmd
+            .as_std()
+            .get_envs()
+            .filter_map(|(name, value)| {
+                Some((name.to_string_lossy().to_string(), value?.to_string_lossy().to_string()))
+            })
+            .collect();
+        // PATH isn't passed through, so only the prepended entries remain
+        assert_eq!(envs["PATH"], "/repo/node_modules/.bin");
+        assert_eq!(envs["MODE"], "ci");
+        assert!(!envs.contains_key("HOME"));
+
+        task.env_passthrough = vec!["PATH".to_string()];
+        let mut cmd = Command::new("bash");
+        configure_env(&mut cmd, &task);
+        let path = cmd
+            .as_std()
+            .get_envs()
+            .find(|(name, _)| *name == "PATH")
+            .and_then(|(_, value)| value)
+            .unwrap()
+            .to_string_lossy()
+            .to_string();
+        assert!(path.starts_with("/repo/node_modules/.bin:"), "{path}");
+        assert!(path.len() > "/repo/node_modules/.bin:".len());
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_task_runs_in_its_dir() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let work_dir = PathBuf::from(temp_dir.path());
+        setup_test_db(&work_dir);
+        let sub_dir = temp_dir.path().join("frontend");
+        std::fs::create_dir(&sub_dir)?;
+
+        let mut task = Task::new(
+            "build".to_string(),
+            None,
+            vec![],
+            vec![],
+            vec![],
+            HashMap::new(),
+            HashMap::new(),
+            "pwd > where.txt".to_string(),
+        );
+        task.dir = Some(sub_dir.clone());
+
+        let workspace = Workspace::new(work_dir).await?;
+        workspace.init().await?;
+        let scheduler = TaskScheduler::new(vec![task], Arc::new(workspace), ExecutionContext::new(), 1, false).await?;
+        scheduler.execute_all().await?;
+
+        let recorded = std::fs::read_to_string(sub_dir.join("where.txt"))?;
+        assert_eq!(PathBuf::from(recorded.trim()).canonicalize()?, sub_dir.canonicalize()?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_env_roundtrip_keeps_types() {
+        let env = json_to_env(&serde_json::json!({"files": ["a b", "it's"], "count": 2}), "build-x");
+        assert!(env.contains("OTTO_INPUT_BUILD_X_FILES=('a b' 'it'\\''s')\n"), "{env}");
+        assert!(env.contains("OTTO_INPUT_BUILD_X_COUNT='2'\n"), "{env}");
+        assert!(
+            env.contains("OTTO_JSON_BUILD_X_FILES='[\"a b\",\"it'\\''s\"]'\n"),
+            "{env}"
+        );
+
+        let json = env_to_json("name='x'\nfiles:json='[1, \"two\"]'\n").unwrap();
+        assert_eq!(json, serde_json::json!({"name": "x", "files": [1, "two"]}));
+
+        let err = env_to_json("files:json='[1,'\n").unwrap_err();
+        assert!(err.to_string().starts_with("output files is not valid JSON"), "{err}");
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_typed_outputs_survive_bash_and_python() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let work_dir = PathBuf::from(temp_dir.path());
+        setup_test_db(&work_dir);
+        let result = temp_dir.path().join("result");
+
+        let mut produce = test_task(
+            "produce",
+            &[],
+            r#"otto_set_output_json files '["a b", "c"]'
+otto_set_output_json meta '{"n": 2}'"#,
+        );
+        produce
+            .outputs
+            .insert("files".to_string(), crate::cfg::task::ValueType::List);
+        let relay = test_task(
+            "relay",
+            &["produce"],
+            &format!(
+                r#"echo "${{#OTTO_INPUT_PRODUCE_FILES[@]}} ${{OTTO_INPUT_PRODUCE_FILES[0]}}" > {result}.bash
+otto_set_output_json files "$(otto_get_input_json produce.files)"
+otto_set_output_json meta "$(otto_get_input_json produce.meta)""#,
+                result = result.display()
+            ),
+        );
+        let consume = test_task(
+            "consume",
+            &["relay"],
+            &format!(
+                "#!/usr/bin/env python3\nfiles = otto_get_input('relay.files')\nmeta = otto_get_input('relay.meta')\nopen('{}', 'w').write(f'{{files}} {{meta[\"n\"] + 1}}')",
+                result.display()
+            ),
+        );
+
+        let workspace = Workspace::new(work_dir).await?;
+        workspace.init().await?;
+        let scheduler = TaskScheduler::new(
+            vec![produce, relay, consume],
+            Arc::new(workspace),
+            ExecutionContext::new(),
+            1,
+            false,
+        )
+        .await?;
+        scheduler.execute_all().await?;
+
+        assert_eq!(
+            std::fs::read_to_string(format!("{}.bash", result.display()))?,
+            "2 a b\n"
+        );
+        assert_eq!(std::fs::read_to_string(&result)?, "['a b', 'c'] 3");
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_output_schema_mismatch_fails_task() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let work_dir = PathBuf::from(temp_dir.path());
+        setup_test_db(&work_dir);
+
+        let mut task = Task::new(
+            "produce".to_string(),
+            None,
+            vec![],
+            vec![],
+            vec![],
+            HashMap::new(),
+            HashMap::new(),
+            "otto_set_output files a.txt".to_string(),
+        );
+        task.outputs
+            .insert("files".to_string(), crate::cfg::task::ValueType::List);
+
+        let workspace = Workspace::new(work_dir).await?;
+        workspace.init().await?;
+        let scheduler = TaskScheduler::new(vec![task], Arc::new(workspace), ExecutionContext::new(), 1, false).await?;
+        let err = scheduler.execute_all().await.unwrap_err();
+        assert!(
+            format!("{err:?}").contains("Task produce output files should be of type list, got a string"),
+            "{err:?}"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_workflow_commands_set_outputs_and_annotate() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let work_dir = PathBuf::from(temp_dir.path());
+        setup_test_db(&work_dir);
+
+        let mut task = Task::new(
+            "lint".to_string(),
+            None,
+            vec![],
+            vec![],
+            vec![],
+            HashMap::new(),
+            HashMap::new(),
+            "otto_set_output count 2\necho '::set-output version=1.2.3'\necho '::warning file=src/a.rs,line=3::unused import'"
+                .to_string(),
+        );
+        task.outputs
+            .insert("version".to_string(), crate::cfg::task::ValueType::String);
+
+        let workspace = Arc::new(Workspace::new(work_dir).await?);
+        workspace.init().await?;
+        workspace.save_execution_context(ExecutionContext::new()).await?;
+        let scheduler = TaskScheduler::new(vec![task], workspace.clone(), ExecutionContext::new(), 1, false).await?;
+        scheduler.execute_all().await?;
+
+        let output: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(workspace.task_output_file("lint"))?)?;
+        assert_eq!(output, serde_json::json!({"count": "2", "version": "1.2.3"}));
+
+        let store = workspace.state_store().expect("state store");
+        let tasks = store.get_run_tasks(workspace.db_run_id().expect("run id"))?;
+        let annotations = store.get_task_annotations(tasks[0].id)?;
+        assert_eq!(annotations.len(), 1);
+        assert_eq!(annotations[0].message, "unused import");
+        assert_eq!(annotations[0].location().as_deref(), Some("src/a.rs:3"));
+        assert_eq!(scheduler.annotations.lock().unwrap().len(), 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_progress_from_stdout_and_progress_file() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let work_dir = PathBuf::from(temp_dir.path());
+        setup_test_db(&work_dir);
+
+        let task = Task::new(
+            "migrate".to_string(),
+            None,
+            vec![],
+            vec![],
+            vec![],
+            HashMap::new(),
+            HashMap::new(),
+            "echo '::progress 1/4 users'\necho '3/4 orders' > \"$OTTO_PROGRESS\"\nsleep 1".to_string(),
+        );
+
+        let workspace = Workspace::new(work_dir).await?;
+        workspace.init().await?;
+        let mut scheduler =
+            TaskScheduler::new(vec![task], Arc::new(workspace), ExecutionContext::new(), 1, true).await?;
+        let (tx, mut rx) = tokio::sync::broadcast::channel(100);
+        scheduler.set_message_channel(tx);
+        scheduler.execute_all().await?;
+
+        let mut reported = Vec::new();
+        while let Ok(message) = rx.try_recv() {
+            if let TaskMessage::Progress { task_name, progress } = message {
+                assert_eq!(task_name, "migrate");
+                reported.push((progress.current, progress.message));
+            }
+        }
+        assert_eq!(reported, vec![(1, "users".to_string()), (3, "orders".to_string())]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_reports_written_after_run() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let work_dir = PathBuf::from(temp_dir.path());
+        setup_test_db(&work_dir);
+
+        let tasks = vec![
+            test_task("build", &[], "echo built"),
+            test_task("test", &[], "echo 'assertion failed' >&2; exit 2"),
+        ];
+
+        let workspace = Arc::new(Workspace::new(work_dir).await?);
+        workspace.init().await?;
+        workspace.save_execution_context(ExecutionContext::new()).await?;
+        let mut scheduler = TaskScheduler::new(tasks, workspace, ExecutionContext::new(), 2, false).await?;
+        scheduler.set_keep_going(true);
+        let junit = temp_dir.path().join("reports/junit.xml");
+        let json = temp_dir.path().join("report.json");
+        scheduler.set_reports(vec![
+            ReportSpec::parse(&format!("junit:{}", junit.display())).unwrap(),
+            ReportSpec::parse(&format!("json:{}", json.display())).unwrap(),
+        ]);
+        assert!(scheduler.execute_all().await.is_err());
+
+        let xml = std::fs::read_to_string(junit)?;
+        assert!(xml.contains("tests=\"2\" failures=\"1\""), "{xml}");
+        assert!(xml.contains("<failure message=\"exit code 2\" type=\"failed\">assertion failed</failure>"));
+
+        let report: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(json)?)?;
+        let outcome = |name: &str| {
+            report["tasks"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|task| task["name"] == name)
+                .map(|task| task["outcome"].clone())
+        };
+        assert_eq!(outcome("build"), Some(serde_json::json!("passed")));
+        assert_eq!(outcome("test"), Some(serde_json::json!("failed")));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_reused_outputs_skip_passed_tasks() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let work_dir = PathBuf::from(temp_dir.path());
+        setup_test_db(&work_dir);
+
+        // Output the build task wrote in the run being rerun
+        let earlier = temp_dir.path().join("earlier");
+        std::fs::create_dir_all(&earlier)?;
+        std::fs::write(earlier.join("output.build.json"), r#"{"version": "1.2.3"}"#)?;
+
+        let marker = temp_dir.path().join("built");
+        let tasks = vec![
+            test_task("build", &[], &format!("touch {}", marker.display())),
+            test_task("test", &["build"], "echo tested"),
+        ];
+
+        let workspace = Arc::new(Workspace::new(work_dir).await?);
+        workspace.init().await?;
+        workspace.save_execution_context(ExecutionContext::new()).await?;
+        let mut scheduler = TaskScheduler::new(tasks, workspace.clone(), ExecutionContext::new(), 2, false).await?;
+        scheduler.set_reused_outputs(HashMap::from([(
+            "build".to_string(),
+            Some(earlier.join("output.build.json")),
+        )]));
+        scheduler.execute_all().await?;
+
+        assert_eq!(scheduler.get_task_status("build").await, TaskStatus::Skipped);
+        assert_eq!(scheduler.get_task_status("test").await, TaskStatus::Completed);
+        assert!(!marker.exists(), "reused task must not run");
+        let input = std::fs::read_to_string(workspace.task_input_file("test", "build"))?;
+        assert!(input.contains("1.2.3"), "{input}");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_missing_reused_output_runs_the_task() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let work_dir = PathBuf::from(temp_dir.path());
+        setup_test_db(&work_dir);
+
+        // The earlier run's output was pruned since
+        let pruned = temp_dir.path().join("earlier/output.build.json");
+        let marker = temp_dir.path().join("built");
+        let tasks = vec![test_task("build", &[], &format!("touch {}", marker.display()))];
+
+        let workspace = Arc::new(Workspace::new(work_dir).await?);
+        workspace.init().await?;
+        workspace.save_execution_context(ExecutionContext::new()).await?;
+        let mut scheduler = TaskScheduler::new(tasks, workspace, ExecutionContext::new(), 1, false).await?;
+        scheduler.set_reused_outputs(HashMap::from([("build".to_string(), Some(pruned.clone()))]));
+
+        assert_eq!(
+            scheduler.reuse_decision("build", Some(&pruned)).await,
+            (
+                true,
+                "could not reuse output from the earlier run, running anyway".to_string()
+            )
+        );
+        scheduler.execute_all().await?;
+        assert_eq!(scheduler.get_task_status("build").await, TaskStatus::Completed);
+        assert!(marker.exists());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_tasks_completed_before_resume_are_not_run() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let work_dir = PathBuf::from(temp_dir.path());
+        setup_test_db(&work_dir);
+
+        let marker = temp_dir.path().join("built");
+        let tasks = vec![
+            test_task("build", &[], &format!("touch {}", marker.display())),
+            test_task("test", &["build"], "echo tested"),
+        ];
+
+        let workspace = Arc::new(Workspace::new(work_dir).await?);
+        workspace.init().await?;
+        workspace.save_execution_context(ExecutionContext::new()).await?;
+
+        // What build left in the run directory before the run was interrupted
+        std::fs::create_dir_all(workspace.task("build"))?;
+        std::fs::write(workspace.task_output_file("build"), r#"{"version": "1.2.3"}"#)?;
+
+        let mut scheduler = TaskScheduler::new(tasks, workspace.clone(), ExecutionContext::new(), 2, false).await?;
+        scheduler.set_completed_before_resume(std::collections::HashSet::from(["build".to_string()]));
+        scheduler.execute_all().await?;
+
+        assert_eq!(scheduler.get_task_status("build").await, TaskStatus::Completed);
+        assert_eq!(scheduler.get_task_status("test").await, TaskStatus::Completed);
+        assert!(!marker.exists(), "completed task must not run again");
+        let input = std::fs::read_to_string(workspace.task_input_file("test", "build"))?;
+        assert!(input.contains("1.2.3"), "{input}");
+
+        // Nothing new is recorded for the task that already completed
+        let store = workspace.state_store().unwrap();
+        let recorded = store.get_run_tasks(workspace.db_run_id().unwrap())?;
+        assert!(recorded.iter().all(|task| task.name != "build"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_pty_task_sees_a_terminal() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let work_dir = PathBuf::from(temp_dir.path());
+        setup_test_db(&work_dir);
+
+        let mut task = Task::new(
+            "colors".to_string(),
+            None,
+            vec![],
+            vec![],
+            vec![],
+            HashMap::new(),
+            HashMap::new(),
+            "[ -t 1 ] && printf '\\033[32mtty\\033[0m\\n'; [ -t 2 ] && echo stderr-tty >&2; true".to_string(),
+        );
+        task.pty = true;
+
+        let workspace = Arc::new(Workspace::new(work_dir).await?);
+        workspace.init().await?;
+        let mut scheduler =
+            TaskScheduler::new(vec![task], workspace.clone(), ExecutionContext::new(), 1, false).await?;
+        scheduler.set_strip_ansi(true);
+        scheduler.execute_all().await?;
+
+        let logs = workspace.run().join("tasks").join("colors");
+        assert_eq!(std::fs::read_to_string(logs.join("stdout.log"))?, "tty\n");
+        assert_eq!(std::fs::read_to_string(logs.join("stderr.log"))?, "stderr-tty\n");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_interactive_task_runs_alone() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let work_dir = PathBuf::from(temp_dir.path());
+        setup_test_db(&work_dir);
+        let log = temp_dir.path().join("log");
+
+        let tasks = ["a", "b", "c"]
+            .iter()
+            .map(|name| {
+                let action = if *name == "b" {
+                    format!("echo b >> {}", log.display())
+                } else {
+                    format!(
+                        "echo start-{name} >> {log}; sleep 0.3; echo end-{name} >> {log}",
+                        log = log.display()
+                    )
+                };
+                let mut task = Task::new(
+                    name.to_string(),
+                    None,
+                    vec![],
+                    vec![],
+                    vec![],
+                    HashMap::new(),
+                    HashMap::new(),
+                    action,
+                );
+                task.interactive = *name == "b";
+                task
+            })
+            .collect();
+
+        let workspace = Workspace::new(work_dir).await?;
+        workspace.init().await?;
+        let scheduler = TaskScheduler::new(tasks, Arc::new(workspace), ExecutionContext::new(), 3, false).await?;
+        scheduler.execute_all().await?;
+
+        let lines: Vec<String> = std::fs::read_to_string(&log)?.lines().map(String::from).collect();
+        assert_eq!(lines.len(), 5, "{lines:?}");
+        let b = lines.iter().position(|line| line == "b").unwrap();
+        let running = lines[..b].iter().filter(|line| line.starts_with("start-")).count()
+            - lines[..b].iter().filter(|line| line.starts_with("end-")).count();
+        assert_eq!(running, 0, "interactive task overlapped another task: {lines:?}");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_service_is_stopped_after_its_dependents() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let work_dir = PathBuf::from(temp_dir.path());
+        setup_test_db(&work_dir);
+
+        let mut server = test_task("server", &[], "echo starting; sleep 0.2; echo listening; sleep 30");
+        server.service = true;
+        server.ready = Some(crate::cfg::task::ReadySpec {
+            log: Some("^listening$".to_string()),
+            ..Default::default()
+        });
+        let tasks = vec![server, test_task("integration", &["server"], "echo integration")];
+
+        let workspace = Workspace::new(work_dir).await?;
+        workspace.init().await?;
+        // One job slot: the service must give it back once ready
+        let scheduler = TaskScheduler::new(tasks, Arc::new(workspace), ExecutionContext::new(), 1, false).await?;
+
+        let started = std::time::Instant::now();
+        scheduler.execute_all().await?;
+        assert!(started.elapsed() < Duration::from_secs(15));
+        assert_eq!(scheduler.get_task_status("integration").await, TaskStatus::Completed);
+        assert_eq!(scheduler.get_task_status("server").await, TaskStatus::Completed);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_on_failure_handler_runs_only_when_triggered() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let work_dir = PathBuf::from(temp_dir.path());
+        setup_test_db(&work_dir);
+
+        let mut notify_broken = test_task("notify-broken", &["broken"], "echo notify");
+        notify_broken.handles_failure_of = vec!["broken".to_string()];
+        let mut notify_lint = test_task("notify-lint", &["lint"], "echo notify");
+        notify_lint.handles_failure_of = vec!["lint".to_string()];
+        let tasks = vec![
+            test_task("broken", &[], "exit 1"),
+            test_task("lint", &[], "echo lint"),
+            notify_broken,
+            notify_lint,
+        ];
+
+        let workspace = Workspace::new(work_dir).await?;
+        workspace.init().await?;
+        let mut scheduler = TaskScheduler::new(tasks, Arc::new(workspace), ExecutionContext::new(), 1, false).await?;
+        scheduler.set_keep_going(true);
+
+        assert!(scheduler.execute_all().await.is_err());
+        assert_eq!(scheduler.get_task_status("notify-broken").await, TaskStatus::Completed);
+        assert_eq!(scheduler.get_task_status("notify-lint").await, TaskStatus::Skipped);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_task_retry_until_success() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let work_dir = PathBuf::from(temp_dir.path());
+        setup_test_db(&work_dir);
+        let counter = work_dir.join("attempts");
+
+        let mut task = Task::new(
+            "flaky".to_string(),
+            None,
+            vec![],
+            vec![],
+            vec![],
+            HashMap::new(),
+            HashMap::new(),
+            format!(
+                "echo x >> {0}\necho attempt $(wc -l < {0})\n[ $(wc -l < {0}) -ge 2 ]",
+                counter.display()
+            ),
+        );
+        task.retry = Some(RetrySpec {
+            attempts: 3,
+            ..Default::default()
+        });
+
+        let workspace = Workspace::new(work_dir).await?;
+        workspace.init().await?;
+        let task_dir = workspace.task("flaky");
+        let scheduler = TaskScheduler::new(vec![task], Arc::new(workspace), ExecutionContext::new(), 1, false).await?;
+
+        scheduler.execute_all().await?;
+
+        assert_eq!(scheduler.get_task_status("flaky").await, TaskStatus::Completed);
+        assert_eq!(std::fs::read_to_string(&counter)?.lines().count(), 2);
+        assert!(task_dir.join("stdout.attempt-1.log").exists());
+        assert!(task_dir.join("stdout.attempt-2.log").exists());
+        assert!(!task_dir.join("stdout.attempt-3.log").exists());
+        assert!(std::fs::read_to_string(task_dir.join("stdout.log"))?.contains("attempt 2"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_task_retry_respects_exit_codes() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let work_dir = PathBuf::from(temp_dir.path());
+        setup_test_db(&work_dir);
+        let counter = work_dir.join("attempts");
+
+        let mut task = Task::new(
+            "fatal".to_string(),
+            None,
+            vec![],
+            vec![],
+            vec![],
+            HashMap::new(),
+            HashMap::new(),
+            format!("echo x >> {}\nexit 3", counter.display()),
+        );
+        task.retry = Some(RetrySpec {
+            attempts: 3,
+            on_exit_codes: vec![75],
+            ..Default::default()
+        });
+
+        let workspace = Workspace::new(work_dir).await?;
+        workspace.init().await?;
+        let scheduler = TaskScheduler::new(vec![task], Arc::new(workspace), ExecutionContext::new(), 1, false).await?;
+
+        assert!(scheduler.execute_all().await.is_err());
+        assert_eq!(std::fs::read_to_string(&counter)?.lines().count(), 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_task_timeout_escalates_to_sigkill() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let work_dir = PathBuf::from(temp_dir.path());
+        setup_test_db(&work_dir);
+
+        // Ignore SIGTERM so only the SIGKILL after the grace period can stop the task
+        let mut task = Task::new(
+            "stubborn".to_string(),
+            None,
+            vec![],
+            vec![],
+            vec![],
+            HashMap::new(),
+            HashMap::new(),
+            "trap '' TERM\nsleep 30".to_string(),
+        );
+        task.timeout = Some(Duration::from_millis(300));
+        task.timeout_grace = Some(Duration::from_millis(300));
+
+        let workspace = Workspace::new(work_dir).await?;
+        workspace.init().await?;
+        let scheduler = TaskScheduler::new(vec![task], Arc::new(workspace), ExecutionContext::new(), 1, false).await?;
+
+        let start = std::time::Instant::now();
+        let result = scheduler.execute_all().await;
+
+        assert!(result.is_err());
+        assert!(start.elapsed() < Duration::from_secs(10));
+        assert_eq!(scheduler.get_task_status("stubborn").await, TaskStatus::TimedOut);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_cancel_stops_running_and_pending_tasks() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let work_dir = PathBuf::from(temp_dir.path());
+        setup_test_db(&work_dir);
+
+        let slow = Task::new(
+            "slow".to_string(),
+            None,
+            vec![],
+            vec![],
+            vec![],
+            HashMap::new(),
+            HashMap::new(),
+            "sleep 30".to_string(),
+        );
+        let after = Task::new(
+            "after".to_string(),
+            None,
+            vec![],
+            vec![],
+            vec!["slow".to_string()],
+            HashMap::new(),
+            HashMap::new(),
+            "echo after".to_string(),
+        );
+
+        let workspace = Workspace::new(work_dir).await?;
+        workspace.init().await?;
+        let scheduler = TaskScheduler::new(
+            vec![slow, after],
+            Arc::new(workspace),
+            ExecutionContext::new(),
+            1,
+            false,
+        )
+        .await?;
+
+        let cancel = scheduler.cancel_handle();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            cancel.interrupt();
+        });
+
+        let start = std::time::Instant::now();
+        let result = scheduler.execute_all().await;
+
+        assert!(result.unwrap_err().to_string().starts_with("Run cancelled"));
+        assert!(start.elapsed() < Duration::from_secs(10));
+        assert_eq!(scheduler.get_task_status("slow").await, TaskStatus::Cancelled);
+        assert_eq!(scheduler.get_task_status("after").await, TaskStatus::Cancelled);
+
+        Ok(())
+    }
+
     #[tokio::test]
     #[serial]
     async fn test_file_dependencies() -> Result<()> {
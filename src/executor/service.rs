@@ -0,0 +1,172 @@
+//! Readiness probes for `service: true` tasks
+//!
+//! A service keeps running in the background while its dependents run, so
+//! the scheduler can't wait for it to exit. Instead it waits until every
+//! probe configured under `ready:` passes: a TCP port accepting connections,
+//! a line on the service's stdout matching a regex, or a command exiting 0.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use eyre::{Result, eyre};
+use regex::Regex;
+use tokio::net::TcpStream;
+use tokio::process::Command;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::time::{Instant, sleep, sleep_until, timeout, timeout_at};
+
+use crate::cfg::duration::format_duration;
+use crate::cfg::task::ReadySpec;
+
+use super::output::{OutputType, TaskOutput};
+
+/// Wait until every probe of a service passes. `output` carries the service's
+/// output lines for the `log` probe; `cwd` and `envs` are used to run the
+/// `command` probe the way the task itself runs.
+pub async fn wait_until_ready(
+    task_name: &str,
+    ready: &ReadySpec,
+    mut output: broadcast::Receiver<TaskOutput>,
+    cwd: &Path,
+    envs: &HashMap<String, String>,
+) -> Result<()> {
+    let log_pattern = ready
+        .log
+        .as_deref()
+        .map(Regex::new)
+        .transpose()
+        .map_err(|e| eyre!("Task {task_name} has an invalid ready.log regex: {e}"))?;
+    let mut log_seen = log_pattern.is_none();
+    let mut output_open = true;
+    let deadline = Instant::now() + ready.timeout();
+
+    loop {
+        // A probe still running at the deadline counts as failed
+        let probes = async {
+            log_seen && port_open(ready.port, ready.interval()).await && command_passes(ready, cwd, envs).await
+        };
+        if timeout_at(deadline, probes).await.unwrap_or(false) {
+            return Ok(());
+        }
+
+        tokio::select! {
+            _ = sleep_until(deadline) => {
+                return Err(eyre!(
+                    "Task {task_name} did not become ready within {}",
+                    format_duration(ready.timeout())
+                ));
+            }
+            _ = sleep(ready.interval()) => {}
+            line = output.recv(), if !log_seen && output_open => match line {
+                Ok(line) => {
+                    log_seen = line.stream_type == OutputType::Stdout
+                        && log_pattern.as_ref().is_some_and(|pattern| pattern.is_match(line.content.trim_end()));
+                }
+                Err(RecvError::Lagged(skipped)) => log::debug!("Readiness probe of {task_name} skipped {skipped} lines"),
+                Err(RecvError::Closed) => output_open = false,
+            },
+        }
+    }
+}
+
+/// Whether the probed port (if any) accepts connections on localhost
+async fn port_open(port: Option<u16>, wait: std::time::Duration) -> bool {
+    let Some(port) = port else {
+        return true;
+    };
+    matches!(timeout(wait, TcpStream::connect(("127.0.0.1", port))).await, Ok(Ok(_)))
+}
+
+/// Whether the probe command (if any) exits 0; it is killed if the wait is abandoned
+async fn command_passes(ready: &ReadySpec, cwd: &Path, envs: &HashMap<String, String>) -> bool {
+    let Some(command) = &ready.command else {
+        return true;
+    };
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .current_dir(cwd)
+        .envs(envs)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .kill_on_drop(true)
+        .status()
+        .await;
+    matches!(status, Ok(status) if status.success())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, SystemTime};
+
+    fn probe(ready: ReadySpec) -> ReadySpec {
+        ReadySpec {
+            timeout: Some(Duration::from_secs(5)),
+            interval: Some(Duration::from_millis(20)),
+            ..ready
+        }
+    }
+
+    fn line(content: &str) -> TaskOutput {
+        TaskOutput {
+            task_name: "api".to_string(),
+            stream_type: OutputType::Stdout,
+            timestamp: SystemTime::now(),
+            content: format!("{content}\n"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_log_probe_waits_for_matching_line() {
+        let (tx, rx) = broadcast::channel(16);
+        let ready = probe(ReadySpec {
+            log: Some(r"listening on :\d+".to_string()),
+            ..Default::default()
+        });
+
+        tokio::spawn(async move {
+            tx.send(line("starting")).unwrap();
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            tx.send(line("listening on :8080")).unwrap();
+            tokio::time::sleep(Duration::from_secs(10)).await;
+        });
+
+        let result = wait_until_ready("api", &ready, rx, Path::new("."), &HashMap::new()).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_port_probe() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let ready = probe(ReadySpec {
+            port: Some(port),
+            ..Default::default()
+        });
+        let (_tx, rx) = broadcast::channel(16);
+
+        assert!(
+            wait_until_ready("api", &ready, rx, Path::new("."), &HashMap::new())
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_command_probe_times_out() {
+        let ready = ReadySpec {
+            command: Some("exit 1".to_string()),
+            timeout: Some(Duration::from_millis(200)),
+            interval: Some(Duration::from_millis(20)),
+            ..Default::default()
+        };
+        let (_tx, rx) = broadcast::channel(16);
+
+        let err = wait_until_ready("api", &ready, rx, Path::new("."), &HashMap::new())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().starts_with("Task api did not become ready within"));
+    }
+}
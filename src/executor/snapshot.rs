@@ -0,0 +1,139 @@
+//! What each task of a run was started with (`snapshot.yaml` in the run directory)
+//!
+//! The state database records how tasks ended but not what they were given.
+//! The resolved params and env of every task are written next to `run.yaml`
+//! when a run starts, so `otto Diff` can show what changed between two runs.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::state::TaskRecord;
+use super::task::Task;
+use crate::cfg::param::Value;
+
+/// File name of the snapshot in the run directory
+pub const SNAPSHOT_FILE: &str = "snapshot.yaml";
+
+/// Resolved params and env of every task in a run
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunSnapshot {
+    pub tasks: BTreeMap<String, TaskSnapshot>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaskSnapshot {
+    /// Param values as the task's action saw them; params without a value are left out
+    pub params: BTreeMap<String, String>,
+    /// Environment the ottofile sets for the task, after evaluation, without the params
+    pub env: BTreeMap<String, String>,
+}
+
+impl RunSnapshot {
+    pub fn from_tasks(tasks: &[Task]) -> Self {
+        let tasks = tasks
+            .iter()
+            .map(|task| {
+                let params = task
+                    .values
+                    .iter()
+                    .filter_map(|(name, value)| value_text(value).map(|text| (name.clone(), text)))
+                    .collect();
+                // Params are exported to the environment too; they're compared as params
+                let env = task
+                    .envs
+                    .iter()
+                    .filter(|(name, _)| !task.values.keys().any(|param| param.replace('-', "_") == **name))
+                    .map(|(k, v)| (k.clone(), v.clone()))
+                    .collect();
+                (task.name.clone(), TaskSnapshot { params, env })
+            })
+            .collect();
+        Self { tasks }
+    }
+
+    /// Read the snapshot of a run directory; runs from before snapshots were written have none
+    pub fn load(run_dir: &Path) -> Option<Self> {
+        let content = std::fs::read_to_string(run_dir.join(SNAPSHOT_FILE)).ok()?;
+        serde_yaml::from_str(&content)
+            .inspect_err(|e| log::warn!("Ignoring unreadable snapshot in {}: {}", run_dir.display(), e))
+            .ok()
+    }
+
+    /// Find the snapshot of a run through the log paths of its tasks (`<run>/tasks/<task>/stdout.log`)
+    pub fn for_tasks(tasks: &[TaskRecord]) -> Option<Self> {
+        let run_dir = tasks
+            .iter()
+            .filter_map(|task| task.stdout_path.as_deref())
+            .find_map(|stdout| stdout.parent()?.parent()?.parent())?;
+        Self::load(run_dir)
+    }
+}
+
+/// A param value as text, the way it is exported to the task's environment
+fn value_text(value: &Value) -> Option<String> {
+    match value {
+        Value::Item(item) => Some(item.clone()),
+        Value::List(items) => Some(items.join(" ")),
+        Value::Dict(entries) => {
+            let mut entries: Vec<String> = entries.iter().map(|(k, v)| format!("{k}={v}")).collect();
+            entries.sort();
+            Some(entries.join(" "))
+        }
+        Value::Empty => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let task = Task::new(
+            "build".to_string(),
+            None,
+            vec![],
+            vec![],
+            vec![],
+            HashMap::from([
+                ("PROFILE".to_string(), "release".to_string()),
+                ("profile".to_string(), "release".to_string()),
+            ]),
+            HashMap::from([
+                ("profile".to_string(), Value::Item("release".to_string())),
+                (
+                    "features".to_string(),
+                    Value::List(vec!["a".to_string(), "b".to_string()]),
+                ),
+                ("unset".to_string(), Value::Empty),
+            ]),
+            "cargo build".to_string(),
+        );
+
+        let snapshot = RunSnapshot::from_tasks(&[task]);
+        let build = &snapshot.tasks["build"];
+        assert_eq!(
+            build.params,
+            BTreeMap::from([
+                ("features".to_string(), "a b".to_string()),
+                ("profile".to_string(), "release".to_string()),
+            ])
+        );
+        assert_eq!(
+            build.env,
+            BTreeMap::from([("PROFILE".to_string(), "release".to_string())])
+        );
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            temp_dir.path().join(SNAPSHOT_FILE),
+            serde_yaml::to_string(&snapshot).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(RunSnapshot::load(temp_dir.path()), Some(snapshot));
+        assert_eq!(RunSnapshot::load(&temp_dir.path().join("missing")), None);
+    }
+}
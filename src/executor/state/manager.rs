@@ -6,6 +6,7 @@ use std::time::SystemTime;
 use super::db::DatabaseManager;
 use super::metadata::RunMetadata;
 use super::schema::{RunStatus, TaskStatus};
+use crate::executor::workflow::{Annotation, AnnotationLevel};
 use crate::ports::StateStore;
 
 /// State manager for recording and querying run/task state
@@ -28,6 +29,8 @@ pub struct RunRecord {
     pub hostname: Option<String>,
     pub args: Option<Vec<String>>,
     pub ended_at: Option<u64>,
+    /// How many times the run was continued with `otto --resume`
+    pub resume_count: u32,
 }
 
 /// A task record from the database
@@ -45,6 +48,10 @@ pub struct TaskRecord {
     pub stdout_path: Option<PathBuf>,
     pub stderr_path: Option<PathBuf>,
     pub script_path: Option<PathBuf>,
+    /// Attempt number (1 for the first run, incremented on each retry)
+    pub attempt: u32,
+    /// Why the task was skipped (only set for skipped tasks)
+    pub skip_reason: Option<String>,
 }
 
 /// Overall system statistics
@@ -70,11 +77,20 @@ pub struct TaskStats {
     pub successful_executions: u64,
     pub failed_executions: u64,
     pub skipped_executions: u64,
+    pub timed_out_executions: u64,
+    /// Executions whose outputs were restored from the output cache
+    pub cached_executions: u64,
+    /// Executions that needed at least one retry
+    pub retried_executions: u64,
+    /// Additional attempt rows created by retries
+    pub retry_attempts: u64,
     pub avg_duration_seconds: Option<f64>,
     pub min_duration_seconds: Option<f64>,
     pub max_duration_seconds: Option<f64>,
     pub last_executed: Option<u64>,
     pub last_status: Option<TaskStatus>,
+    /// Whether the most recent script differs from the one of the last successful run
+    pub script_changed_since_success: bool,
 }
 
 /// Project summary information
@@ -118,11 +134,11 @@ impl StateManager {
                 .transpose()
                 .context("Failed to serialize args")?;
 
-            // Insert run record
+            // Insert run record, remembering which process owns it so a crash can be detected later
             conn.execute(
                 "INSERT INTO runs (
-                    project_id, timestamp, status, ottofile_path, cwd, user, hostname, args
-                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                    project_id, timestamp, status, ottofile_path, cwd, user, hostname, args, pid
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
                 params![
                     project_id,
                     metadata.timestamp as i64,
@@ -132,6 +148,7 @@ impl StateManager {
                     metadata.user,
                     metadata.hostname,
                     args_json,
+                    std::process::id() as i64,
                 ],
             )?;
 
@@ -174,6 +191,85 @@ impl StateManager {
         })
     }
 
+    /// Mark an interrupted run as running again in this process (`otto --resume`)
+    pub fn record_run_resumed(&self, run_id: i64) -> Result<()> {
+        self.db.with_connection(|conn| {
+            let updated = conn.execute(
+                "UPDATE runs
+                 SET status = ?1, pid = ?2, ended_at = NULL, duration_seconds = NULL,
+                     resume_count = resume_count + 1
+                 WHERE id = ?3",
+                params![RunStatus::Running.as_str(), std::process::id() as i64, run_id],
+            )?;
+            if updated == 0 {
+                return Err(eyre::eyre!("Run {} not found", run_id));
+            }
+            Ok(())
+        })
+    }
+
+    /// Finalize runs left `running` by a process that no longer exists (crash, kill -9, power loss).
+    /// Their unfinished tasks are marked cancelled and the run gets the status its tasks imply.
+    /// Returns the number of runs reconciled.
+    pub fn reconcile_stale_runs(&self) -> Result<usize> {
+        let now = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .context("Failed to get current time")?
+            .as_secs() as i64;
+
+        self.db.with_connection(|conn| {
+            let mut stmt = conn.prepare("SELECT id, pid FROM runs WHERE status = ?1")?;
+            let stale: Vec<i64> = stmt
+                .query_map(params![RunStatus::Running.as_str()], |row| {
+                    Ok((row.get::<_, i64>(0)?, row.get::<_, Option<i64>>(1)?))
+                })?
+                .collect::<Result<Vec<_>, _>>()?
+                .into_iter()
+                .filter(|(_, pid)| !pid.is_some_and(|pid| process_is_alive(pid as u32)))
+                .map(|(id, _)| id)
+                .collect();
+
+            for run_id in &stale {
+                let interrupted = conn.execute(
+                    "UPDATE tasks SET status = ?1, ended_at = ?2 WHERE run_id = ?3 AND status = ?4",
+                    params![
+                        TaskStatus::Cancelled.as_str(),
+                        now,
+                        run_id,
+                        TaskStatus::Running.as_str()
+                    ],
+                )?;
+                let failed: bool = conn.query_row(
+                    "SELECT EXISTS(SELECT 1 FROM tasks WHERE run_id = ?1 AND status IN ('failed', 'timed_out'))",
+                    params![run_id],
+                    |row| row.get(0),
+                )?;
+                let cancelled: bool = conn.query_row(
+                    "SELECT EXISTS(SELECT 1 FROM tasks WHERE run_id = ?1 AND status = 'cancelled')",
+                    params![run_id],
+                    |row| row.get(0),
+                )?;
+
+                let status = if interrupted > 0 || cancelled {
+                    RunStatus::Cancelled
+                } else if failed {
+                    RunStatus::Failed
+                } else {
+                    RunStatus::Success
+                };
+                conn.execute(
+                    "UPDATE runs
+                     SET status = ?1,
+                         ended_at = COALESCE(ended_at, (SELECT MAX(ended_at) FROM tasks WHERE run_id = ?2), timestamp)
+                     WHERE id = ?2",
+                    params![status.as_str(), run_id],
+                )?;
+            }
+
+            Ok(stale.len())
+        })
+    }
+
     pub fn record_task_start(
         &self,
         run_id: i64,
@@ -236,25 +332,170 @@ impl StateManager {
         })
     }
 
-    /// Record that a task was skipped
-    pub fn record_task_skipped(&self, run_id: i64, task_name: &str, script_hash: Option<&str>) -> Result<i64> {
+    /// Record which attempt a task row belongs to (for retried tasks)
+    pub fn record_task_attempt(&self, task_id: i64, attempt: u32) -> Result<()> {
         self.db.with_connection(|conn| {
             conn.execute(
-                "INSERT INTO tasks (run_id, name, status, script_hash)
-                 VALUES (?1, ?2, ?3, ?4)",
-                params![run_id, task_name, TaskStatus::Skipped.as_str(), script_hash],
+                "UPDATE tasks SET attempt = ?1 WHERE id = ?2",
+                params![attempt as i64, task_id],
+            )?;
+
+            Ok(())
+        })
+    }
+
+    /// Record that a task was skipped, along with the reason it was considered up to date
+    pub fn record_task_skipped(
+        &self,
+        run_id: i64,
+        task_name: &str,
+        script_hash: Option<&str>,
+        reason: &str,
+    ) -> Result<i64> {
+        let now = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .context("Failed to get current time")?
+            .as_secs() as i64;
+
+        self.db.with_connection(|conn| {
+            conn.execute(
+                "INSERT INTO tasks (run_id, name, status, script_hash, started_at, ended_at, skip_reason)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?5, ?6)",
+                params![
+                    run_id,
+                    task_name,
+                    TaskStatus::Skipped.as_str(),
+                    script_hash,
+                    now,
+                    reason
+                ],
             )?;
 
             Ok(conn.last_insert_rowid())
         })
     }
 
+    /// Record that a task's outputs were restored from the output cache
+    pub fn record_task_cached(&self, run_id: i64, task_name: &str, script_hash: Option<&str>) -> Result<i64> {
+        let now = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .context("Failed to get current time")?
+            .as_secs() as i64;
+
+        self.db.with_connection(|conn| {
+            conn.execute(
+                "INSERT INTO tasks (run_id, name, status, script_hash, started_at, ended_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?5)",
+                params![run_id, task_name, TaskStatus::Cached.as_str(), script_hash, now],
+            )?;
+
+            Ok(conn.last_insert_rowid())
+        })
+    }
+
+    /// Record that a task was not run because one of its dependencies failed
+    pub fn record_task_dependency_failed(&self, run_id: i64, task_name: &str) -> Result<i64> {
+        self.db.with_connection(|conn| {
+            conn.execute(
+                "INSERT INTO tasks (run_id, name, status)
+                 VALUES (?1, ?2, ?3)",
+                params![run_id, task_name, TaskStatus::DependencyFailed.as_str()],
+            )?;
+
+            Ok(conn.last_insert_rowid())
+        })
+    }
+
+    /// Get the content fingerprint of the last successful run of a task in this run's project
+    pub fn get_task_fingerprint(&self, run_id: i64, task_name: &str) -> Result<Option<String>> {
+        self.db.with_connection(|conn| {
+            let fingerprint = conn
+                .query_row(
+                    "SELECT f.fingerprint FROM fingerprints f
+                     JOIN runs r ON r.project_id = f.project_id
+                     WHERE r.id = ?1 AND f.task_name = ?2",
+                    params![run_id, task_name],
+                    |row| row.get(0),
+                )
+                .optional()?;
+
+            Ok(fingerprint)
+        })
+    }
+
+    /// Store the content fingerprint of a successful task run for this run's project
+    pub fn record_task_fingerprint(&self, run_id: i64, task_name: &str, fingerprint: &str) -> Result<()> {
+        let updated_at = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .context("Failed to get current time")?
+            .as_secs();
+
+        self.db.with_connection(|conn| {
+            conn.execute(
+                "INSERT INTO fingerprints (project_id, task_name, fingerprint, updated_at)
+                 SELECT project_id, ?2, ?3, ?4 FROM runs WHERE id = ?1
+                 ON CONFLICT (project_id, task_name)
+                 DO UPDATE SET fingerprint = excluded.fingerprint, updated_at = excluded.updated_at",
+                params![run_id, task_name, fingerprint, updated_at as i64],
+            )?;
+
+            Ok(())
+        })
+    }
+
+    /// Store the errors, warnings and notices a task reported
+    pub fn record_task_annotations(&self, task_id: i64, annotations: &[Annotation]) -> Result<()> {
+        self.db.with_connection(|conn| {
+            let mut stmt = conn.prepare(
+                "INSERT INTO annotations (task_id, level, message, file, line, col, title)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            )?;
+            for annotation in annotations {
+                stmt.execute(params![
+                    task_id,
+                    annotation.level.as_str(),
+                    annotation.message,
+                    annotation.file,
+                    annotation.line,
+                    annotation.col,
+                    annotation.title,
+                ])?;
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Get the annotations a task reported, in the order they were printed
+    pub fn get_task_annotations(&self, task_id: i64) -> Result<Vec<Annotation>> {
+        self.db.with_connection(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT level, message, file, line, col, title FROM annotations WHERE task_id = ?1 ORDER BY id",
+            )?;
+            let annotations = stmt
+                .query_map(params![task_id], |row| {
+                    let level: String = row.get(0)?;
+                    Ok(Annotation {
+                        level: AnnotationLevel::parse(&level).unwrap_or(AnnotationLevel::Notice),
+                        message: row.get(1)?,
+                        file: row.get(2)?,
+                        line: row.get(3)?,
+                        col: row.get(4)?,
+                        title: row.get(5)?,
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(annotations)
+        })
+    }
+
     /// Get recent runs, optionally filtered by project hash
     pub fn get_recent_runs(&self, limit: usize, project_filter: Option<&str>) -> Result<Vec<RunRecord>> {
         self.db.with_connection(|conn| {
             let query = if let Some(_project_hash) = project_filter {
                 "SELECT r.id, r.project_id, r.timestamp, r.status, r.duration_seconds,
-                            r.size_bytes, r.ottofile_path, r.cwd, r.user, r.hostname, r.args, r.ended_at
+                            r.size_bytes, r.ottofile_path, r.cwd, r.user, r.hostname, r.args, r.ended_at, r.resume_count
                      FROM runs r
                      JOIN projects p ON r.project_id = p.id
                      WHERE p.hash = ?1
@@ -262,7 +503,7 @@ impl StateManager {
                      LIMIT ?2"
             } else {
                 "SELECT r.id, r.project_id, r.timestamp, r.status, r.duration_seconds,
-                            r.size_bytes, r.ottofile_path, r.cwd, r.user, r.hostname, r.args, r.ended_at
+                            r.size_bytes, r.ottofile_path, r.cwd, r.user, r.hostname, r.args, r.ended_at, r.resume_count
                      FROM runs r
                      ORDER BY r.timestamp DESC
                      LIMIT ?1"
@@ -280,12 +521,27 @@ impl StateManager {
         })
     }
 
+    pub fn get_run(&self, run_id: i64) -> Result<Option<RunRecord>> {
+        self.db.with_connection(|conn| {
+            conn.query_row(
+                "SELECT id, project_id, timestamp, status, duration_seconds,
+                        size_bytes, ottofile_path, cwd, user, hostname, args, ended_at, resume_count
+                 FROM runs
+                 WHERE id = ?1",
+                params![run_id],
+                Self::row_to_run_record,
+            )
+            .optional()
+            .context("Failed to fetch run")
+        })
+    }
+
     pub fn get_run_tasks(&self, run_id: i64) -> Result<Vec<TaskRecord>> {
         self.db.with_connection(|conn| {
             let mut stmt = conn.prepare(
                 "SELECT id, run_id, name, status, script_hash, exit_code,
                         started_at, ended_at, duration_seconds,
-                        stdout_path, stderr_path, script_path
+                        stdout_path, stderr_path, script_path, attempt, skip_reason
                  FROM tasks
                  WHERE run_id = ?1
                  ORDER BY started_at ASC",
@@ -302,7 +558,7 @@ impl StateManager {
             let mut stmt = conn.prepare(
                 "SELECT id, run_id, name, status, script_hash, exit_code,
                         started_at, ended_at, duration_seconds,
-                        stdout_path, stderr_path, script_path
+                        stdout_path, stderr_path, script_path, attempt, skip_reason
                  FROM tasks
                  WHERE name = ?1
                  ORDER BY started_at DESC
@@ -337,6 +593,7 @@ impl StateManager {
             hostname: row.get(9)?,
             args,
             ended_at: row.get::<_, Option<i64>>(11)?.map(|t| t as u64),
+            resume_count: row.get(12)?,
         })
     }
 
@@ -358,9 +615,38 @@ impl StateManager {
             stdout_path: row.get::<_, Option<String>>(9)?.map(PathBuf::from),
             stderr_path: row.get::<_, Option<String>>(10)?.map(PathBuf::from),
             script_path: row.get::<_, Option<String>>(11)?.map(PathBuf::from),
+            attempt: row.get::<_, Option<i64>>(12)?.map(|a| a as u32).unwrap_or(1),
+            skip_reason: row.get(13)?,
         })
     }
 
+    /// Whether the latest recorded script of a task differs from the script of its last successful run
+    fn script_changed_since_success(
+        conn: &rusqlite::Connection,
+        task_name: &str,
+        project_id: i64,
+    ) -> rusqlite::Result<bool> {
+        let latest_hash = |status_filter: &str| -> rusqlite::Result<Option<String>> {
+            conn.query_row(
+                &format!(
+                    "SELECT t.script_hash
+                     FROM tasks t
+                     JOIN runs r ON t.run_id = r.id
+                     WHERE t.name = ?1 AND r.project_id = ?2 AND t.script_hash IS NOT NULL {status_filter}
+                     ORDER BY t.started_at DESC, t.id DESC
+                     LIMIT 1"
+                ),
+                params![task_name, project_id],
+                |row| row.get(0),
+            )
+            .optional()
+        };
+
+        let latest = latest_hash("")?;
+        let last_success = latest_hash("AND t.status = 'completed'")?;
+        Ok(matches!((latest, last_success), (Some(latest), Some(success)) if latest != success))
+    }
+
     /// Get overall system statistics
     pub fn get_overall_stats(&self) -> Result<OverallStats> {
         self.db.with_connection(|conn| {
@@ -488,6 +774,42 @@ impl StateManager {
                     |row| row.get(0),
                 )?;
 
+                let timed_out_executions: u64 = conn.query_row(
+                    "SELECT COUNT(*)
+                     FROM tasks t
+                     JOIN runs r ON t.run_id = r.id
+                     WHERE t.name = ?1 AND r.project_id = ?2 AND t.status = 'timed_out'",
+                    params![task_name, project_id],
+                    |row| row.get(0),
+                )?;
+
+                let cached_executions: u64 = conn.query_row(
+                    "SELECT COUNT(*)
+                     FROM tasks t
+                     JOIN runs r ON t.run_id = r.id
+                     WHERE t.name = ?1 AND r.project_id = ?2 AND t.status = 'cached'",
+                    params![task_name, project_id],
+                    |row| row.get(0),
+                )?;
+
+                let retried_executions: u64 = conn.query_row(
+                    "SELECT COUNT(*)
+                     FROM tasks t
+                     JOIN runs r ON t.run_id = r.id
+                     WHERE t.name = ?1 AND r.project_id = ?2 AND t.attempt = 2",
+                    params![task_name, project_id],
+                    |row| row.get(0),
+                )?;
+
+                let retry_attempts: u64 = conn.query_row(
+                    "SELECT COUNT(*)
+                     FROM tasks t
+                     JOIN runs r ON t.run_id = r.id
+                     WHERE t.name = ?1 AND r.project_id = ?2 AND t.attempt > 1",
+                    params![task_name, project_id],
+                    |row| row.get(0),
+                )?;
+
                 let avg_duration_seconds: Option<f64> = conn
                     .query_row(
                         "SELECT AVG(t.duration_seconds)
@@ -538,6 +860,7 @@ impl StateManager {
                     .unwrap_or((None, None));
 
                 let last_status = last_status_str.and_then(|s| TaskStatus::parse(&s));
+                let script_changed_since_success = Self::script_changed_since_success(conn, task_name, project_id)?;
 
                 stats.push(TaskStats {
                     project_id,
@@ -548,11 +871,16 @@ impl StateManager {
                     successful_executions,
                     failed_executions,
                     skipped_executions,
+                    timed_out_executions,
+                    cached_executions,
+                    retried_executions,
+                    retry_attempts,
                     avg_duration_seconds,
                     min_duration_seconds,
                     max_duration_seconds,
                     last_executed: last_executed.map(|t| t as u64),
                     last_status,
+                    script_changed_since_success,
                 });
             }
 
@@ -640,6 +968,42 @@ impl StateManager {
                     |row| row.get(0),
                 )?;
 
+                let timed_out_executions: u64 = conn.query_row(
+                    "SELECT COUNT(*)
+                     FROM tasks t
+                     JOIN runs r ON t.run_id = r.id
+                     WHERE t.name = ?1 AND r.project_id = ?2 AND t.status = 'timed_out'",
+                    params![&task_name, project_id],
+                    |row| row.get(0),
+                )?;
+
+                let cached_executions: u64 = conn.query_row(
+                    "SELECT COUNT(*)
+                     FROM tasks t
+                     JOIN runs r ON t.run_id = r.id
+                     WHERE t.name = ?1 AND r.project_id = ?2 AND t.status = 'cached'",
+                    params![&task_name, project_id],
+                    |row| row.get(0),
+                )?;
+
+                let retried_executions: u64 = conn.query_row(
+                    "SELECT COUNT(*)
+                     FROM tasks t
+                     JOIN runs r ON t.run_id = r.id
+                     WHERE t.name = ?1 AND r.project_id = ?2 AND t.attempt = 2",
+                    params![&task_name, project_id],
+                    |row| row.get(0),
+                )?;
+
+                let retry_attempts: u64 = conn.query_row(
+                    "SELECT COUNT(*)
+                     FROM tasks t
+                     JOIN runs r ON t.run_id = r.id
+                     WHERE t.name = ?1 AND r.project_id = ?2 AND t.attempt > 1",
+                    params![&task_name, project_id],
+                    |row| row.get(0),
+                )?;
+
                 let avg_duration_seconds: Option<f64> = conn
                     .query_row(
                         "SELECT AVG(t.duration_seconds)
@@ -691,6 +1055,7 @@ impl StateManager {
                     .unwrap_or((None, None));
 
                 let last_status = last_status_str.and_then(|s| TaskStatus::parse(&s));
+                let script_changed_since_success = Self::script_changed_since_success(conn, &task_name, project_id)?;
 
                 stats.push(TaskStats {
                     project_id,
@@ -701,11 +1066,16 @@ impl StateManager {
                     successful_executions,
                     failed_executions,
                     skipped_executions,
+                    timed_out_executions,
+                    cached_executions,
+                    retried_executions,
+                    retry_attempts,
                     avg_duration_seconds,
                     min_duration_seconds,
                     max_duration_seconds,
                     last_executed: last_executed.map(|t| t as u64),
                     last_status,
+                    script_changed_since_success,
                 });
             }
 
@@ -722,7 +1092,7 @@ impl StateManager {
         self.db.with_connection(|conn| {
             let mut query = String::from(
                 "SELECT r.id, r.project_id, r.timestamp, r.status, r.duration_seconds,
-                        r.size_bytes, r.ottofile_path, r.cwd, r.user, r.hostname, r.args, r.ended_at
+                        r.size_bytes, r.ottofile_path, r.cwd, r.user, r.hostname, r.args, r.ended_at, r.resume_count
                  FROM runs r",
             );
 
@@ -786,7 +1156,7 @@ impl StateManager {
         self.db.with_connection(|conn| {
             let mut query = String::from(
                 "SELECT r.id, r.project_id, r.timestamp, r.status, r.duration_seconds,
-                        r.size_bytes, r.ottofile_path, r.cwd, r.user, r.hostname, r.args, r.ended_at
+                        r.size_bytes, r.ottofile_path, r.cwd, r.user, r.hostname, r.args, r.ended_at, r.resume_count
                  FROM runs r",
             );
 
@@ -838,7 +1208,7 @@ impl StateManager {
             let run: Option<RunRecord> = conn
                 .query_row(
                     "SELECT r.id, r.project_id, r.timestamp, r.status, r.duration_seconds,
-                            r.size_bytes, r.ottofile_path, r.cwd, r.user, r.hostname, r.args, r.ended_at
+                            r.size_bytes, r.ottofile_path, r.cwd, r.user, r.hostname, r.args, r.ended_at, r.resume_count
                      FROM runs r
                      WHERE r.timestamp = ?1",
                     params![timestamp as i64],
@@ -948,6 +1318,14 @@ impl StateStore for StateManager {
         StateManager::record_run_complete(self, timestamp, status, size_bytes)
     }
 
+    fn record_run_resumed(&self, run_id: i64) -> Result<()> {
+        StateManager::record_run_resumed(self, run_id)
+    }
+
+    fn reconcile_stale_runs(&self) -> Result<usize> {
+        StateManager::reconcile_stale_runs(self)
+    }
+
     fn record_task_start(
         &self,
         run_id: i64,
@@ -972,14 +1350,52 @@ impl StateStore for StateManager {
         StateManager::record_task_complete(self, task_id, exit_code, status)
     }
 
-    fn record_task_skipped(&self, run_id: i64, task_name: &str, script_hash: Option<&str>) -> Result<i64> {
-        StateManager::record_task_skipped(self, run_id, task_name, script_hash)
+    fn record_task_attempt(&self, task_id: i64, attempt: u32) -> Result<()> {
+        StateManager::record_task_attempt(self, task_id, attempt)
+    }
+
+    fn record_task_skipped(
+        &self,
+        run_id: i64,
+        task_name: &str,
+        script_hash: Option<&str>,
+        reason: &str,
+    ) -> Result<i64> {
+        StateManager::record_task_skipped(self, run_id, task_name, script_hash, reason)
+    }
+
+    fn record_task_cached(&self, run_id: i64, task_name: &str, script_hash: Option<&str>) -> Result<i64> {
+        StateManager::record_task_cached(self, run_id, task_name, script_hash)
+    }
+
+    fn record_task_dependency_failed(&self, run_id: i64, task_name: &str) -> Result<i64> {
+        StateManager::record_task_dependency_failed(self, run_id, task_name)
+    }
+
+    fn get_task_fingerprint(&self, run_id: i64, task_name: &str) -> Result<Option<String>> {
+        StateManager::get_task_fingerprint(self, run_id, task_name)
+    }
+
+    fn record_task_fingerprint(&self, run_id: i64, task_name: &str, fingerprint: &str) -> Result<()> {
+        StateManager::record_task_fingerprint(self, run_id, task_name, fingerprint)
+    }
+
+    fn record_task_annotations(&self, task_id: i64, annotations: &[Annotation]) -> Result<()> {
+        StateManager::record_task_annotations(self, task_id, annotations)
+    }
+
+    fn get_task_annotations(&self, task_id: i64) -> Result<Vec<Annotation>> {
+        StateManager::get_task_annotations(self, task_id)
     }
 
     fn get_recent_runs(&self, limit: usize, project_filter: Option<&str>) -> Result<Vec<RunRecord>> {
         StateManager::get_recent_runs(self, limit, project_filter)
     }
 
+    fn get_run(&self, run_id: i64) -> Result<Option<RunRecord>> {
+        StateManager::get_run(self, run_id)
+    }
+
     fn get_run_tasks(&self, run_id: i64) -> Result<Vec<TaskRecord>> {
         StateManager::get_run_tasks(self, run_id)
     }
@@ -1028,6 +1444,22 @@ impl StateStore for StateManager {
     }
 }
 
+/// Whether a process with this pid is still running on this machine
+fn process_is_alive(pid: u32) -> bool {
+    #[cfg(unix)]
+    {
+        // SAFETY: kill(2) with signal 0 only checks for existence and permission
+        let rc = unsafe { libc::kill(pid as libc::pid_t, 0) };
+        rc == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+    }
+    #[cfg(not(unix))]
+    {
+        // No cheap liveness check; never treat a run with a known owner as stale
+        let _ = pid;
+        true
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1237,6 +1669,68 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_record_task_timed_out() -> Result<()> {
+        let (manager, _temp_dir) = create_test_manager()?;
+
+        let metadata = RunMetadata::minimal(Some(PathBuf::from("/test/otto.yml")), "abc123".to_string(), 1234567890);
+        let run_id = manager.record_run_start(&metadata)?;
+
+        let task_id = manager.record_task_start(run_id, "test-task", None, None, None, None)?;
+        manager.record_task_complete(task_id, 124, TaskStatus::TimedOut)?;
+
+        let tasks = manager.get_run_tasks(run_id)?;
+        assert_eq!(tasks[0].status, TaskStatus::TimedOut);
+        assert_eq!(tasks[0].exit_code, Some(124));
+
+        let stats = manager.get_task_stats("test-task")?;
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].timed_out_executions, 1);
+        assert_eq!(stats[0].failed_executions, 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_reconcile_stale_runs() -> Result<()> {
+        let (manager, _temp_dir) = create_test_manager()?;
+
+        let live = manager.record_run_start(&RunMetadata::minimal(None, "abc123".to_string(), 1000))?;
+        let crashed = manager.record_run_start(&RunMetadata::minimal(None, "abc123".to_string(), 2000))?;
+        let legacy = manager.record_run_start(&RunMetadata::minimal(None, "abc123".to_string(), 3000))?;
+
+        let done = manager.record_task_start(crashed, "build", None, None, None, None)?;
+        manager.record_task_complete(done, 0, TaskStatus::Completed)?;
+        manager.record_task_start(crashed, "test", None, None, None, None)?;
+        let done = manager.record_task_start(legacy, "build", None, None, None, None)?;
+        manager.record_task_complete(done, 0, TaskStatus::Completed)?;
+
+        // A pid that cannot exist, and a run recorded before pids were tracked
+        manager.db.with_connection(|conn| {
+            conn.execute("UPDATE runs SET pid = ?1 WHERE id = ?2", params![i32::MAX, crashed])?;
+            conn.execute("UPDATE runs SET pid = NULL WHERE id = ?1", params![legacy])?;
+            Ok(())
+        })?;
+
+        assert_eq!(manager.reconcile_stale_runs()?, 2);
+        assert_eq!(manager.reconcile_stale_runs()?, 0);
+
+        let status_of = |run_id: i64| -> Result<RunStatus> {
+            let runs = manager.get_recent_runs(10, None)?;
+            Ok(runs.into_iter().find(|r| r.id == run_id).unwrap().status)
+        };
+        assert_eq!(status_of(live)?, RunStatus::Running);
+        assert_eq!(status_of(crashed)?, RunStatus::Cancelled);
+        assert_eq!(status_of(legacy)?, RunStatus::Success);
+
+        let tasks = manager.get_run_tasks(crashed)?;
+        let test_task = tasks.iter().find(|t| t.name == "test").unwrap();
+        assert_eq!(test_task.status, TaskStatus::Cancelled);
+        assert!(test_task.ended_at.is_some());
+
+        Ok(())
+    }
+
     #[test]
     fn test_record_task_skipped() -> Result<()> {
         let (manager, _temp_dir) = create_test_manager()?;
@@ -1244,7 +1738,7 @@ mod tests {
         let metadata = RunMetadata::minimal(Some(PathBuf::from("/test/otto.yml")), "abc123".to_string(), 1234567890);
         let run_id = manager.record_run_start(&metadata)?;
 
-        let task_id = manager.record_task_skipped(run_id, "test-task", Some("hash123"))?;
+        let task_id = manager.record_task_skipped(run_id, "test-task", Some("hash123"), "up to date")?;
         assert!(task_id > 0);
 
         let tasks = manager.get_run_tasks(run_id)?;
@@ -1252,7 +1746,128 @@ mod tests {
         assert_eq!(tasks[0].name, "test-task");
         assert_eq!(tasks[0].status, TaskStatus::Skipped);
         assert_eq!(tasks[0].script_hash, Some("hash123".to_string()));
+        assert_eq!(tasks[0].skip_reason, Some("up to date".to_string()));
+        assert!(tasks[0].started_at.is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_script_changed_since_success() -> Result<()> {
+        let (manager, _temp_dir) = create_test_manager()?;
+
+        let metadata = RunMetadata::minimal(Some(PathBuf::from("/test/otto.yml")), "abc123".to_string(), 1234567890);
+        let run_id = manager.record_run_start(&metadata)?;
 
+        let task_id = manager.record_task_start(run_id, "build", Some("aaaa1111"), None, None, None)?;
+        manager.record_task_complete(task_id, 0, TaskStatus::Completed)?;
+        manager.record_task_skipped(run_id, "build", Some("aaaa1111"), "up to date")?;
+        assert!(!manager.get_task_stats("build")?[0].script_changed_since_success);
+
+        let task_id = manager.record_task_start(run_id, "build", Some("bbbb2222"), None, None, None)?;
+        manager.record_task_complete(task_id, 1, TaskStatus::Failed)?;
+        assert!(manager.get_task_stats("build")?[0].script_changed_since_success);
+        assert!(manager.get_all_task_stats(None)?[0].script_changed_since_success);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_record_task_cached_counts_in_stats() -> Result<()> {
+        let (manager, _temp_dir) = create_test_manager()?;
+
+        let metadata = RunMetadata::minimal(Some(PathBuf::from("/test/otto.yml")), "abc123".to_string(), 1234567890);
+        let run_id = manager.record_run_start(&metadata)?;
+
+        let task_id = manager.record_task_start(run_id, "codegen", None, None, None, None)?;
+        manager.record_task_complete(task_id, 0, TaskStatus::Completed)?;
+        manager.record_task_cached(run_id, "codegen", None)?;
+
+        let tasks = manager.get_run_tasks(run_id)?;
+        assert!(tasks.iter().any(|t| t.status == TaskStatus::Cached));
+
+        let stats = manager.get_task_stats("codegen")?;
+        assert_eq!(stats[0].cached_executions, 1);
+        assert_eq!(stats[0].successful_executions, 1);
+
+        let all_stats = manager.get_all_task_stats(None)?;
+        assert_eq!(all_stats[0].cached_executions, 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_record_task_dependency_failed() -> Result<()> {
+        let (manager, _temp_dir) = create_test_manager()?;
+
+        let metadata = RunMetadata::minimal(Some(PathBuf::from("/test/otto.yml")), "abc123".to_string(), 1234567890);
+        let run_id = manager.record_run_start(&metadata)?;
+
+        manager.record_task_dependency_failed(run_id, "deploy")?;
+
+        let tasks = manager.get_run_tasks(run_id)?;
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].name, "deploy");
+        assert_eq!(tasks[0].status, TaskStatus::DependencyFailed);
+        assert_eq!(tasks[0].exit_code, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_task_fingerprint_shared_across_runs_of_project() -> Result<()> {
+        let (manager, _temp_dir) = create_test_manager()?;
+
+        let first = RunMetadata::minimal(Some(PathBuf::from("/test/otto.yml")), "abc123".to_string(), 1234567890);
+        let first_run = manager.record_run_start(&first)?;
+        assert_eq!(manager.get_task_fingerprint(first_run, "build")?, None);
+
+        manager.record_task_fingerprint(first_run, "build", "fp1")?;
+        manager.record_task_fingerprint(first_run, "build", "fp2")?;
+
+        let second = RunMetadata::minimal(Some(PathBuf::from("/test/otto.yml")), "abc123".to_string(), 1234567891);
+        let second_run = manager.record_run_start(&second)?;
+        assert_eq!(
+            manager.get_task_fingerprint(second_run, "build")?,
+            Some("fp2".to_string())
+        );
+
+        let other = RunMetadata::minimal(Some(PathBuf::from("/other/otto.yml")), "def456".to_string(), 1234567892);
+        let other_run = manager.record_run_start(&other)?;
+        assert_eq!(manager.get_task_fingerprint(other_run, "build")?, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_task_annotations_roundtrip() -> Result<()> {
+        let (manager, _temp_dir) = create_test_manager()?;
+
+        let metadata = RunMetadata::minimal(Some(PathBuf::from("/test/otto.yml")), "abc123".to_string(), 1234567890);
+        let run_id = manager.record_run_start(&metadata)?;
+        let task_id = manager.record_task_start(run_id, "lint", None, None, None, None)?;
+
+        let annotations = vec![
+            Annotation {
+                level: AnnotationLevel::Error,
+                message: "unused variable".to_string(),
+                file: Some("src/x.rs".to_string()),
+                line: Some(10),
+                col: None,
+                title: None,
+            },
+            Annotation {
+                level: AnnotationLevel::Warning,
+                message: "slow test".to_string(),
+                file: None,
+                line: None,
+                col: None,
+                title: Some("perf".to_string()),
+            },
+        ];
+        manager.record_task_annotations(task_id, &annotations)?;
+
+        assert_eq!(manager.get_task_annotations(task_id)?, annotations);
         Ok(())
     }
 
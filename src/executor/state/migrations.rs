@@ -2,7 +2,10 @@ use eyre::{Context, Result};
 use rusqlite::Connection;
 use std::time::SystemTime;
 
-use super::schema::{SCHEMA_VERSION, init_schema, migrate_v1_to_v2};
+use super::schema::{
+    SCHEMA_VERSION, init_schema, migrate_v1_to_v2, migrate_v2_to_v3, migrate_v3_to_v4, migrate_v4_to_v5,
+    migrate_v5_to_v6, migrate_v6_to_v7, migrate_v7_to_v8,
+};
 
 pub fn get_current_version(conn: &Connection) -> Result<i64> {
     let table_exists: bool = conn
@@ -60,7 +63,31 @@ pub fn migrate(conn: &Connection) -> Result<()> {
             migrate_v1_to_v2(conn).context("Failed to migrate from v1 to v2")?;
             set_version(conn, 2)?;
         }
-        // Future migrations will go here (v2 to v3, etc.)
+        if current_version < 3 {
+            migrate_v2_to_v3(conn).context("Failed to migrate from v2 to v3")?;
+            set_version(conn, 3)?;
+        }
+        if current_version < 4 {
+            migrate_v3_to_v4(conn).context("Failed to migrate from v3 to v4")?;
+            set_version(conn, 4)?;
+        }
+        if current_version < 5 {
+            migrate_v4_to_v5(conn).context("Failed to migrate from v4 to v5")?;
+            set_version(conn, 5)?;
+        }
+        if current_version < 6 {
+            migrate_v5_to_v6(conn).context("Failed to migrate from v5 to v6")?;
+            set_version(conn, 6)?;
+        }
+        if current_version < 7 {
+            migrate_v6_to_v7(conn).context("Failed to migrate from v6 to v7")?;
+            set_version(conn, 7)?;
+        }
+        if current_version < 8 {
+            migrate_v7_to_v8(conn).context("Failed to migrate from v7 to v8")?;
+            set_version(conn, 8)?;
+        }
+        // Future migrations will go here (v8 to v9, etc.)
     } else if current_version > SCHEMA_VERSION {
         return Err(eyre::eyre!(
             "Database schema version {} is newer than supported version {}. Please upgrade otto.",
@@ -124,6 +151,141 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_migrate_v2_to_v3_adds_attempt_column() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+
+        // Simulate a v2 database: tasks table without the attempt column
+        conn.execute(
+            "CREATE TABLE schema_version (version INTEGER PRIMARY KEY, applied_at INTEGER NOT NULL)",
+            [],
+        )?;
+        conn.execute("CREATE TABLE runs (id INTEGER PRIMARY KEY)", [])?;
+        conn.execute(
+            "CREATE TABLE tasks (id INTEGER PRIMARY KEY, run_id INTEGER NOT NULL, name TEXT NOT NULL, status TEXT NOT NULL)",
+            [],
+        )?;
+        conn.execute(
+            "INSERT INTO tasks (run_id, name, status) VALUES (1, 'build', 'completed')",
+            [],
+        )?;
+        set_version(&conn, 2)?;
+
+        migrate(&conn)?;
+
+        assert_eq!(get_current_version(&conn)?, SCHEMA_VERSION);
+        let attempt: i64 = conn.query_row("SELECT attempt FROM tasks WHERE name = 'build'", [], |row| row.get(0))?;
+        assert_eq!(attempt, 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_migrate_v3_to_v4_adds_fingerprints_table() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+
+        // Simulate a v3 database: current schema minus everything added since
+        init_schema(&conn)?;
+        conn.execute("DROP TABLE fingerprints", [])?;
+        conn.execute("ALTER TABLE tasks DROP COLUMN skip_reason", [])?;
+        conn.execute("ALTER TABLE runs DROP COLUMN pid", [])?;
+        conn.execute("ALTER TABLE runs DROP COLUMN resume_count", [])?;
+        set_version(&conn, 3)?;
+
+        migrate(&conn)?;
+
+        assert_eq!(get_current_version(&conn)?, SCHEMA_VERSION);
+        let mut stmt = conn.prepare("SELECT name FROM sqlite_master WHERE type='table' AND name='fingerprints'")?;
+        assert!(stmt.exists([])?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_migrate_v4_to_v5_adds_skip_reason_column() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+
+        // Simulate a v4 database: tasks table without the skip_reason column
+        conn.execute(
+            "CREATE TABLE schema_version (version INTEGER PRIMARY KEY, applied_at INTEGER NOT NULL)",
+            [],
+        )?;
+        conn.execute("CREATE TABLE runs (id INTEGER PRIMARY KEY)", [])?;
+        conn.execute(
+            "CREATE TABLE tasks (id INTEGER PRIMARY KEY, run_id INTEGER NOT NULL, name TEXT NOT NULL, status TEXT NOT NULL)",
+            [],
+        )?;
+        conn.execute(
+            "INSERT INTO tasks (run_id, name, status) VALUES (1, 'build', 'skipped')",
+            [],
+        )?;
+        set_version(&conn, 4)?;
+
+        migrate(&conn)?;
+
+        assert_eq!(get_current_version(&conn)?, SCHEMA_VERSION);
+        let reason: Option<String> =
+            conn.query_row("SELECT skip_reason FROM tasks WHERE name = 'build'", [], |row| {
+                row.get(0)
+            })?;
+        assert_eq!(reason, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_migrate_v5_to_v6_adds_run_pid_column() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+
+        init_schema(&conn)?;
+        conn.execute("ALTER TABLE runs DROP COLUMN pid", [])?;
+        conn.execute("ALTER TABLE runs DROP COLUMN resume_count", [])?;
+        set_version(&conn, 5)?;
+
+        migrate(&conn)?;
+
+        assert_eq!(get_current_version(&conn)?, SCHEMA_VERSION);
+        let mut stmt = conn.prepare("SELECT pid FROM runs")?;
+        assert!(!stmt.exists([])?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_migrate_v6_to_v7_adds_annotations_table() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+
+        init_schema(&conn)?;
+        conn.execute("DROP TABLE annotations", [])?;
+        conn.execute("ALTER TABLE runs DROP COLUMN resume_count", [])?;
+        set_version(&conn, 6)?;
+
+        migrate(&conn)?;
+
+        assert_eq!(get_current_version(&conn)?, SCHEMA_VERSION);
+        let mut stmt = conn.prepare("SELECT name FROM sqlite_master WHERE type='table' AND name='annotations'")?;
+        assert!(stmt.exists([])?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_migrate_v7_to_v8_adds_resume_count_column() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+
+        init_schema(&conn)?;
+        conn.execute("ALTER TABLE runs DROP COLUMN resume_count", [])?;
+        set_version(&conn, 7)?;
+
+        migrate(&conn)?;
+
+        assert_eq!(get_current_version(&conn)?, SCHEMA_VERSION);
+        let mut stmt = conn.prepare("SELECT resume_count FROM runs")?;
+        assert!(!stmt.exists([])?);
+
+        Ok(())
+    }
+
     #[test]
     fn test_set_version() -> Result<()> {
         let conn = Connection::open_in_memory()?;
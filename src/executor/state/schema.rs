@@ -2,7 +2,7 @@ use eyre::Result;
 use rusqlite::Connection;
 
 /// SQL schema for the otto database
-pub const SCHEMA_VERSION: i64 = 2;
+pub const SCHEMA_VERSION: i64 = 8;
 
 /// Status of a run
 #[derive(Debug, Clone, PartialEq, serde::Serialize)]
@@ -10,6 +10,7 @@ pub enum RunStatus {
     Running,
     Success,
     Failed,
+    Cancelled,
 }
 
 impl RunStatus {
@@ -18,6 +19,7 @@ impl RunStatus {
             RunStatus::Running => "running",
             RunStatus::Success => "success",
             RunStatus::Failed => "failed",
+            RunStatus::Cancelled => "cancelled",
         }
     }
 
@@ -26,6 +28,7 @@ impl RunStatus {
             "running" => Some(RunStatus::Running),
             "success" => Some(RunStatus::Success),
             "failed" => Some(RunStatus::Failed),
+            "cancelled" => Some(RunStatus::Cancelled),
             _ => None,
         }
     }
@@ -38,7 +41,13 @@ pub enum TaskStatus {
     Running,
     Completed,
     Failed,
+    /// Failed, but the task has `allow_failure: true`
+    FailedAllowed,
     Skipped,
+    Cached,
+    TimedOut,
+    DependencyFailed,
+    Cancelled,
 }
 
 impl TaskStatus {
@@ -48,7 +57,12 @@ impl TaskStatus {
             TaskStatus::Running => "running",
             TaskStatus::Completed => "completed",
             TaskStatus::Failed => "failed",
+            TaskStatus::FailedAllowed => "failed_allowed",
             TaskStatus::Skipped => "skipped",
+            TaskStatus::Cached => "cached",
+            TaskStatus::TimedOut => "timed_out",
+            TaskStatus::DependencyFailed => "dependency_failed",
+            TaskStatus::Cancelled => "cancelled",
         }
     }
 
@@ -58,7 +72,12 @@ impl TaskStatus {
             "running" => Some(TaskStatus::Running),
             "completed" => Some(TaskStatus::Completed),
             "failed" => Some(TaskStatus::Failed),
+            "failed_allowed" => Some(TaskStatus::FailedAllowed),
             "skipped" => Some(TaskStatus::Skipped),
+            "cached" => Some(TaskStatus::Cached),
+            "timed_out" => Some(TaskStatus::TimedOut),
+            "dependency_failed" => Some(TaskStatus::DependencyFailed),
+            "cancelled" => Some(TaskStatus::Cancelled),
             _ => None,
         }
     }
@@ -104,6 +123,8 @@ pub fn init_schema(conn: &Connection) -> Result<()> {
             hostname TEXT,
             args TEXT,
             ended_at INTEGER,
+            pid INTEGER,
+            resume_count INTEGER NOT NULL DEFAULT 0,
             FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
         )",
         [],
@@ -129,6 +150,8 @@ pub fn init_schema(conn: &Connection) -> Result<()> {
             stdout_path TEXT,
             stderr_path TEXT,
             script_path TEXT,
+            attempt INTEGER NOT NULL DEFAULT 1,
+            skip_reason TEXT,
             FOREIGN KEY (run_id) REFERENCES runs(id) ON DELETE CASCADE
         )",
         [],
@@ -146,6 +169,50 @@ pub fn init_schema(conn: &Connection) -> Result<()> {
     // Projects indexes
     conn.execute("CREATE INDEX IF NOT EXISTS idx_projects_name ON projects(name)", [])?;
 
+    create_fingerprints_table(conn)?;
+    create_annotations_table(conn)?;
+
+    Ok(())
+}
+
+/// Content fingerprints of the last successful run of each task, per project
+fn create_fingerprints_table(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS fingerprints (
+            project_id INTEGER NOT NULL,
+            task_name TEXT NOT NULL,
+            fingerprint TEXT NOT NULL,
+            updated_at INTEGER NOT NULL,
+            PRIMARY KEY (project_id, task_name),
+            FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Errors, warnings and notices tasks reported with `::error` etc.
+fn create_annotations_table(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS annotations (
+            id INTEGER PRIMARY KEY,
+            task_id INTEGER NOT NULL,
+            level TEXT NOT NULL,
+            message TEXT NOT NULL,
+            file TEXT,
+            line INTEGER,
+            col INTEGER,
+            title TEXT,
+            FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_annotations_task ON annotations(task_id)",
+        [],
+    )?;
+
     Ok(())
 }
 
@@ -189,6 +256,53 @@ pub fn migrate_v1_to_v2(conn: &Connection) -> Result<()> {
     Ok(())
 }
 
+/// Migrate from schema version 2 to 3
+/// Adds 'attempt' column to tasks table so retried tasks get one row per attempt
+pub fn migrate_v2_to_v3(conn: &Connection) -> Result<()> {
+    conn.execute("ALTER TABLE tasks ADD COLUMN attempt INTEGER NOT NULL DEFAULT 1", [])?;
+
+    Ok(())
+}
+
+/// Migrate from schema version 3 to 4
+/// Adds 'fingerprints' table used by `fingerprint: content` up-to-date checks
+pub fn migrate_v3_to_v4(conn: &Connection) -> Result<()> {
+    create_fingerprints_table(conn)
+}
+
+/// Migrate from schema version 4 to 5
+/// Adds 'skip_reason' column to tasks table so skipped tasks record why they were skipped
+pub fn migrate_v4_to_v5(conn: &Connection) -> Result<()> {
+    conn.execute("ALTER TABLE tasks ADD COLUMN skip_reason TEXT", [])?;
+
+    Ok(())
+}
+
+/// Migrate from schema version 5 to 6
+/// Adds 'pid' column to runs table so runs left 'running' by a crashed process can be detected
+pub fn migrate_v5_to_v6(conn: &Connection) -> Result<()> {
+    conn.execute("ALTER TABLE runs ADD COLUMN pid INTEGER", [])?;
+
+    Ok(())
+}
+
+/// Migrate from schema version 6 to 7
+/// Adds 'annotations' table for the errors and warnings tasks report on stdout
+pub fn migrate_v6_to_v7(conn: &Connection) -> Result<()> {
+    create_annotations_table(conn)
+}
+
+/// Migrate from schema version 7 to 8
+/// Adds 'resume_count' column to runs table for runs continued with `otto --resume`
+pub fn migrate_v7_to_v8(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "ALTER TABLE runs ADD COLUMN resume_count INTEGER NOT NULL DEFAULT 0",
+        [],
+    )?;
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -223,10 +337,12 @@ mod tests {
         assert_eq!(RunStatus::Running.as_str(), "running");
         assert_eq!(RunStatus::Success.as_str(), "success");
         assert_eq!(RunStatus::Failed.as_str(), "failed");
+        assert_eq!(RunStatus::Cancelled.as_str(), "cancelled");
 
         assert_eq!(RunStatus::parse("running"), Some(RunStatus::Running));
         assert_eq!(RunStatus::parse("success"), Some(RunStatus::Success));
         assert_eq!(RunStatus::parse("failed"), Some(RunStatus::Failed));
+        assert_eq!(RunStatus::parse("cancelled"), Some(RunStatus::Cancelled));
         assert_eq!(RunStatus::parse("invalid"), None);
     }
 
@@ -237,12 +353,23 @@ mod tests {
         assert_eq!(TaskStatus::Completed.as_str(), "completed");
         assert_eq!(TaskStatus::Failed.as_str(), "failed");
         assert_eq!(TaskStatus::Skipped.as_str(), "skipped");
+        assert_eq!(TaskStatus::TimedOut.as_str(), "timed_out");
+        assert_eq!(TaskStatus::FailedAllowed.as_str(), "failed_allowed");
+        assert_eq!(TaskStatus::DependencyFailed.as_str(), "dependency_failed");
+        assert_eq!(TaskStatus::Cancelled.as_str(), "cancelled");
 
         assert_eq!(TaskStatus::parse("pending"), Some(TaskStatus::Pending));
         assert_eq!(TaskStatus::parse("running"), Some(TaskStatus::Running));
         assert_eq!(TaskStatus::parse("completed"), Some(TaskStatus::Completed));
         assert_eq!(TaskStatus::parse("failed"), Some(TaskStatus::Failed));
         assert_eq!(TaskStatus::parse("skipped"), Some(TaskStatus::Skipped));
+        assert_eq!(TaskStatus::parse("timed_out"), Some(TaskStatus::TimedOut));
+        assert_eq!(TaskStatus::parse("failed_allowed"), Some(TaskStatus::FailedAllowed));
+        assert_eq!(
+            TaskStatus::parse("dependency_failed"),
+            Some(TaskStatus::DependencyFailed)
+        );
+        assert_eq!(TaskStatus::parse("cancelled"), Some(TaskStatus::Cancelled));
         assert_eq!(TaskStatus::parse("invalid"), None);
     }
 }
@@ -1,13 +1,14 @@
 use daggy::Dag;
-use eyre::Result;
+use eyre::{Result, eyre};
 use hex;
 use sha2::{Digest, Sha256};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
+use std::time::Duration;
 
 use crate::cfg::config::Value;
 use crate::cfg::env as env_eval;
-use crate::cfg::task::TaskSpec;
+use crate::cfg::task::{Fingerprint, ReadySpec, RetrySpec, TaskSpec, ValueType};
 
 pub type DAG<T> = Dag<T, (), u32>;
 
@@ -23,6 +24,42 @@ pub struct Task {
     pub values: HashMap<String, Value>,
     pub action: String,
     pub hash: String,
+    /// Maximum wall-clock time before the task's process group is terminated
+    pub timeout: Option<Duration>,
+    /// Time between SIGTERM and SIGKILL once the timeout expires
+    pub timeout_grace: Option<Duration>,
+    /// Retry policy applied when the task fails
+    pub retry: Option<RetrySpec>,
+    /// Up-to-date check strategy
+    pub fingerprint: Fingerprint,
+    /// Runs once its dependencies finish, whether they succeeded or not
+    pub finally: bool,
+    /// Tasks whose failure triggers this `on_failure` handler; it doesn't run otherwise
+    pub handles_failure_of: Vec<String>,
+    /// Background process that keeps running while its dependents do
+    pub service: bool,
+    /// Probe deciding when a service is ready for its dependents
+    pub ready: Option<ReadySpec>,
+    /// Working directory of the action (default: the workspace root)
+    pub dir: Option<PathBuf>,
+    /// Run with an empty environment apart from `env_passthrough` and the task's envs
+    pub env_clear: bool,
+    /// Variables inherited from otto's environment despite `env_clear`
+    pub env_passthrough: Vec<String>,
+    /// Directories put in front of PATH
+    pub path_prepend: Vec<PathBuf>,
+    /// Command registered in `otto.interpreters` for the script's shebang
+    pub interpreter: Option<String>,
+    /// A failing exit code is a warning that still unblocks dependents
+    pub allow_failure: bool,
+    /// Exit codes that count as success; empty means only 0
+    pub success_codes: Vec<i32>,
+    /// Runs attached to the terminal, with no other task running alongside
+    pub interactive: bool,
+    /// Runs with its stdout and stderr connected to pseudo-terminals
+    pub pty: bool,
+    /// Declared types of the structured output keys
+    pub outputs: BTreeMap<String, ValueType>,
 }
 
 impl Task {
@@ -49,9 +86,55 @@ impl Task {
             values,
             action,
             hash,
+            timeout: None,
+            timeout_grace: None,
+            retry: None,
+            fingerprint: Fingerprint::default(),
+            finally: false,
+            handles_failure_of: Vec::new(),
+            service: false,
+            ready: None,
+            dir: None,
+            env_clear: false,
+            env_passthrough: Vec::new(),
+            path_prepend: Vec::new(),
+            interpreter: None,
+            allow_failure: false,
+            success_codes: Vec::new(),
+            interactive: false,
+            pty: false,
+            outputs: BTreeMap::new(),
         }
     }
 
+    /// Whether the task's process exiting with `code` counts as success
+    #[must_use]
+    pub fn is_success_code(&self, code: i32) -> bool {
+        if self.success_codes.is_empty() {
+            code == 0
+        } else {
+            self.success_codes.contains(&code)
+        }
+    }
+
+    /// Check the task's structured output against the types declared under `outputs:`
+    pub fn validate_outputs(&self, output: &serde_json::Value) -> Result<()> {
+        for (key, expected) in &self.outputs {
+            match output.get(key) {
+                None => return Err(eyre!("Task {} did not set its declared output {key}", self.name)),
+                Some(value) if !expected.matches(value) => {
+                    return Err(eyre!(
+                        "Task {} output {key} should be of type {expected}, got {}",
+                        self.name,
+                        ValueType::describe(value)
+                    ));
+                }
+                Some(_) => {}
+            }
+        }
+        Ok(())
+    }
+
     #[must_use]
     pub fn from_task(task_spec: &TaskSpec) -> Self {
         let _name = task_spec.name.clone();
@@ -83,22 +166,25 @@ impl Task {
             None
         };
 
-        // Resolve file globs from input to canonical paths using explicit cwd
-        let file_deps = Self::resolve_file_globs(&task_spec.input, cwd);
+        let working_dir = task_spec.working_dir(cwd);
 
-        // Resolve output globs to canonical paths using explicit cwd
-        let output_deps = Self::resolve_file_globs(&task_spec.output, cwd);
+        // Resolve file globs from input to canonical paths relative to the task's dir
+        let file_deps = Self::resolve_file_globs(&task_spec.input, &working_dir);
 
-        let evaluated_envs = Self::evaluate_merged_envs(global_envs, &task_spec.envs, cwd).unwrap_or_else(|e| {
-            eprintln!("Warning: Failed to evaluate environment variables for task '{name}': {e}");
-            HashMap::new()
-        });
+        // Resolve output globs to canonical paths relative to the task's dir
+        let output_deps = Self::resolve_file_globs(&task_spec.output, &working_dir);
+
+        let evaluated_envs =
+            Self::evaluate_merged_envs(global_envs, &task_spec.envs, &working_dir).unwrap_or_else(|e| {
+                eprintln!("Warning: Failed to evaluate environment variables for task '{name}': {e}");
+                HashMap::new()
+            });
 
         // Note: We do NOT add after tasks here since they depend on us, not vice versa
         // The after dependencies will be handled during DAG construction
         let values = HashMap::new();
         let action = task_spec.action.trim().to_string(); // Trim whitespace from script content
-        Self::new(
+        let mut task = Self::new(
             name,
             parent,
             task_deps,
@@ -107,7 +193,24 @@ impl Task {
             evaluated_envs,
             values,
             action,
-        )
+        );
+        task.timeout = task_spec.timeout;
+        task.timeout_grace = task_spec.timeout_grace;
+        task.retry = task_spec.retry.clone();
+        task.fingerprint = task_spec.fingerprint;
+        task.finally = task_spec.finally;
+        task.service = task_spec.service;
+        task.ready = task_spec.ready.clone();
+        task.path_prepend = task_spec.resolved_path_prepend(&working_dir);
+        task.dir = task_spec.dir.is_some().then_some(working_dir);
+        task.env_clear = task_spec.env_clear;
+        task.env_passthrough = task_spec.env_passthrough.clone();
+        task.allow_failure = task_spec.allow_failure;
+        task.success_codes = task_spec.success_codes.clone();
+        task.interactive = task_spec.interactive;
+        task.pty = task_spec.pty;
+        task.outputs = task_spec.outputs.clone();
+        task
     }
 
     /// Evaluate and merge environment variables from global and task-level sources
@@ -179,6 +282,46 @@ impl Task {
     }
 }
 
+impl From<crate::cli::parser::Task> for Task {
+    fn from(parser_task: crate::cli::parser::Task) -> Self {
+        // Derive parent for subtasks (names with colons like "install:td")
+        let parent = if parser_task.name.contains(':') {
+            parser_task.name.split(':').next().map(|s| s.to_string())
+        } else {
+            None
+        };
+        let mut task = Self::new(
+            parser_task.name,
+            parent,
+            parser_task.task_deps,
+            parser_task.file_deps,
+            parser_task.output_deps,
+            parser_task.envs,
+            parser_task.values,
+            parser_task.action,
+        );
+        task.timeout = parser_task.timeout;
+        task.timeout_grace = parser_task.timeout_grace;
+        task.retry = parser_task.retry;
+        task.fingerprint = parser_task.fingerprint;
+        task.finally = parser_task.finally;
+        task.handles_failure_of = parser_task.handles_failure_of;
+        task.service = parser_task.service;
+        task.ready = parser_task.ready;
+        task.dir = parser_task.dir;
+        task.env_clear = parser_task.env_clear;
+        task.env_passthrough = parser_task.env_passthrough;
+        task.path_prepend = parser_task.path_prepend;
+        task.interpreter = parser_task.interpreter;
+        task.allow_failure = parser_task.allow_failure;
+        task.success_codes = parser_task.success_codes;
+        task.interactive = parser_task.interactive;
+        task.pty = parser_task.pty;
+        task.outputs = parser_task.outputs;
+        task
+    }
+}
+
 fn calculate_hash(action: &String) -> String {
     let mut hasher = Sha256::new();
     hasher.update(action.as_bytes());
@@ -205,6 +348,7 @@ mod tests {
             action: action.to_string(),
             foreach: None,
             virtual_parent: false,
+            ..Default::default()
         }
     }
 
@@ -468,4 +612,27 @@ mod tests {
 
         assert_eq!(task.parent, Some("group".to_string()));
     }
+
+    #[test]
+    fn test_validate_outputs() {
+        let mut task = Task::from_task(&make_task_spec("build", vec![], "echo build"));
+        task.outputs.insert("files".to_string(), ValueType::List);
+        task.outputs.insert("count".to_string(), ValueType::Integer);
+
+        assert!(
+            task.validate_outputs(&serde_json::json!({"files": ["a"], "count": 2, "extra": "x"}))
+                .is_ok()
+        );
+
+        let err = task
+            .validate_outputs(&serde_json::json!({"files": "a", "count": 2}))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Task build output files should be of type list, got a string"
+        );
+
+        let err = task.validate_outputs(&serde_json::json!({"files": []})).unwrap_err();
+        assert_eq!(err.to_string(), "Task build did not set its declared output count");
+    }
 }
@@ -0,0 +1,184 @@
+//! Chrome trace-event export of a run (`otto History --run <id> --trace out.json`)
+//!
+//! Each task becomes a complete ("X") event on a lane standing for the job
+//! slot it ran in, so `chrome://tracing` or Perfetto shows how many tasks ran
+//! at once and where the run went serial. Times come from the `started_at` and
+//! `ended_at` columns of the `tasks` table, which have one-second resolution.
+//! Task params come from the run's snapshot, when it still has one.
+
+use serde_json::{Value, json};
+
+use super::snapshot::RunSnapshot;
+use super::state::{RunRecord, TaskRecord};
+
+/// Microseconds per second; trace timestamps are in microseconds
+const MICROS: u64 = 1_000_000;
+
+/// Build a trace-event JSON document for a run and its tasks
+pub fn chrome_trace(run: &RunRecord, tasks: &[TaskRecord], snapshot: Option<&RunSnapshot>) -> Value {
+    let mut tasks: Vec<&TaskRecord> = tasks.iter().filter(|task| task.started_at.is_some()).collect();
+    tasks.sort_by_key(|task| (task.started_at, task.id));
+
+    let mut events = vec![json!({
+        "name": "process_name",
+        "ph": "M",
+        "pid": 1,
+        "args": {"name": format!("otto run {}", run.id)},
+    })];
+
+    // End time of the last task in each lane; a task takes the first lane that is free
+    let mut lanes: Vec<u64> = Vec::new();
+    for task in tasks {
+        let start = task.started_at.unwrap_or(run.timestamp);
+        let end = task.ended_at.or(run.ended_at).unwrap_or(start).max(start);
+        let lane = match lanes.iter().position(|lane_end| *lane_end <= start) {
+            Some(lane) => lane,
+            None => {
+                lanes.push(0);
+                events.push(json!({
+                    "name": "thread_name",
+                    "ph": "M",
+                    "pid": 1,
+                    "tid": lanes.len(),
+                    "args": {"name": format!("slot {}", lanes.len())},
+                }));
+                lanes.len() - 1
+            }
+        };
+        lanes[lane] = end;
+        let params = snapshot
+            .and_then(|snapshot| snapshot.tasks.get(&task.name))
+            .map(|task| &task.params);
+
+        events.push(json!({
+            "name": task.name,
+            "cat": task.status.as_str(),
+            "ph": "X",
+            "ts": start.saturating_sub(run.timestamp) * MICROS,
+            "dur": (end - start) * MICROS,
+            "pid": 1,
+            "tid": lane + 1,
+            "args": {
+                "status": task.status.as_str(),
+                "exit_code": task.exit_code,
+                "script_hash": task.script_hash,
+                "attempt": task.attempt,
+                "skip_reason": task.skip_reason,
+                "params": params,
+            },
+        }));
+    }
+
+    json!({
+        "traceEvents": events,
+        "displayTimeUnit": "ms",
+        "otherData": {
+            "run_id": run.id,
+            "status": run.status.as_str(),
+            "args": run.args,
+            "cwd": run.cwd,
+            "user": run.user,
+            "hostname": run.hostname,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::snapshot::TaskSnapshot;
+    use crate::executor::state::{RunStatus, TaskStatus};
+    use std::collections::BTreeMap;
+
+    fn task(id: i64, name: &str, started_at: u64, ended_at: u64) -> TaskRecord {
+        TaskRecord {
+            id,
+            run_id: 1,
+            name: name.to_string(),
+            status: TaskStatus::Completed,
+            script_hash: Some("abc".to_string()),
+            exit_code: Some(0),
+            started_at: Some(started_at),
+            ended_at: Some(ended_at),
+            duration_seconds: Some((ended_at - started_at) as f64),
+            stdout_path: None,
+            stderr_path: None,
+            script_path: None,
+            attempt: 1,
+            skip_reason: None,
+        }
+    }
+
+    #[test]
+    fn test_tasks_are_packed_into_lanes() {
+        let run = RunRecord {
+            id: 1,
+            project_id: 1,
+            timestamp: 100,
+            status: RunStatus::Success,
+            duration_seconds: Some(10.0),
+            size_bytes: None,
+            ottofile_path: None,
+            cwd: None,
+            user: None,
+            hostname: None,
+            args: Some(vec!["otto".to_string(), "ci".to_string()]),
+            ended_at: Some(110),
+            resume_count: 0,
+        };
+        let tasks = [
+            task(1, "lint", 100, 103),
+            task(2, "build", 100, 105),
+            task(3, "test", 103, 110),
+            task(4, "package", 105, 106),
+        ];
+
+        let snapshot = RunSnapshot {
+            tasks: BTreeMap::from([(
+                "build".to_string(),
+                TaskSnapshot {
+                    params: BTreeMap::from([("profile".to_string(), "release".to_string())]),
+                    env: BTreeMap::new(),
+                },
+            )]),
+        };
+
+        let trace = chrome_trace(&run, &tasks, Some(&snapshot));
+        let slices: Vec<(&str, u64, u64, u64)> = trace["traceEvents"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|event| event["ph"] == "X")
+            .map(|event| {
+                (
+                    event["name"].as_str().unwrap(),
+                    event["tid"].as_u64().unwrap(),
+                    event["ts"].as_u64().unwrap(),
+                    event["dur"].as_u64().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            slices,
+            vec![
+                ("lint", 1, 0, 3_000_000),
+                ("build", 2, 0, 5_000_000),
+                ("test", 1, 3_000_000, 7_000_000),
+                ("package", 2, 5_000_000, 1_000_000),
+            ]
+        );
+        assert_eq!(trace["otherData"]["args"], json!(["otto", "ci"]));
+
+        let args = |name: &str| {
+            trace["traceEvents"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|event| event["name"] == name)
+                .unwrap()["args"]
+                .clone()
+        };
+        assert_eq!(args("build")["params"], json!({"profile": "release"}));
+        assert_eq!(args("lint")["params"], Value::Null);
+    }
+}
@@ -0,0 +1,229 @@
+//! Workflow commands: special stdout lines scripts use to talk to otto
+//!
+//! Modelled on GitHub Actions, so any language can pass data and report
+//! problems without otto's builtins:
+//!
+//! ```text
+//! ::set-output version=1.2.3                  # or ::set-output name=version::1.2.3
+//! ::error file=src/x.rs,line=10::message      # also ::warning and ::notice
+//! ::group::Compiling                          # start a collapsible section
+//! ::endgroup::
+//! ::progress 42/100 migrating users           # see the progress module
+//! ```
+//!
+//! Values use GitHub's escaping: `%25` for `%`, `%0A`/`%0D` for newlines, and
+//! in properties also `%3A` for `:` and `%2C` for `,`.
+
+use std::fmt;
+
+use colored::Colorize;
+use serde::Serialize;
+
+use super::progress::Progress;
+
+/// Severity of an annotation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum AnnotationLevel {
+    Error,
+    Warning,
+    Notice,
+}
+
+impl AnnotationLevel {
+    pub fn as_str(&self) -> &str {
+        match self {
+            AnnotationLevel::Error => "error",
+            AnnotationLevel::Warning => "warning",
+            AnnotationLevel::Notice => "notice",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "error" => Some(AnnotationLevel::Error),
+            "warning" => Some(AnnotationLevel::Warning),
+            "notice" => Some(AnnotationLevel::Notice),
+            _ => None,
+        }
+    }
+}
+
+/// A problem reported by a task with `::error`, `::warning` or `::notice`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Annotation {
+    pub level: AnnotationLevel,
+    pub message: String,
+    pub file: Option<String>,
+    pub line: Option<u32>,
+    pub col: Option<u32>,
+    pub title: Option<String>,
+}
+
+impl Annotation {
+    /// `file:line:col` of the annotation, if it names a file
+    pub fn location(&self) -> Option<String> {
+        let file = self.file.as_ref()?;
+        Some(match (self.line, self.col) {
+            (Some(line), Some(col)) => format!("{file}:{line}:{col}"),
+            (Some(line), None) => format!("{file}:{line}"),
+            _ => file.clone(),
+        })
+    }
+}
+
+impl fmt::Display for Annotation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let level = match self.level {
+            AnnotationLevel::Error => "error".red().bold(),
+            AnnotationLevel::Warning => "warning".yellow().bold(),
+            AnnotationLevel::Notice => "notice".cyan().bold(),
+        };
+        write!(f, "{level}: ")?;
+        if let Some(location) = self.location() {
+            write!(f, "{}: ", location.bold())?;
+        }
+        if let Some(title) = &self.title {
+            write!(f, "{title}: ")?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+/// A command parsed from a line of task output
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WorkflowCommand {
+    /// Add a key to the task's structured output
+    SetOutput { key: String, value: String },
+    /// Report an error, warning or notice
+    Annotate(Annotation),
+    /// Start a collapsible group of output lines
+    Group(String),
+    /// End the current group
+    EndGroup,
+    /// Report how far along the task is
+    Progress(Progress),
+}
+
+impl WorkflowCommand {
+    /// What to show on the terminal instead of the raw command line, if anything
+    pub fn terminal_line(&self) -> Option<String> {
+        match self {
+            WorkflowCommand::SetOutput { .. } | WorkflowCommand::EndGroup | WorkflowCommand::Progress(_) => None,
+            WorkflowCommand::Annotate(annotation) => Some(annotation.to_string()),
+            WorkflowCommand::Group(name) => Some(format!("▸ {}", name.bold())),
+        }
+    }
+}
+
+/// Parse a line of output as a workflow command. Lines that merely start with
+/// `::` but aren't a known command are left alone.
+pub fn parse(line: &str) -> Option<WorkflowCommand> {
+    let rest = line.trim_end_matches(['\r', '\n']).strip_prefix("::")?;
+
+    // ::name props::data, where props is optional
+    let (head, data) = match rest.find("::") {
+        Some(pos) => (&rest[..pos], Some(&rest[pos + 2..])),
+        None => (rest, None),
+    };
+    let (name, props) = match head.split_once(' ') {
+        Some((name, props)) => (name, props.trim()),
+        None => (head, ""),
+    };
+
+    match name {
+        "set-output" => {
+            // GitHub's `::set-output name=key::value`, or the shorter `::set-output key=value`
+            let (key, value) = match data {
+                Some(value) => (property(props, "name")?, unescape_data(value)),
+                None => {
+                    let (key, value) = props.split_once('=')?;
+                    (key.trim().to_string(), unescape_data(value))
+                }
+            };
+            (!key.is_empty()).then_some(WorkflowCommand::SetOutput { key, value })
+        }
+        "error" | "warning" | "notice" => Some(WorkflowCommand::Annotate(Annotation {
+            level: AnnotationLevel::parse(name)?,
+            message: unescape_data(data.unwrap_or_default()),
+            file: property(props, "file"),
+            line: property(props, "line").and_then(|line| line.parse().ok()),
+            col: property(props, "col").and_then(|col| col.parse().ok()),
+            title: property(props, "title"),
+        })),
+        "group" => Some(WorkflowCommand::Group(unescape_data(data.unwrap_or_default()))),
+        "endgroup" => Some(WorkflowCommand::EndGroup),
+        "progress" => Progress::parse(data.unwrap_or(props)).map(WorkflowCommand::Progress),
+        _ => None,
+    }
+}
+
+/// Value of `key` in a `key=value,key=value` property list
+fn property(props: &str, key: &str) -> Option<String> {
+    props
+        .split(',')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(name, _)| name.trim() == key)
+        .map(|(_, value)| unescape_property(value.trim()))
+}
+
+fn unescape_data(value: &str) -> String {
+    value.replace("%0D", "\r").replace("%0A", "\n").replace("%25", "%")
+}
+
+fn unescape_property(value: &str) -> String {
+    value
+        .replace("%0D", "\r")
+        .replace("%0A", "\n")
+        .replace("%3A", ":")
+        .replace("%2C", ",")
+        .replace("%25", "%")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_set_output() {
+        let expected = Some(WorkflowCommand::SetOutput {
+            key: "version".to_string(),
+            value: "1.2=3".to_string(),
+        });
+        assert_eq!(parse("::set-output version=1.2=3\n"), expected);
+        assert_eq!(parse("::set-output name=version::1.2=3"), expected);
+        assert_eq!(parse("::set-output =x"), None);
+    }
+
+    #[test]
+    fn test_parse_annotation() {
+        let Some(WorkflowCommand::Annotate(annotation)) = parse("::error file=src/x.rs,line=10,col=4::bad%0Athing")
+        else {
+            panic!("expected an annotation");
+        };
+        assert_eq!(annotation.level, AnnotationLevel::Error);
+        assert_eq!(annotation.message, "bad\nthing");
+        assert_eq!(annotation.location().as_deref(), Some("src/x.rs:10:4"));
+
+        let Some(WorkflowCommand::Annotate(annotation)) = parse("::warning::deprecated flag") else {
+            panic!("expected an annotation");
+        };
+        assert_eq!(annotation.level, AnnotationLevel::Warning);
+        assert_eq!(annotation.location(), None);
+    }
+
+    #[test]
+    fn test_parse_groups_and_other_lines() {
+        assert_eq!(
+            parse("::group::Build step"),
+            Some(WorkflowCommand::Group("Build step".to_string()))
+        );
+        assert_eq!(parse("::endgroup::"), Some(WorkflowCommand::EndGroup));
+        assert!(matches!(
+            parse("::progress 3/4 almost"),
+            Some(WorkflowCommand::Progress(_))
+        ));
+        assert_eq!(parse("::debug::not ours"), None);
+        assert_eq!(parse("std::env::var"), None);
+        assert_eq!(parse("plain output"), None);
+    }
+}
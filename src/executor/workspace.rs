@@ -8,6 +8,7 @@ use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::SystemTime;
 
+use super::snapshot::{RunSnapshot, SNAPSHOT_FILE};
 use super::state::{RunMetadata, StateManager};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -154,6 +155,15 @@ impl<F: FileSystem> Workspace<F> {
         })
     }
 
+    /// Reattach to the directory and database row of an interrupted run (`otto --resume`)
+    /// instead of starting a new one
+    pub fn resuming(mut self, run_id: i64, timestamp: u64) -> Self {
+        self.time = timestamp;
+        self.run = self.project.join(timestamp.to_string());
+        self.db_run_id = std::sync::Mutex::new(Some(run_id));
+        self
+    }
+
     /// Set a custom state store (for testing with MemoryStateStore)
     pub fn with_state_store(mut self, store: Arc<dyn StateStore>) -> Self {
         self.state_store = Some(store);
@@ -320,6 +330,21 @@ impl<F: FileSystem> Workspace<F> {
 
     fn record_run_start_in_db(&self, context: &ExecutionContext) {
         if let Some(ref store) = self.state_store {
+            // Finalize runs a crashed otto left behind before recording this one
+            match store.reconcile_stale_runs() {
+                Ok(0) => {}
+                Ok(count) => log::info!("Reconciled {} stale run(s) left running by a previous otto", count),
+                Err(e) => log::warn!("Failed to reconcile stale runs in database: {}", e),
+            }
+
+            // A resumed workspace continues the run it already has
+            if let Some(run_id) = self.db_run_id() {
+                if let Err(e) = store.record_run_resumed(run_id) {
+                    log::warn!("Failed to record run resume in database: {}", e);
+                }
+                return;
+            }
+
             // Convert ExecutionContext to RunMetadata
             let metadata = RunMetadata::full(
                 context.ottofile.clone(),
@@ -351,14 +376,8 @@ impl<F: FileSystem> Workspace<F> {
         self.db_run_id.lock().ok().and_then(|guard| *guard)
     }
 
-    pub fn record_run_complete_in_db(&self, success: bool) {
+    pub fn record_run_complete_in_db(&self, status: super::state::RunStatus) {
         if let Some(ref store) = self.state_store {
-            let status = if success {
-                super::state::RunStatus::Success
-            } else {
-                super::state::RunStatus::Failed
-            };
-
             // Try to calculate directory size
             let size_bytes = Self::calculate_directory_size(&self.run).ok();
 
@@ -385,6 +404,19 @@ impl<F: FileSystem> Workspace<F> {
         Ok(total)
     }
 
+    /// Write what each task of the run is started with, for `otto Diff`
+    pub async fn save_snapshot(&self, snapshot: &RunSnapshot) -> Result<()> {
+        let yaml_content =
+            serde_yaml::to_string(snapshot).map_err(|e| eyre!("Failed to serialize run snapshot: {}", e))?;
+
+        self.fs
+            .write(&self.run.join(SNAPSHOT_FILE), yaml_content.as_bytes())
+            .await
+            .map_err(|e| eyre!("Failed to write {}: {}", SNAPSHOT_FILE, e))?;
+
+        Ok(())
+    }
+
     pub async fn save_task_context(&self, task_name: &str, context: &ExecutionContext) -> Result<()> {
         let task_run_yaml = self.task(task_name).join("run.yaml");
         let yaml_content =
@@ -1018,6 +1050,66 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    #[serial]
+    async fn test_resumed_workspace_continues_its_run() -> Result<()> {
+        use crate::executor::state::RunStatus;
+        use crate::ports::MemoryStateStore;
+
+        let fs = Arc::new(MemFs::new());
+        fs.create_dir_all(Path::new("/project")).await?;
+
+        unsafe {
+            std::env::set_var("OTTO_HOME", "/otto-home");
+        }
+
+        let store = Arc::new(MemoryStateStore::new());
+        let context = ExecutionContext {
+            prog: "otto".to_string(),
+            cwd: PathBuf::from("/project"),
+            user: "testuser".to_string(),
+            timestamp: 1234567890,
+            hash: "abc12345".to_string(),
+            ottofile: Some(PathBuf::from("/project/.otto.yml")),
+            args: vec!["otto".to_string(), "build".to_string()],
+        };
+        let run_id = store.record_run_start(&RunMetadata::full(
+            context.ottofile.clone(),
+            context.hash.clone(),
+            context.timestamp,
+            Some(context.cwd.clone()),
+            None,
+            None,
+            Some(context.args.clone()),
+        ))?;
+        store.record_run_complete(1234567890, RunStatus::Cancelled, None)?;
+
+        let ws = Workspace::new_with_hash_and_fs(
+            PathBuf::from("/project"),
+            "myproject".to_string(),
+            "abc12345".to_string(),
+            fs.clone(),
+        )
+        .await?
+        .with_state_store(store.clone())
+        .resuming(run_id, 1234567890);
+
+        assert_eq!(ws.run(), &PathBuf::from("/otto-home/myproject-abc12345/1234567890"));
+        assert_eq!(ws.timestamp(), 1234567890);
+
+        ws.init().await?;
+        ws.save_execution_context(context).await?;
+
+        // The interrupted run is running again instead of a new one being recorded
+        assert_eq!(ws.db_run_id(), Some(run_id));
+        let runs = store.get_recent_runs(10, None)?;
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].status, RunStatus::Running);
+        assert_eq!(runs[0].resume_count, 1);
+
+        Ok(())
+    }
+
     #[tokio::test]
     #[serial]
     async fn test_workspace_without_state_store() -> Result<()> {
@@ -1079,7 +1171,7 @@ mod tests {
         ws.save_execution_context(context).await?;
 
         // Now record completion
-        ws.record_run_complete_in_db(true);
+        ws.record_run_complete_in_db(crate::executor::RunStatus::Success);
 
         // Verify the run was marked complete
         let runs = store.get_recent_runs(10, None)?;
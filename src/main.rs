@@ -131,6 +131,7 @@ async fn handle_subcommand(args: &[String]) -> Option<Result<(), Report>> {
     match args[1].as_str() {
         "Clean" => Some(otto::app::execute_clean_command(&args[1..]).await),
         "Convert" => Some(otto::app::execute_convert_command(&args[1..])),
+        "Diff" => Some(otto::app::execute_diff_command(&args[1..])),
         "History" => Some(otto::app::execute_history_command(&args[1..])),
         "Stats" => Some(otto::app::execute_stats_command(&args[1..])),
         "Upgrade" => Some(otto::app::execute_upgrade_command(&args[1..]).await),
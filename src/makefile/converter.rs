@@ -1,8 +1,8 @@
 use eyre::Result;
 use std::collections::HashMap;
 
-use crate::cfg::config::ConfigSpec;
-use crate::cfg::otto::{OttoSpec, RetentionSpec};
+use crate::cfg::config::{ConfigSpec, ParamSpecs};
+use crate::cfg::otto::{OttoSpec, OutputMode, RetentionSpec};
 use crate::cfg::task::{TaskSpec, TaskSpecs};
 
 use super::ast::{AssignmentType, MakefileAst, Target};
@@ -37,6 +37,10 @@ impl OttoConverter {
             verbosity: 1,
             envs,
             retention: RetentionSpec::default(),
+            finally: Vec::new(),
+            interpreters: HashMap::new(),
+            strip_ansi: false,
+            output: OutputMode::default(),
         })
     }
 
@@ -92,10 +96,11 @@ impl OttoConverter {
             input: Vec::new(),
             output: Vec::new(),
             envs: HashMap::new(),
-            params: HashMap::new(),
+            params: ParamSpecs::new(),
             action,
             foreach: None,
             virtual_parent: false,
+            ..Default::default()
         })
     }
 
@@ -9,6 +9,7 @@ use std::path::PathBuf;
 use crate::executor::state::{
     OverallStats, ProjectSummary, RunMetadata, RunRecord, RunStatus, TaskRecord, TaskStats, TaskStatus,
 };
+use crate::executor::workflow::Annotation;
 
 /// Abstraction for state storage operations
 ///
@@ -19,6 +20,8 @@ pub trait StateStore: Send + Sync {
     // Recording methods
     fn record_run_start(&self, metadata: &RunMetadata) -> Result<i64>;
     fn record_run_complete(&self, timestamp: u64, status: RunStatus, size_bytes: Option<u64>) -> Result<()>;
+    fn record_run_resumed(&self, run_id: i64) -> Result<()>;
+    fn reconcile_stale_runs(&self) -> Result<usize>;
     fn record_task_start(
         &self,
         run_id: i64,
@@ -29,10 +32,19 @@ pub trait StateStore: Send + Sync {
         script_path: Option<&PathBuf>,
     ) -> Result<i64>;
     fn record_task_complete(&self, task_id: i64, exit_code: i32, status: TaskStatus) -> Result<()>;
-    fn record_task_skipped(&self, run_id: i64, task_name: &str, script_hash: Option<&str>) -> Result<i64>;
+    fn record_task_attempt(&self, task_id: i64, attempt: u32) -> Result<()>;
+    fn record_task_skipped(&self, run_id: i64, task_name: &str, script_hash: Option<&str>, reason: &str)
+    -> Result<i64>;
+    fn record_task_cached(&self, run_id: i64, task_name: &str, script_hash: Option<&str>) -> Result<i64>;
+    fn record_task_dependency_failed(&self, run_id: i64, task_name: &str) -> Result<i64>;
+    fn get_task_fingerprint(&self, run_id: i64, task_name: &str) -> Result<Option<String>>;
+    fn record_task_fingerprint(&self, run_id: i64, task_name: &str, fingerprint: &str) -> Result<()>;
+    fn record_task_annotations(&self, task_id: i64, annotations: &[Annotation]) -> Result<()>;
+    fn get_task_annotations(&self, task_id: i64) -> Result<Vec<Annotation>>;
 
     // Query methods
     fn get_recent_runs(&self, limit: usize, project_filter: Option<&str>) -> Result<Vec<RunRecord>>;
+    fn get_run(&self, run_id: i64) -> Result<Option<RunRecord>>;
     fn get_run_tasks(&self, run_id: i64) -> Result<Vec<TaskRecord>>;
     fn get_task_history(&self, task_name: &str, limit: usize) -> Result<Vec<TaskRecord>>;
     fn get_overall_stats(&self) -> Result<OverallStats>;
@@ -67,6 +79,8 @@ pub struct MemoryStateStore {
     runs: std::sync::RwLock<Vec<RunRecord>>,
     tasks: std::sync::RwLock<Vec<TaskRecord>>,
     projects: std::sync::RwLock<Vec<ProjectSummary>>,
+    fingerprints: std::sync::RwLock<std::collections::HashMap<(i64, String), String>>,
+    annotations: std::sync::RwLock<std::collections::HashMap<i64, Vec<Annotation>>>,
     next_run_id: std::sync::atomic::AtomicI64,
     next_task_id: std::sync::atomic::AtomicI64,
     next_project_id: std::sync::atomic::AtomicI64,
@@ -78,6 +92,8 @@ impl MemoryStateStore {
             runs: std::sync::RwLock::new(Vec::new()),
             tasks: std::sync::RwLock::new(Vec::new()),
             projects: std::sync::RwLock::new(Vec::new()),
+            fingerprints: std::sync::RwLock::new(std::collections::HashMap::new()),
+            annotations: std::sync::RwLock::new(std::collections::HashMap::new()),
             next_run_id: std::sync::atomic::AtomicI64::new(1),
             next_task_id: std::sync::atomic::AtomicI64::new(1),
             next_project_id: std::sync::atomic::AtomicI64::new(1),
@@ -138,6 +154,7 @@ impl StateStore for MemoryStateStore {
             hostname: metadata.hostname.clone(),
             args: metadata.args.clone(),
             ended_at: None,
+            resume_count: 0,
         };
 
         self.runs.write().unwrap().push(run);
@@ -169,6 +186,25 @@ impl StateStore for MemoryStateStore {
         Ok(())
     }
 
+    fn record_run_resumed(&self, run_id: i64) -> Result<()> {
+        let mut runs = self.runs.write().unwrap();
+        let run = runs
+            .iter_mut()
+            .find(|r| r.id == run_id)
+            .ok_or_else(|| eyre::eyre!("Run {} not found", run_id))?;
+        run.status = RunStatus::Running;
+        run.ended_at = None;
+        run.duration_seconds = None;
+        run.resume_count += 1;
+
+        Ok(())
+    }
+
+    fn reconcile_stale_runs(&self) -> Result<usize> {
+        // Every run in a memory store belongs to the current process, so none can be stale
+        Ok(0)
+    }
+
     fn record_task_start(
         &self,
         run_id: i64,
@@ -198,6 +234,8 @@ impl StateStore for MemoryStateStore {
             stdout_path: stdout_path.cloned(),
             stderr_path: stderr_path.cloned(),
             script_path: script_path.cloned(),
+            attempt: 1,
+            skip_reason: None,
         };
 
         self.tasks.write().unwrap().push(task);
@@ -224,9 +262,29 @@ impl StateStore for MemoryStateStore {
         Ok(())
     }
 
-    fn record_task_skipped(&self, run_id: i64, task_name: &str, script_hash: Option<&str>) -> Result<i64> {
+    fn record_task_attempt(&self, task_id: i64, attempt: u32) -> Result<()> {
+        let mut tasks = self.tasks.write().unwrap();
+        if let Some(task) = tasks.iter_mut().find(|t| t.id == task_id) {
+            task.attempt = attempt;
+        }
+
+        Ok(())
+    }
+
+    fn record_task_skipped(
+        &self,
+        run_id: i64,
+        task_name: &str,
+        script_hash: Option<&str>,
+        reason: &str,
+    ) -> Result<i64> {
         let task_id = self.next_task_id.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
 
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
         let task = TaskRecord {
             id: task_id,
             run_id,
@@ -234,12 +292,69 @@ impl StateStore for MemoryStateStore {
             status: TaskStatus::Skipped,
             script_hash: script_hash.map(String::from),
             exit_code: None,
+            started_at: Some(now),
+            ended_at: Some(now),
+            duration_seconds: None,
+            stdout_path: None,
+            stderr_path: None,
+            script_path: None,
+            attempt: 1,
+            skip_reason: Some(reason.to_string()),
+        };
+
+        self.tasks.write().unwrap().push(task);
+
+        Ok(task_id)
+    }
+
+    fn record_task_cached(&self, run_id: i64, task_name: &str, script_hash: Option<&str>) -> Result<i64> {
+        let task_id = self.next_task_id.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        let task = TaskRecord {
+            id: task_id,
+            run_id,
+            name: task_name.to_string(),
+            status: TaskStatus::Cached,
+            script_hash: script_hash.map(String::from),
+            exit_code: None,
+            started_at: Some(now),
+            ended_at: Some(now),
+            duration_seconds: None,
+            stdout_path: None,
+            stderr_path: None,
+            script_path: None,
+            attempt: 1,
+            skip_reason: None,
+        };
+
+        self.tasks.write().unwrap().push(task);
+
+        Ok(task_id)
+    }
+
+    fn record_task_dependency_failed(&self, run_id: i64, task_name: &str) -> Result<i64> {
+        let task_id = self.next_task_id.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+
+        let task = TaskRecord {
+            id: task_id,
+            run_id,
+            name: task_name.to_string(),
+            status: TaskStatus::DependencyFailed,
+            script_hash: None,
+            exit_code: None,
             started_at: None,
             ended_at: None,
             duration_seconds: None,
             stdout_path: None,
             stderr_path: None,
             script_path: None,
+            attempt: 1,
+            skip_reason: None,
         };
 
         self.tasks.write().unwrap().push(task);
@@ -247,6 +362,49 @@ impl StateStore for MemoryStateStore {
         Ok(task_id)
     }
 
+    fn get_task_fingerprint(&self, run_id: i64, task_name: &str) -> Result<Option<String>> {
+        let runs = self.runs.read().unwrap();
+        let Some(project_id) = runs.iter().find(|r| r.id == run_id).map(|r| r.project_id) else {
+            return Ok(None);
+        };
+
+        let fingerprints = self.fingerprints.read().unwrap();
+        Ok(fingerprints.get(&(project_id, task_name.to_string())).cloned())
+    }
+
+    fn record_task_fingerprint(&self, run_id: i64, task_name: &str, fingerprint: &str) -> Result<()> {
+        let runs = self.runs.read().unwrap();
+        if let Some(run) = runs.iter().find(|r| r.id == run_id) {
+            self.fingerprints
+                .write()
+                .unwrap()
+                .insert((run.project_id, task_name.to_string()), fingerprint.to_string());
+        }
+
+        Ok(())
+    }
+
+    fn record_task_annotations(&self, task_id: i64, annotations: &[Annotation]) -> Result<()> {
+        self.annotations
+            .write()
+            .unwrap()
+            .entry(task_id)
+            .or_default()
+            .extend_from_slice(annotations);
+
+        Ok(())
+    }
+
+    fn get_task_annotations(&self, task_id: i64) -> Result<Vec<Annotation>> {
+        Ok(self
+            .annotations
+            .read()
+            .unwrap()
+            .get(&task_id)
+            .cloned()
+            .unwrap_or_default())
+    }
+
     fn get_recent_runs(&self, limit: usize, project_filter: Option<&str>) -> Result<Vec<RunRecord>> {
         let runs = self.runs.read().unwrap();
         let projects = self.projects.read().unwrap();
@@ -263,18 +421,22 @@ impl StateStore for MemoryStateStore {
             .cloned()
             .collect();
 
-        result.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+        result.sort_by_key(|r| std::cmp::Reverse(r.timestamp));
         result.truncate(limit);
 
         Ok(result)
     }
 
+    fn get_run(&self, run_id: i64) -> Result<Option<RunRecord>> {
+        Ok(self.runs.read().unwrap().iter().find(|r| r.id == run_id).cloned())
+    }
+
     fn get_run_tasks(&self, run_id: i64) -> Result<Vec<TaskRecord>> {
         let tasks = self.tasks.read().unwrap();
 
         let mut result: Vec<TaskRecord> = tasks.iter().filter(|t| t.run_id == run_id).cloned().collect();
 
-        result.sort_by(|a, b| a.started_at.cmp(&b.started_at));
+        result.sort_by_key(|r| r.started_at);
 
         Ok(result)
     }
@@ -284,7 +446,7 @@ impl StateStore for MemoryStateStore {
 
         let mut result: Vec<TaskRecord> = tasks.iter().filter(|t| t.name == task_name).cloned().collect();
 
-        result.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+        result.sort_by_key(|r| std::cmp::Reverse(r.started_at));
         result.truncate(limit);
 
         Ok(result)
@@ -316,7 +478,7 @@ impl StateStore for MemoryStateStore {
     fn get_all_projects(&self) -> Result<Vec<ProjectSummary>> {
         let projects = self.projects.read().unwrap();
         let mut result = projects.clone();
-        result.sort_by(|a, b| b.last_seen.cmp(&a.last_seen));
+        result.sort_by_key(|r| std::cmp::Reverse(r.last_seen));
         Ok(result)
     }
 
@@ -340,11 +502,16 @@ impl StateStore for MemoryStateStore {
                     successful_executions: 0,
                     failed_executions: 0,
                     skipped_executions: 0,
+                    timed_out_executions: 0,
+                    cached_executions: 0,
+                    retried_executions: 0,
+                    retry_attempts: 0,
                     avg_duration_seconds: None,
                     min_duration_seconds: None,
                     max_duration_seconds: None,
                     last_executed: None,
                     last_status: None,
+                    script_changed_since_success: false,
                 });
 
                 stats.total_executions += 1;
@@ -352,8 +519,16 @@ impl StateStore for MemoryStateStore {
                     TaskStatus::Completed => stats.successful_executions += 1,
                     TaskStatus::Failed => stats.failed_executions += 1,
                     TaskStatus::Skipped => stats.skipped_executions += 1,
+                    TaskStatus::TimedOut => stats.timed_out_executions += 1,
+                    TaskStatus::Cached => stats.cached_executions += 1,
                     _ => {}
                 }
+                if task.attempt == 2 {
+                    stats.retried_executions += 1;
+                }
+                if task.attempt > 1 {
+                    stats.retry_attempts += 1;
+                }
 
                 if let Some(started_at) = task.started_at
                     && (stats.last_executed.is_none() || started_at > stats.last_executed.unwrap())
@@ -364,6 +539,26 @@ impl StateStore for MemoryStateStore {
             }
         }
 
+        // Tasks are stored in insertion order, so the last matching hash is the most recent one
+        for stats in stats_map.values_mut() {
+            let project_tasks: Vec<&TaskRecord> = tasks
+                .iter()
+                .filter(|t| {
+                    t.name == task_name
+                        && runs
+                            .iter()
+                            .any(|r| r.id == t.run_id && r.project_id == stats.project_id)
+                })
+                .collect();
+            let latest = project_tasks.iter().rev().find_map(|t| t.script_hash.as_ref());
+            let last_success = project_tasks
+                .iter()
+                .rev()
+                .filter(|t| t.status == TaskStatus::Completed)
+                .find_map(|t| t.script_hash.as_ref());
+            stats.script_changed_since_success = matches!((latest, last_success), (Some(l), Some(s)) if l != s);
+        }
+
         Ok(stats_map.into_values().collect())
     }
 
@@ -377,7 +572,7 @@ impl StateStore for MemoryStateStore {
             all_stats.extend(self.get_task_stats(task_name)?);
         }
 
-        all_stats.sort_by(|a, b| b.total_executions.cmp(&a.total_executions));
+        all_stats.sort_by_key(|r| std::cmp::Reverse(r.total_executions));
 
         if let Some(limit) = limit {
             all_stats.truncate(limit);
@@ -406,7 +601,7 @@ impl StateStore for MemoryStateStore {
             .cloned()
             .collect();
 
-        result.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+        result.sort_by_key(|r| std::cmp::Reverse(r.timestamp));
         result.truncate(limit);
 
         Ok(result)
@@ -438,7 +633,7 @@ impl StateStore for MemoryStateStore {
             .cloned()
             .collect();
 
-        filtered_runs.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+        filtered_runs.sort_by_key(|r| std::cmp::Reverse(r.timestamp));
 
         let keep_count = keep_last.unwrap_or(0);
         let mut runs_to_delete = Vec::new();
@@ -568,13 +763,113 @@ mod tests {
         let metadata = create_test_metadata("abc123", 1234567890);
         let run_id = store.record_run_start(&metadata).unwrap();
 
-        let task_id = store.record_task_skipped(run_id, "build", Some("hash123")).unwrap();
+        let task_id = store
+            .record_task_skipped(run_id, "build", Some("hash123"), "up to date")
+            .unwrap();
 
         assert!(task_id > 0);
 
         let tasks = store.get_run_tasks(run_id).unwrap();
         assert_eq!(tasks.len(), 1);
         assert_eq!(tasks[0].status, TaskStatus::Skipped);
+        assert_eq!(tasks[0].skip_reason.as_deref(), Some("up to date"));
+    }
+
+    #[test]
+    fn test_memory_store_script_changed_since_success() {
+        let store = MemoryStateStore::new();
+
+        let metadata = create_test_metadata("abc123", 1234567890);
+        let run_id = store.record_run_start(&metadata).unwrap();
+
+        let task_id = store
+            .record_task_start(run_id, "build", Some("aaaa1111"), None, None, None)
+            .unwrap();
+        store.record_task_complete(task_id, 0, TaskStatus::Completed).unwrap();
+        assert!(!store.get_task_stats("build").unwrap()[0].script_changed_since_success);
+
+        let task_id = store
+            .record_task_start(run_id, "build", Some("bbbb2222"), None, None, None)
+            .unwrap();
+        store.record_task_complete(task_id, 1, TaskStatus::Failed).unwrap();
+        assert!(store.get_task_stats("build").unwrap()[0].script_changed_since_success);
+    }
+
+    #[test]
+    fn test_memory_store_task_cached() {
+        let store = MemoryStateStore::new();
+
+        let metadata = create_test_metadata("abc123", 1234567890);
+        let run_id = store.record_run_start(&metadata).unwrap();
+
+        store.record_task_cached(run_id, "codegen", None).unwrap();
+
+        let tasks = store.get_run_tasks(run_id).unwrap();
+        assert_eq!(tasks[0].status, TaskStatus::Cached);
+        assert_eq!(store.get_task_stats("codegen").unwrap()[0].cached_executions, 1);
+    }
+
+    #[test]
+    fn test_memory_store_task_dependency_failed() {
+        let store = MemoryStateStore::new();
+
+        let metadata = create_test_metadata("abc123", 1234567890);
+        let run_id = store.record_run_start(&metadata).unwrap();
+
+        store.record_task_dependency_failed(run_id, "deploy").unwrap();
+
+        let tasks = store.get_run_tasks(run_id).unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].status, TaskStatus::DependencyFailed);
+    }
+
+    #[test]
+    fn test_memory_store_task_fingerprint() {
+        let store = MemoryStateStore::new();
+
+        let first_run = store
+            .record_run_start(&create_test_metadata("abc123", 1234567890))
+            .unwrap();
+        assert_eq!(store.get_task_fingerprint(first_run, "build").unwrap(), None);
+        store.record_task_fingerprint(first_run, "build", "fp1").unwrap();
+
+        let second_run = store
+            .record_run_start(&create_test_metadata("abc123", 1234567891))
+            .unwrap();
+        assert_eq!(
+            store.get_task_fingerprint(second_run, "build").unwrap(),
+            Some("fp1".to_string())
+        );
+
+        let other_run = store
+            .record_run_start(&create_test_metadata("def456", 1234567892))
+            .unwrap();
+        assert_eq!(store.get_task_fingerprint(other_run, "build").unwrap(), None);
+    }
+
+    #[test]
+    fn test_memory_store_task_attempts() {
+        let store = MemoryStateStore::new();
+
+        let metadata = create_test_metadata("abc123", 1234567890);
+        let run_id = store.record_run_start(&metadata).unwrap();
+
+        let first = store
+            .record_task_start(run_id, "flaky", None, None, None, None)
+            .unwrap();
+        store.record_task_complete(first, 1, TaskStatus::Failed).unwrap();
+        let second = store
+            .record_task_start(run_id, "flaky", None, None, None, None)
+            .unwrap();
+        store.record_task_attempt(second, 2).unwrap();
+        store.record_task_complete(second, 0, TaskStatus::Completed).unwrap();
+
+        let tasks = store.get_run_tasks(run_id).unwrap();
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(tasks.iter().map(|t| t.attempt).collect::<Vec<_>>(), vec![1, 2]);
+
+        let stats = store.get_task_stats("flaky").unwrap();
+        assert_eq!(stats[0].retried_executions, 1);
     }
 
     #[test]
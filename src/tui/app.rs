@@ -1,4 +1,5 @@
 use super::layout::PaneLayout;
+use crate::executor::handoff::{TerminalHandoff, TerminalState};
 use crossterm::event::{self, Event, KeyCode, KeyEventKind};
 use ratatui::{
     Terminal,
@@ -21,6 +22,7 @@ pub struct TuiApp {
     tick_rate: Duration,
     fullscreen_mode: bool,
     shutdown_flag: Option<std::sync::Arc<std::sync::atomic::AtomicBool>>,
+    terminal_handoff: Option<TerminalHandoff>,
 }
 
 impl Default for TuiApp {
@@ -38,6 +40,7 @@ impl TuiApp {
             tick_rate: Duration::from_millis(TUI_TICK_RATE_MS),
             fullscreen_mode: false,
             shutdown_flag: None,
+            terminal_handoff: None,
         }
     }
 
@@ -45,12 +48,27 @@ impl TuiApp {
         self.shutdown_flag = Some(flag);
     }
 
+    /// Step aside whenever an interactive task needs the terminal
+    pub fn set_terminal_handoff(&mut self, handoff: TerminalHandoff) {
+        self.terminal_handoff = Some(handoff);
+    }
+
     pub fn layout_mut(&mut self) -> &mut PaneLayout {
         &mut self.layout
     }
 
-    pub fn run<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> io::Result<()> {
+    pub fn run<B: Backend + io::Write>(&mut self, terminal: &mut Terminal<B>) -> io::Result<()> {
         loop {
+            if self.hand_over_terminal(terminal)? {
+                // Keep up with task messages, but leave the screen and the keyboard alone
+                std::thread::sleep(self.tick_rate);
+                self.on_tick();
+                if self.shutdown_requested() {
+                    break;
+                }
+                continue;
+            }
+
             // Draw UI
             terminal.draw(|f| {
                 let chunks = Layout::default()
@@ -90,9 +108,7 @@ impl TuiApp {
                 self.last_tick = Instant::now();
             }
 
-            if let Some(ref flag) = self.shutdown_flag
-                && flag.load(std::sync::atomic::Ordering::SeqCst)
-            {
+            if self.shutdown_requested() {
                 self.should_quit = true;
             }
 
@@ -104,6 +120,27 @@ impl TuiApp {
         Ok(())
     }
 
+    /// Suspend for an interactive task or resume after it; true while a task has the terminal
+    fn hand_over_terminal<B: Backend + io::Write>(&mut self, terminal: &mut Terminal<B>) -> io::Result<bool> {
+        let Some(handoff) = &self.terminal_handoff else {
+            return Ok(false);
+        };
+        if handoff.take_back() {
+            super::resume_terminal(terminal)?;
+        }
+        if handoff.state() == TerminalState::Requested {
+            super::suspend_terminal(terminal)?;
+            handoff.released();
+        }
+        Ok(handoff.state() != TerminalState::Tui)
+    }
+
+    fn shutdown_requested(&self) -> bool {
+        self.shutdown_flag
+            .as_ref()
+            .is_some_and(|flag| flag.load(std::sync::atomic::Ordering::SeqCst))
+    }
+
     fn on_tick(&mut self) {
         // Update all panes (receive from broadcast channels)
         self.layout.update_all();
@@ -121,7 +158,7 @@ impl TuiApp {
 
         let help_text = if self.fullscreen_mode {
             format!(
-                "{}f/Enter: Exit Fullscreen | ↑↓/jk: Scroll | Home: Top | q/Esc: Quit",
+                "{}f/Enter: Exit Fullscreen | ↑↓/jk: Scroll | Home: Top | g: Groups | q/Esc: Quit",
                 page_info
             )
         } else if total_pages > 1 {
@@ -131,7 +168,7 @@ impl TuiApp {
             )
         } else {
             format!(
-                "{}f/Enter: Fullscreen | Tab/←→: Switch Pane | ↑↓/jk: Scroll | Home: Top | q/Esc: Quit",
+                "{}f/Enter: Fullscreen | Tab/←→: Switch Pane | ↑↓/jk: Scroll | Home: Top | g: Groups | q/Esc: Quit",
                 page_info
             )
         };
@@ -176,6 +213,11 @@ impl TuiApp {
                     pane.reset_scroll();
                 }
             }
+            KeyCode::Char('g') => {
+                if let Some(pane) = self.layout.focused_pane_mut() {
+                    pane.toggle_groups();
+                }
+            }
             KeyCode::PageDown => {
                 self.layout.next_page();
             }
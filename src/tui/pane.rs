@@ -1,11 +1,15 @@
 use crate::executor::output::{TaskMessage, TaskOutput, TuiTaskStatus};
+use crate::executor::progress::Progress;
+use crate::executor::workflow::{self, WorkflowCommand};
+use ansi_to_tui::IntoText;
 use ratatui::{
     Frame,
     layout::Rect,
     style::{Color, Style},
     text::Line,
-    widgets::{Block, Borders, Paragraph},
+    widgets::{Block, Borders, Gauge, Paragraph},
 };
+use std::borrow::Cow;
 use std::collections::VecDeque;
 use std::time::{Duration, SystemTime};
 use tokio::sync::broadcast;
@@ -17,7 +21,12 @@ pub enum PaneStatus {
     Running,
     Completed,
     Failed,
+    FailedAllowed,
     Skipped,
+    Cached,
+    TimedOut,
+    DependencyFailed,
+    Cancelled,
 }
 
 impl PaneStatus {
@@ -27,7 +36,12 @@ impl PaneStatus {
             PaneStatus::Running => "●",
             PaneStatus::Completed => "✓",
             PaneStatus::Failed => "✗",
+            PaneStatus::FailedAllowed => "⚠",
             PaneStatus::Skipped => "⊘",
+            PaneStatus::Cached => "↺",
+            PaneStatus::TimedOut => "⏱",
+            PaneStatus::DependencyFailed => "⊗",
+            PaneStatus::Cancelled => "⊖",
         }
     }
 
@@ -37,7 +51,12 @@ impl PaneStatus {
             PaneStatus::Running => Color::Green,
             PaneStatus::Completed => Color::Green,
             PaneStatus::Failed => Color::Red,
+            PaneStatus::FailedAllowed => Color::Yellow,
             PaneStatus::Skipped => Color::Yellow,
+            PaneStatus::Cached => Color::Cyan,
+            PaneStatus::TimedOut => Color::Magenta,
+            PaneStatus::DependencyFailed => Color::DarkGray,
+            PaneStatus::Cancelled => Color::LightRed,
         }
     }
 }
@@ -61,6 +80,9 @@ pub trait Pane {
     fn scroll_down(&mut self, visible_height: u16);
 
     fn reset_scroll(&mut self);
+
+    /// Expand or collapse finished `::group::` sections
+    fn toggle_groups(&mut self);
 }
 
 /// A pane that displays output from a single task
@@ -75,6 +97,10 @@ pub struct TaskPane {
     start_time: Option<SystemTime>,
     duration: Option<Duration>,
     auto_scroll: bool,
+    /// Show the lines of finished groups instead of just their headers
+    expand_groups: bool,
+    /// Latest progress reported by the running task
+    progress: Option<Progress>,
 }
 
 impl TaskPane {
@@ -90,6 +116,8 @@ impl TaskPane {
             start_time: None,
             duration: None,
             auto_scroll: true, // Auto-scroll enabled by default
+            expand_groups: false,
+            progress: None,
         }
     }
 
@@ -101,13 +129,68 @@ impl TaskPane {
         self.status = status;
     }
 
+    /// Output lines as displayed: workflow commands are interpreted, and groups that
+    /// have ended are folded into their header unless groups are expanded
+    fn display_lines(&self) -> Vec<Cow<'_, str>> {
+        /// Header index, name and body of a group
+        type Group<'a> = (usize, String, Vec<Cow<'a, str>>);
+
+        fn close<'a>(lines: &mut Vec<Cow<'a, str>>, (header, name, body): Group<'a>, expand: bool) {
+            if expand {
+                lines[header] = Cow::Owned(format!("▾ {name}"));
+                lines.extend(body);
+            } else {
+                lines[header] = Cow::Owned(format!("▸ {name} ({} lines)", body.len()));
+            }
+        }
+
+        let mut lines = Vec::new();
+        let mut group: Option<Group<'_>> = None;
+
+        for line in &self.output_buffer {
+            let shown = match workflow::parse(line) {
+                None => Cow::Borrowed(line.as_str()),
+                Some(WorkflowCommand::SetOutput { .. } | WorkflowCommand::Progress(_)) => continue,
+                Some(WorkflowCommand::Annotate(annotation)) => Cow::Owned(annotation.to_string()),
+                Some(WorkflowCommand::Group(name)) => {
+                    if let Some(open) = group.take() {
+                        close(&mut lines, open, self.expand_groups);
+                    }
+                    lines.push(Cow::Owned(format!("▾ {name}")));
+                    group = Some((lines.len() - 1, name, Vec::new()));
+                    continue;
+                }
+                Some(WorkflowCommand::EndGroup) => {
+                    if let Some(open) = group.take() {
+                        close(&mut lines, open, self.expand_groups);
+                    }
+                    continue;
+                }
+            };
+            match &mut group {
+                Some((_, _, body)) => body.push(shown),
+                None => lines.push(shown),
+            }
+        }
+        // A group that hasn't ended yet is still running, so keep it open
+        if let Some((_, _, body)) = group {
+            lines.extend(body);
+        }
+        lines
+    }
+
     fn tui_status_to_pane_status(status: &TuiTaskStatus) -> PaneStatus {
         match status {
             TuiTaskStatus::Pending => PaneStatus::Pending,
             TuiTaskStatus::Running => PaneStatus::Running,
             TuiTaskStatus::Completed => PaneStatus::Completed,
             TuiTaskStatus::Failed => PaneStatus::Failed,
+            TuiTaskStatus::FailedAllowed => PaneStatus::FailedAllowed,
             TuiTaskStatus::Skipped => PaneStatus::Skipped,
+            TuiTaskStatus::Cached => PaneStatus::Cached,
+            TuiTaskStatus::TimedOut => PaneStatus::TimedOut,
+            TuiTaskStatus::DependencyFailed => PaneStatus::DependencyFailed,
+            TuiTaskStatus::Cancelled => PaneStatus::Cancelled,
         }
     }
 }
@@ -131,12 +214,44 @@ impl Pane for TaskPane {
             .borders(Borders::ALL)
             .border_style(Style::default().fg(border_color));
 
-        let inner_area = block.inner(area);
+        let mut inner_area = block.inner(area);
         frame.render_widget(block, area);
 
+        // The last line of a running task's pane shows its progress
+        if let Some(progress) = &self.progress
+            && inner_area.height > 1
+        {
+            inner_area.height -= 1;
+            let gauge_area = Rect {
+                y: inner_area.y + inner_area.height,
+                height: 1,
+                ..inner_area
+            };
+            match progress.ratio() {
+                Some(ratio) => {
+                    let label = format!(
+                        "{}/{} {}",
+                        progress.current,
+                        progress.total.unwrap_or_default(),
+                        progress.message
+                    );
+                    let gauge = Gauge::default()
+                        .gauge_style(Style::default().fg(Color::Cyan).bg(Color::DarkGray))
+                        .ratio(ratio)
+                        .label(label);
+                    frame.render_widget(gauge, gauge_area);
+                }
+                None => {
+                    let line = Line::from(format!("⟳ {} {}", progress.current, progress.message));
+                    frame.render_widget(Paragraph::new(line).style(Style::default().fg(Color::Cyan)), gauge_area);
+                }
+            }
+        }
+
         // Render output lines with scrolling
+        let display_lines = self.display_lines();
         let visible_height = inner_area.height as usize;
-        let total_lines = self.output_buffer.len();
+        let total_lines = display_lines.len();
 
         // Auto-scroll to bottom if enabled
         let start_line = if self.auto_scroll && total_lines > visible_height {
@@ -150,12 +265,18 @@ impl Pane for TaskPane {
         let max_width = inner_area.width as usize;
         let mut wrapped_lines: Vec<Line> = Vec::new();
 
-        for line in self.output_buffer.iter().skip(start_line).take(end_line - start_line) {
-            if line.len() <= max_width {
-                wrapped_lines.push(Line::from(line.as_str()));
+        for line in display_lines.iter().skip(start_line).take(end_line - start_line) {
+            // Colored output (pty tasks) is rendered with its styles; escapes take no width,
+            // so these lines are cut off at the pane edge instead of being wrapped
+            if line.contains('\x1b')
+                && let Ok(text) = line.as_bytes().into_text()
+            {
+                wrapped_lines.extend(text.lines);
+            } else if line.len() <= max_width {
+                wrapped_lines.push(Line::from(line.as_ref()));
             } else {
                 // Line is too long, wrap it
-                let mut remaining = line.as_str();
+                let mut remaining = line.as_ref();
                 while !remaining.is_empty() {
                     let end_idx = remaining
                         .char_indices()
@@ -197,10 +318,15 @@ impl Pane for TaskPane {
                             self.status = Self::tui_status_to_pane_status(&status);
                             let status_msg = match status {
                                 TuiTaskStatus::Skipped => "○ Task skipped (up to date)",
+                                TuiTaskStatus::Cached => "↺ Task restored from cache",
                                 TuiTaskStatus::Running => "● Task running",
                                 TuiTaskStatus::Completed => "✓ Task completed",
                                 TuiTaskStatus::Failed => "✗ Task failed",
+                                TuiTaskStatus::FailedAllowed => "⚠ Task failed (allowed)",
                                 TuiTaskStatus::Pending => "◌ Task pending",
+                                TuiTaskStatus::TimedOut => "⏱ Task timed out",
+                                TuiTaskStatus::DependencyFailed => "⊗ Dependency failed",
+                                TuiTaskStatus::Cancelled => "⊖ Task cancelled",
                             };
                             self.output_buffer.push_back(status_msg.to_string());
                             if self.output_buffer.len() > self.max_buffer_lines {
@@ -208,6 +334,11 @@ impl Pane for TaskPane {
                             }
                         }
                     }
+                    TaskMessage::Progress { task_name, progress } => {
+                        if task_name == self.task_name {
+                            self.progress = Some(progress);
+                        }
+                    }
                     TaskMessage::Started { task_name, timestamp } => {
                         if task_name == self.task_name {
                             self.status = PaneStatus::Running;
@@ -227,10 +358,16 @@ impl Pane for TaskPane {
                         if task_name == self.task_name {
                             self.status = Self::tui_status_to_pane_status(&status);
                             self.duration = Some(Duration::from_millis(duration_ms));
+                            self.progress = None;
                             let status_msg = match status {
                                 TuiTaskStatus::Completed => "✓ Task completed successfully",
                                 TuiTaskStatus::Failed => "✗ Task failed",
+                                TuiTaskStatus::FailedAllowed => "⚠ Task failed (allowed)",
                                 TuiTaskStatus::Skipped => "○ Task skipped",
+                                TuiTaskStatus::Cached => "↺ Task restored from cache",
+                                TuiTaskStatus::TimedOut => "⏱ Task timed out",
+                                TuiTaskStatus::DependencyFailed => "⊗ Dependency failed",
+                                TuiTaskStatus::Cancelled => "⊖ Task cancelled",
                                 _ => "Task finished",
                             };
                             self.output_buffer.push_back(status_msg.to_string());
@@ -269,7 +406,7 @@ impl Pane for TaskPane {
     }
 
     fn scroll_down(&mut self, visible_height: u16) {
-        let total_lines = self.output_buffer.len() as u16;
+        let total_lines = self.display_lines().len() as u16;
         if total_lines > visible_height {
             let max_scroll = total_lines - visible_height;
             if self.scroll_offset < max_scroll {
@@ -285,4 +422,8 @@ impl Pane for TaskPane {
         self.scroll_offset = 0;
         self.auto_scroll = false; // Manual reset disables auto-scroll
     }
+
+    fn toggle_groups(&mut self) {
+        self.expand_groups = !self.expand_groups;
+    }
 }
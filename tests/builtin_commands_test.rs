@@ -55,6 +55,14 @@ tasks:
         stdout.contains("History") && stdout.contains("[built-in]"),
         "History command not found in help"
     );
+    assert!(
+        stdout.contains("Diff") && stdout.contains("[built-in]"),
+        "Diff command not found in help"
+    );
+    assert!(
+        stdout.contains("Rerun") && stdout.contains("[built-in]"),
+        "Rerun command not found in help"
+    );
     assert!(
         stdout.contains("Stats") && stdout.contains("[built-in]"),
         "Stats command not found in help"
@@ -219,8 +227,8 @@ tasks:
     let builtin_count = stdout.matches("[built-in]").count();
 
     assert_eq!(
-        builtin_count, 6,
-        "Expected exactly 6 built-in commands, found {}. Commands: Clean, Convert, Graph, History, Stats, Upgrade",
+        builtin_count, 8,
+        "Expected exactly 8 built-in commands, found {}. Commands: Clean, Convert, Diff, Graph, History, Rerun, Stats, Upgrade",
         builtin_count
     );
 
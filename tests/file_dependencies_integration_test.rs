@@ -6,7 +6,7 @@ use tempfile::TempDir;
 use tokio::time::timeout;
 
 use otto::Task;
-use otto::cfg::config::ConfigSpec;
+use otto::cfg::config::{ConfigSpec, ParamSpecs};
 use otto::cfg::task::TaskSpec;
 use otto::executor::scheduler::{TaskScheduler, TaskStatus};
 use otto::executor::workspace::{ExecutionContext, Workspace};
@@ -75,10 +75,11 @@ impl TestFixture {
                 input: inputs.iter().map(|s| s.to_string()).collect(),
                 output: outputs.iter().map(|s| s.to_string()).collect(),
                 envs: HashMap::new(),
-                params: HashMap::new(),
+                params: ParamSpecs::new(),
                 help: None,
                 foreach: None,
                 virtual_parent: false,
+                ..Default::default()
             },
             &self.temp_path,
         )
@@ -101,10 +102,11 @@ impl TestFixture {
                 input: inputs.iter().map(|s| s.to_string()).collect(),
                 output: outputs.iter().map(|s| s.to_string()).collect(),
                 envs: HashMap::new(),
-                params: HashMap::new(),
+                params: ParamSpecs::new(),
                 help: None,
                 foreach: None,
                 virtual_parent: false,
+                ..Default::default()
             },
             &self.temp_path,
         )
@@ -329,10 +331,11 @@ async fn test_file_dependencies_error_handling() -> Result<()> {
             input: vec![missing_input.to_string_lossy().to_string()],
             output: vec![output_file.to_string_lossy().to_string()],
             envs: HashMap::new(),
-            params: HashMap::new(),
+            params: ParamSpecs::new(),
             help: None,
             foreach: None,
             virtual_parent: false,
+            ..Default::default()
         },
         temp_path,
     );
@@ -375,10 +378,11 @@ async fn test_file_dependencies_error_handling() -> Result<()> {
             input: vec![],
             output: vec![readonly_output.to_string_lossy().to_string()],
             envs: HashMap::new(),
-            params: HashMap::new(),
+            params: ParamSpecs::new(),
             help: None,
             foreach: None,
             virtual_parent: false,
+            ..Default::default()
         },
         temp_path,
     );
@@ -479,10 +483,11 @@ async fn test_mixed_task_and_file_dependencies() -> Result<()> {
             ],
             output: vec![processed_file.to_string_lossy().to_string()],
             envs: HashMap::new(),
-            params: HashMap::new(),
+            params: ParamSpecs::new(),
             help: None,
             foreach: None,
             virtual_parent: false,
+            ..Default::default()
         },
         temp_path,
     );
@@ -503,10 +508,11 @@ async fn test_mixed_task_and_file_dependencies() -> Result<()> {
             input: vec![processed_file.to_string_lossy().to_string()],
             output: vec![report_file.to_string_lossy().to_string()],
             envs: HashMap::new(),
-            params: HashMap::new(),
+            params: ParamSpecs::new(),
             help: None,
             foreach: None,
             virtual_parent: false,
+            ..Default::default()
         },
         temp_path,
     );
@@ -583,10 +589,11 @@ async fn test_file_dependencies_incremental_detection() -> Result<()> {
             input: vec!["main.c".to_string(), "main.h".to_string()],
             output: vec!["main.o".to_string()],
             envs: HashMap::new(),
-            params: HashMap::new(),
+            params: ParamSpecs::new(),
             help: None,
             foreach: None,
             virtual_parent: false,
+            ..Default::default()
         },
         temp_path,
     );
@@ -729,10 +736,11 @@ async fn test_file_dependencies_multiple_files() -> Result<()> {
             ],
             output: vec!["combined.txt".to_string(), "summary.txt".to_string()],
             envs: HashMap::new(),
-            params: HashMap::new(),
+            params: ParamSpecs::new(),
             help: None,
             foreach: None,
             virtual_parent: false,
+            ..Default::default()
         },
         temp_path,
     );
@@ -900,10 +908,11 @@ async fn test_file_dependencies_task_skipping() -> Result<()> {
             input: vec!["config.txt".to_string()],
             output: vec!["generated.json".to_string()],
             envs: HashMap::new(),
-            params: HashMap::new(),
+            params: ParamSpecs::new(),
             help: None,
             foreach: None,
             virtual_parent: false,
+            ..Default::default()
         },
         temp_path,
     );
@@ -967,10 +976,11 @@ async fn test_file_dependencies_modification_detection() -> Result<()> {
             input: vec!["old_config.txt".to_string(), "new_config.txt".to_string()],
             output: vec!["result.txt".to_string()],
             envs: HashMap::new(),
-            params: HashMap::new(),
+            params: ParamSpecs::new(),
             help: None,
             foreach: None,
             virtual_parent: false,
+            ..Default::default()
         },
         &fixture.temp_path,
     );